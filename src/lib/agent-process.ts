@@ -0,0 +1,161 @@
+/**
+ * Child process runner shared by the agent backends
+ * Handles timeouts, stdout streaming and error normalisation
+ *
+ * @module agent-process
+ */
+
+import { StringDecoder } from "string_decoder";
+import { execa } from "execa";
+import type { ClineEvent } from "./cline-events";
+import { emitAgentEvent, getRunContext } from "./run-context";
+
+// Configuration constants
+export const DEFAULT_TIMEOUT = 300000; // 5 minutes
+
+/**
+ * Describes one agent CLI invocation
+ * @interface AgentProcessSpec
+ */
+export interface AgentProcessSpec {
+  /** Short backend label used in log lines */
+  label: string;
+  /** Executable to run */
+  command: string;
+  /** Arguments (passed without a shell) */
+  args: string[];
+  /** Text written to stdin; stdin is closed immediately when omitted */
+  input?: string;
+  /** Working directory */
+  cwd?: string;
+  /** Timeout in milliseconds (default: 300000) */
+  timeout?: number;
+  /** Extra environment variables */
+  env?: Record<string, string>;
+  /** Called with each stdout chunk as it arrives */
+  onStdout?: (chunk: string) => void;
+}
+
+/**
+ * Outcome of an agent CLI invocation
+ * @interface AgentProcessResult
+ */
+export interface AgentProcessResult {
+  /** Whether the process exited successfully */
+  success: boolean;
+  /** Captured stdout */
+  stdout: string;
+  /** Captured stderr */
+  stderr: string;
+  /** Error message if the process failed */
+  error?: string;
+}
+
+/**
+ * Runs an agent CLI and captures its output
+ *
+ * Never throws for process failures; non-zero exits, timeouts, cancellation
+ * and spawn errors are reported through `success: false`. The process is
+ * killed when the signal of the current run context is aborted.
+ *
+ * @param spec - Invocation details
+ * @returns Promise resolving to the captured result
+ * @public
+ */
+export async function runAgentProcess(spec: AgentProcessSpec): Promise<AgentProcessResult> {
+  const { signal } = getRunContext();
+  if (signal?.aborted) {
+    return { success: false, stdout: "", stderr: "", error: `${spec.label} run was cancelled` };
+  }
+
+  try {
+    console.log(`[AgentMesh] Running ${spec.label}: ${spec.command} ${spec.args.join(" ")}`);
+
+    const subprocess = execa(spec.command, spec.args, {
+      cwd: spec.cwd || process.cwd(),
+      timeout: spec.timeout || DEFAULT_TIMEOUT,
+      env: { ...process.env, ...spec.env },
+      cancelSignal: signal,
+      ...(spec.input !== undefined ? { input: spec.input } : { stdin: "ignore" as const }),
+    });
+    if (spec.onStdout) {
+      const onStdout = spec.onStdout;
+      // Characters split across chunks are held back until their last byte arrives
+      const decoder = new StringDecoder("utf8");
+      subprocess.stdout?.on("data", (chunk: Buffer) => onStdout(decoder.write(chunk)));
+      subprocess.stdout?.on("end", () => {
+        const rest = decoder.end();
+        if (rest) onStdout(rest);
+      });
+    }
+
+    const { stdout, stderr } = await subprocess;
+    console.log(`[AgentMesh] ${spec.label} completed. Output length: ${stdout?.length || 0}`);
+
+    return { success: true, stdout: stdout || "", stderr: stderr || "" };
+  } catch (err) {
+    // Improved error handling with type guards
+    let errorMessage: string;
+    let stdoutStr: string = "";
+    let stderrStr: string = "";
+
+    if (err instanceof Error) {
+      errorMessage = err.message;
+      if ('stdout' in err) {
+        stdoutStr = String(err.stdout ?? "");
+      }
+      if ('stderr' in err) {
+        stderrStr = String(err.stderr ?? "");
+      }
+    } else {
+      errorMessage = String(err);
+    }
+
+    // Log detailed error information
+    console.error(`[AgentMesh] ${spec.label} execution failed:`, {
+      error: errorMessage,
+      stdout: stdoutStr,
+      stderr: stderrStr,
+      timestamp: new Date().toISOString()
+    });
+
+    return {
+      success: false,
+      stdout: stdoutStr,
+      stderr: stderrStr,
+      error: signal?.aborted ? `${spec.label} run was cancelled` : stderrStr || errorMessage,
+    };
+  }
+}
+
+/**
+ * Runs `<command> <args>` and returns trimmed stdout, or undefined on failure
+ *
+ * @param command - Executable to probe
+ * @param args - Version arguments (default: ["--version"])
+ * @public
+ */
+export async function probeVersion(command: string, args: string[] = ["--version"]): Promise<string | undefined> {
+  try {
+    const { stdout } = await execa(command, args, { timeout: 10000 });
+    return stdout.trim();
+  } catch {
+    return undefined;
+  }
+}
+
+/**
+ * Wraps plain-text agent output as a single completion event and publishes it
+ * to the current run context
+ *
+ * @param output - Final text printed by the agent
+ * @returns Event list for the result
+ * @public
+ */
+export function textEvents(output: string): ClineEvent[] {
+  const text = output.trim();
+  if (!text) return [];
+  const event: ClineEvent = { kind: "completion", text, raw: { type: "say", say: "completion_result", text } };
+  emitAgentEvent(event);
+  return [event];
+}
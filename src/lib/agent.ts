@@ -0,0 +1,148 @@
+/**
+ * Agent Backend Abstraction for AgentMesh
+ * Lets every tool run on top of any configured coding agent CLI
+ *
+ * Backends are declared in `.agentmesh/config.json`:
+ *
+ * ```json
+ * {
+ *   "defaultBackend": "cline",
+ *   "backends": {
+ *     "aider-sonnet": { "type": "aider", "model": "sonnet" },
+ *     "my-agent": { "type": "command", "path": "my-agent", "template": ["run", "--mode", "{mode}", "{prompt}"] }
+ *   }
+ * }
+ * ```
+ *
+ * @module agent
+ */
+
+import { loadConfig, type BackendConfig } from "./config";
+import type { ClineEvent } from "./cline-events";
+import { trackChanges, type ChangeSet } from "./changes";
+import { createClineBackend } from "./cline";
+import { createAiderBackend } from "./aider";
+import { createCodexBackend } from "./codex";
+import { createCommandBackend } from "./command-agent";
+import { resolveWorkspacePath } from "./workspace";
+
+/**
+ * Event emitted during an agent run. Backends without a structured stream
+ * report their output as a single `completion` event.
+ */
+export type AgentEvent = ClineEvent;
+
+/**
+ * Options for a single agent run
+ * @interface AgentOptions
+ */
+export interface AgentOptions {
+  /** Enable auto-approve mode (default: true) */
+  yolo?: boolean;
+  /** Execution mode - "act" or "plan" */
+  mode?: "act" | "plan";
+  /** Working directory for command execution */
+  cwd?: string;
+  /** Custom timeout in milliseconds (default: 300000) */
+  timeout?: number;
+  /** Snapshot the working directory around the run and report changed files */
+  trackChanges?: boolean;
+}
+
+/**
+ * Result of an agent run
+ * @interface AgentResult
+ */
+export interface AgentResult {
+  /** Whether the command executed successfully */
+  success: boolean;
+  /** Command output or processed response */
+  output: string;
+  /** Error message if command failed */
+  error?: string;
+  /** Every event the agent emitted, in order */
+  events: AgentEvent[];
+  /** Name of the backend that ran the task */
+  backend?: string;
+  /** Files the run changed (only with `trackChanges`) */
+  changes?: ChangeSet;
+}
+
+/**
+ * A coding agent AgentMesh can delegate tasks to
+ * @interface AgentBackend
+ */
+export interface AgentBackend {
+  /** Configured name (key in `backends`) */
+  readonly name: string;
+  /** Implementation type */
+  readonly type: BackendConfig["type"];
+  /** Executable the backend runs */
+  readonly command: string;
+  /** Whether the agent CLI is installed and runnable */
+  isInstalled(): Promise<boolean>;
+  /** Installed version, or "unknown" */
+  getVersion(): Promise<string>;
+  /** Runs a task prompt */
+  run(prompt: string, options: AgentOptions): Promise<AgentResult>;
+}
+
+const FACTORIES: Record<BackendConfig["type"], (name: string, config: BackendConfig) => AgentBackend> = {
+  cline: createClineBackend,
+  aider: createAiderBackend,
+  codex: createCodexBackend,
+  command: createCommandBackend,
+};
+
+/**
+ * Returns the backend with the given name, or the default backend
+ *
+ * @param name - Configured backend name
+ * @returns The backend
+ * @throws {Error} If no backend with that name is configured
+ * @public
+ */
+export function getBackend(name?: string): AgentBackend {
+  const config = loadConfig();
+  const backendName = name || config.defaultBackend;
+  const backendConfig = config.backends[backendName];
+  if (!backendConfig) {
+    const known = Object.keys(config.backends).join(", ");
+    throw new Error(`Unknown agent backend "${backendName}". Configured backends: ${known}`);
+  }
+  return FACTORIES[backendConfig.type](backendName, backendConfig);
+}
+
+/**
+ * Lists every configured backend
+ * @public
+ */
+export function listBackends(): AgentBackend[] {
+  return Object.keys(loadConfig().backends).map((name) => getBackend(name));
+}
+
+/**
+ * Runs a task on the selected agent backend
+ *
+ * @param prompt - The task prompt to execute
+ * @param options - Run options plus an optional backend name
+ * @returns Promise resolving to execution result
+ * @throws {Error} For invalid inputs or unknown backends
+ * @throws {WorkspaceError} If the working directory is outside the workspace roots
+ * @public
+ */
+export async function runAgentTask(
+  prompt: string,
+  options: AgentOptions & { backend?: string } = {}
+): Promise<AgentResult> {
+  const { backend: backendName, trackChanges: track, ...runOptions } = options;
+  const backend = getBackend(backendName);
+  const cwd = resolveWorkspacePath(runOptions.cwd || process.cwd());
+  const run = () => backend.run(prompt, { ...runOptions, cwd });
+
+  if (!track) {
+    return { ...(await run()), backend: backend.name };
+  }
+  const { result, changes } = await trackChanges(cwd, run);
+  return { ...result, backend: backend.name, changes };
+}
@@ -0,0 +1,58 @@
+/**
+ * Aider Backend for AgentMesh
+ * Runs tasks through `aider --message-file` in non-interactive mode
+ *
+ * @module aider
+ */
+
+import { applyPromptPolicy, withPromptFile } from "./prompt-policy";
+import { probeVersion, runAgentProcess, textEvents } from "./agent-process";
+import type { AgentBackend } from "./agent";
+import type { BackendConfig } from "./config";
+
+/**
+ * Creates the Aider implementation of the agent backend interface
+ *
+ * Mode mapping:
+ * - act: `--chat-mode code` (edits files)
+ * - plan: `--chat-mode ask` (answers without editing)
+ *
+ * @param name - Configured backend name
+ * @param config - Backend settings
+ * @returns Aider backend
+ * @public
+ */
+export function createAiderBackend(name: string, config: BackendConfig): AgentBackend {
+  const command = config.path || process.env.AIDER_PATH || "aider";
+
+  return {
+    name,
+    type: "aider",
+    command,
+    isInstalled: async () => (await probeVersion(command)) !== undefined,
+    getVersion: async () => (await probeVersion(command)) ?? "unknown",
+    async run(prompt, options) {
+      const checkedPrompt = applyPromptPolicy(prompt);
+      const args = [...(config.args ?? [])];
+      if (config.model) args.push("--model", config.model);
+      args.push("--chat-mode", options.mode === "plan" ? "ask" : "code");
+      if (options.yolo !== false) args.push("--yes-always");
+      args.push("--no-pretty", "--no-stream", "--no-auto-commits");
+
+      const run = await withPromptFile(checkedPrompt, (file) => runAgentProcess({
+        label: "Aider",
+        command,
+        args: [...args, "--message-file", file],
+        cwd: options.cwd,
+        timeout: options.timeout,
+      }));
+
+      return {
+        success: run.success,
+        output: run.stdout.trim() || (run.success ? "Task completed (no output)" : ""),
+        error: run.success ? run.stderr || undefined : run.error,
+        events: textEvents(run.stdout),
+      };
+    },
+  };
+}
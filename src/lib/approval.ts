@@ -0,0 +1,104 @@
+/**
+ * Human Approval for AgentMesh Workflows
+ * Pauses a workflow at a step marked `approval: true` until someone approves it
+ *
+ * The reviewer is reached in one of two ways:
+ * - background jobs: the job enters the "awaiting_approval" state and the
+ *   decision comes from the `approve_job` tool
+ * - direct tool calls: the client is asked through MCP elicitation
+ *
+ * When neither is available the step counts as not approved, so a workflow
+ * never runs past a gate unreviewed.
+ *
+ * @module approval
+ */
+
+import { z } from "zod";
+import type { ToolExtra } from "./progress";
+import { getRunContext, type ApprovalDecision, type ApprovalRequest } from "./run-context";
+
+// Longest step output shown in an elicitation message
+const MAX_SUMMARY_LENGTH = 4000;
+// How long a client may take to answer; the SDK default (60s) is too short for a human
+const APPROVAL_TIMEOUT = 24 * 60 * 60 * 1000;
+// JSON-RPC "method not found", returned by clients without elicitation
+const METHOD_NOT_FOUND = -32601;
+
+const NO_REVIEWER =
+  "this client cannot be asked for approval (no elicitation support). " +
+  "Run the workflow with start_job and answer with approve_job.";
+
+const elicitResultSchema = z.object({
+  action: z.enum(["accept", "decline", "cancel"]),
+  content: z.record(z.unknown()).optional(),
+}).passthrough();
+
+/**
+ * Message shown to the reviewer
+ *
+ * @param request - Approval request
+ * @returns Markdown text
+ * @public
+ */
+export function approvalMessage(request: ApprovalRequest): string {
+  const summary = request.summary.length > MAX_SUMMARY_LENGTH
+    ? `${request.summary.substring(0, MAX_SUMMARY_LENGTH)}\n...(truncated)`
+    : request.summary;
+  return `Workflow "${request.workflow}" finished step "${request.step}" and needs your approval to continue.
+
+${summary}
+
+Approve to continue. Reject with feedback to re-run the step, or without feedback to stop the workflow.`;
+}
+
+/**
+ * Asks the client to approve a step through MCP elicitation
+ * @internal
+ */
+async function elicitApproval(request: ApprovalRequest, extra: ToolExtra): Promise<ApprovalDecision> {
+  let result: z.infer<typeof elicitResultSchema>;
+  try {
+    result = await extra.sendRequest!({
+      method: "elicitation/create",
+      params: {
+        message: approvalMessage(request),
+        requestedSchema: {
+          type: "object",
+          properties: {
+            decision: { type: "string", title: "Decision", enum: ["approve", "reject"] },
+            feedback: { type: "string", title: "Feedback", description: "What to change (re-runs the step)" },
+          },
+          required: ["decision"],
+        },
+      },
+    }, elicitResultSchema, { signal: extra.signal, timeout: APPROVAL_TIMEOUT });
+  } catch (error) {
+    if ((error as { code?: number }).code === METHOD_NOT_FOUND) {
+      return { approved: false, reason: NO_REVIEWER };
+    }
+    throw error;
+  }
+
+  if (result.action === "decline") return { approved: false, reason: "the reviewer declined" };
+  if (result.action === "cancel") return { approved: false, reason: "the reviewer dismissed the approval request" };
+
+  const feedback = typeof result.content?.feedback === "string" && result.content.feedback.trim()
+    ? result.content.feedback.trim()
+    : undefined;
+  return { approved: result.content?.decision === "approve", feedback };
+}
+
+/**
+ * Asks a human to approve a workflow step
+ *
+ * @param request - Step to approve
+ * @param extra - Request extra of the tool call, for elicitation
+ * @returns The decision; `reason` explains a refusal nobody made
+ * @public
+ */
+export async function requestApproval(request: ApprovalRequest, extra?: ToolExtra): Promise<ApprovalDecision> {
+  const onApproval = getRunContext().onApproval;
+  if (onApproval) return onApproval(request);
+  if (extra?.sendRequest) return elicitApproval(request, extra);
+  return { approved: false, reason: NO_REVIEWER };
+}
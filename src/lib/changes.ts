@@ -0,0 +1,340 @@
+/**
+ * File Change Tracking for AgentMesh
+ * Snapshots a working tree before and after an agent run and reports what changed
+ *
+ * Inside a git repository the snapshot is a tree object written through a
+ * temporary index (so the real index and staging area are left alone) and
+ * the diff comes from git. Elsewhere files are compared by content hash and
+ * a unified diff is computed in-process.
+ *
+ * @module changes
+ */
+
+import * as fs from "fs";
+import * as os from "os";
+import * as path from "path";
+import { createHash } from "crypto";
+import { findRepoRoot, git } from "./git";
+
+// Largest diff included in tool output, in characters
+const MAX_DIFF_LENGTH = 20000;
+// Limits for hash snapshots of directories outside git
+export const MAX_SNAPSHOT_FILES = 20000;
+const MAX_TEXT_FILE_SIZE = 512 * 1024;
+// Directories never walked by hash snapshots
+const SKIPPED_DIRS = new Set([".git", "node_modules"]);
+// Lines of context around each diff hunk
+const CONTEXT_LINES = 3;
+
+/**
+ * Files changed by an agent run
+ * @interface ChangeSet
+ */
+export interface ChangeSet {
+  /** New files */
+  added: string[];
+  /** Files whose content changed */
+  modified: string[];
+  /** Removed files */
+  deleted: string[];
+  /** Unified diff of all changes */
+  diff: string;
+}
+
+interface FileEntry {
+  hash: string;
+  /** Content of small text files, kept for diffing */
+  text?: string;
+}
+
+/**
+ * Working tree state captured by `snapshotWorkspace`
+ */
+export type WorkspaceSnapshot =
+  | { kind: "git"; repoRoot: string; tree: string }
+  | { kind: "files"; root: string; files: Map<string, FileEntry> };
+
+/**
+ * Writes the current working tree (tracked and untracked, minus ignored files)
+ * as a git tree object without touching the real index
+ * @internal
+ */
+async function writeWorkingTree(repoRoot: string): Promise<string> {
+  const tmp = fs.mkdtempSync(path.join(os.tmpdir(), "agentmesh-index-"));
+  const env = { GIT_INDEX_FILE: path.join(tmp, "index") };
+  try {
+    // Start from a copy of the real index so unchanged files are not re-hashed
+    const realIndex = path.resolve(repoRoot, await git(["rev-parse", "--git-path", "index"], repoRoot));
+    if (fs.existsSync(realIndex)) fs.copyFileSync(realIndex, env.GIT_INDEX_FILE);
+    await git(["add", "-A"], repoRoot, env);
+    return await git(["write-tree"], repoRoot, env);
+  } finally {
+    fs.rmSync(tmp, { recursive: true, force: true });
+  }
+}
+
+function hashFiles(root: string): Map<string, FileEntry> | undefined {
+  const files = new Map<string, FileEntry>();
+  const walk = (dir: string): boolean => {
+    for (const entry of fs.readdirSync(dir, { withFileTypes: true })) {
+      const full = path.join(dir, entry.name);
+      if (entry.isDirectory()) {
+        if (!SKIPPED_DIRS.has(entry.name) && !walk(full)) return false;
+      } else if (entry.isFile()) {
+        if (files.size >= MAX_SNAPSHOT_FILES) return false;
+        const content = fs.readFileSync(full);
+        const isText = content.length <= MAX_TEXT_FILE_SIZE && !content.includes(0);
+        files.set(path.relative(root, full).split(path.sep).join("/"), {
+          hash: createHash("sha1").update(content).digest("hex"),
+          text: isText ? content.toString("utf8") : undefined,
+        });
+      }
+    }
+    return true;
+  };
+  return walk(root) ? files : undefined;
+}
+
+/**
+ * Captures the state of a working directory
+ *
+ * @param dir - Directory the agent runs in
+ * @returns Snapshot, or undefined if the directory is too large to hash
+ * @public
+ */
+export async function snapshotWorkspace(dir: string): Promise<WorkspaceSnapshot | undefined> {
+  const repoRoot = await findRepoRoot(dir);
+  if (repoRoot) {
+    return { kind: "git", repoRoot, tree: await writeWorkingTree(repoRoot) };
+  }
+  const files = hashFiles(dir);
+  if (!files) {
+    console.warn(`[AgentMesh] ${dir} has more than ${MAX_SNAPSHOT_FILES} files; change tracking skipped`);
+    return undefined;
+  }
+  return { kind: "files", root: dir, files };
+}
+
+/**
+ * Compares a snapshot with the current state of the same directory
+ *
+ * @param before - Snapshot taken before the run
+ * @returns Files added, modified and deleted, with a unified diff, or undefined
+ *   if the directory grew too large to hash
+ * @public
+ */
+export async function diffSnapshot(before: WorkspaceSnapshot): Promise<ChangeSet | undefined> {
+  const changes: ChangeSet = { added: [], modified: [], deleted: [], diff: "" };
+
+  if (before.kind === "git") {
+    const after = await writeWorkingTree(before.repoRoot);
+    if (after === before.tree) return changes;
+
+    const status = await git(["diff-tree", "-r", "--no-renames", "--name-status", before.tree, after], before.repoRoot);
+    for (const line of status.split("\n").filter(Boolean)) {
+      const [code, file] = line.split("\t");
+      if (code === "A") changes.added.push(file);
+      else if (code === "D") changes.deleted.push(file);
+      else changes.modified.push(file);
+    }
+    changes.diff = await git(["diff", "--no-renames", before.tree, after], before.repoRoot);
+    return changes;
+  }
+
+  const after = hashFiles(before.root);
+  if (!after) {
+    console.warn(`[AgentMesh] ${before.root} now has more than ${MAX_SNAPSHOT_FILES} files; change tracking skipped`);
+    return undefined;
+  }
+  const diffs: string[] = [];
+  const paths = [...new Set([...before.files.keys(), ...after.keys()])].sort();
+  for (const file of paths) {
+    const old = before.files.get(file);
+    const current = after.get(file);
+    if (old && current && old.hash === current.hash) continue;
+
+    if (!old) changes.added.push(file);
+    else if (!current) changes.deleted.push(file);
+    else changes.modified.push(file);
+    diffs.push(unifiedDiff(file, old, current));
+  }
+  changes.diff = diffs.join("\n");
+  return changes;
+}
+
+/**
+ * Runs a task and reports the files it changed in a directory
+ *
+ * Tracking failures are logged and reported as `undefined` changes; they
+ * never fail the task itself.
+ *
+ * @param dir - Directory to watch
+ * @param run - Task to run
+ * @returns Task result and change set
+ * @public
+ */
+export async function trackChanges<T>(dir: string, run: () => Promise<T>): Promise<{ result: T; changes?: ChangeSet }> {
+  const before = await snapshotWorkspace(dir).catch((error) => {
+    console.warn(`[AgentMesh] Change tracking failed:`, (error as Error).message);
+    return undefined;
+  });
+  const result = await run();
+  if (!before) return { result };
+
+  try {
+    return { result, changes: await diffSnapshot(before) };
+  } catch (error) {
+    console.warn(`[AgentMesh] Change tracking failed:`, (error as Error).message);
+    return { result };
+  }
+}
+
+/**
+ * Splits text into lines without a trailing empty line
+ * @internal
+ */
+function lines(text: string | undefined): string[] {
+  if (!text) return [];
+  const split = text.split("\n");
+  if (split[split.length - 1] === "") split.pop();
+  return split;
+}
+
+/**
+ * Builds a unified diff for one file of a hash snapshot
+ * @internal
+ */
+function unifiedDiff(file: string, before?: FileEntry, after?: FileEntry): string {
+  const header = `--- ${before ? `a/${file}` : "/dev/null"}\n+++ ${after ? `b/${file}` : "/dev/null"}`;
+  if ((before && before.text === undefined) || (after && after.text === undefined)) {
+    return `${header}\nBinary or large file changed`;
+  }
+
+  const a = lines(before?.text);
+  const b = lines(after?.text);
+  const ops = diffLines(a, b);
+
+  const hunks: string[] = [];
+  let i = 0;
+  while (i < ops.length) {
+    if (ops[i].op === " ") { i++; continue; }
+    // Grow the hunk while changes are separated by at most 2 * CONTEXT_LINES unchanged lines
+    const start = Math.max(0, i - CONTEXT_LINES);
+    let end = i;
+    while (end < ops.length) {
+      if (ops[end].op !== " ") { end++; continue; }
+      let run = end;
+      while (run < ops.length && ops[run].op === " ") run++;
+      if (run === ops.length || run - end > 2 * CONTEXT_LINES) break;
+      end = run;
+    }
+    end = Math.min(ops.length, end + CONTEXT_LINES);
+
+    const slice = ops.slice(start, end);
+    const aStart = ops[start].a;
+    const bStart = ops[start].b;
+    const aLen = slice.filter((o) => o.op !== "+").length;
+    const bLen = slice.filter((o) => o.op !== "-").length;
+    hunks.push(
+      `@@ -${aLen ? aStart + 1 : aStart},${aLen} +${bLen ? bStart + 1 : bStart},${bLen} @@\n` +
+      slice.map((o) => `${o.op}${o.text}`).join("\n")
+    );
+    i = end;
+  }
+  return `${header}\n${hunks.join("\n")}`;
+}
+
+interface DiffOp {
+  op: " " | "-" | "+";
+  text: string;
+  /** Index in the old file at this point */
+  a: number;
+  /** Index in the new file at this point */
+  b: number;
+}
+
+/**
+ * Line diff via longest common subsequence; very large files fall back to a
+ * full replacement
+ * @internal
+ */
+function diffLines(a: string[], b: string[]): DiffOp[] {
+  let prefix = 0;
+  while (prefix < a.length && prefix < b.length && a[prefix] === b[prefix]) prefix++;
+  let suffix = 0;
+  while (
+    suffix < a.length - prefix && suffix < b.length - prefix &&
+    a[a.length - 1 - suffix] === b[b.length - 1 - suffix]
+  ) suffix++;
+
+  const midA = a.slice(prefix, a.length - suffix);
+  const midB = b.slice(prefix, b.length - suffix);
+  const ops: DiffOp[] = [];
+  for (let k = 0; k < prefix; k++) ops.push({ op: " ", text: a[k], a: k, b: k });
+
+  let ai = prefix;
+  let bi = prefix;
+  if (midA.length * midB.length > 4_000_000) {
+    for (const text of midA) ops.push({ op: "-", text, a: ai++, b: bi });
+    for (const text of midB) ops.push({ op: "+", text, a: ai, b: bi++ });
+  } else {
+    // lcs[i][j] = LCS length of midA[i:] and midB[j:]
+    const lcs = Array.from({ length: midA.length + 1 }, () => new Uint32Array(midB.length + 1));
+    for (let i = midA.length - 1; i >= 0; i--) {
+      for (let j = midB.length - 1; j >= 0; j--) {
+        lcs[i][j] = midA[i] === midB[j] ? lcs[i + 1][j + 1] + 1 : Math.max(lcs[i + 1][j], lcs[i][j + 1]);
+      }
+    }
+    let i = 0;
+    let j = 0;
+    while (i < midA.length || j < midB.length) {
+      if (i < midA.length && j < midB.length && midA[i] === midB[j]) {
+        ops.push({ op: " ", text: midA[i], a: ai++, b: bi++ });
+        i++; j++;
+      } else if (i < midA.length && (j === midB.length || lcs[i + 1][j] >= lcs[i][j + 1])) {
+        ops.push({ op: "-", text: midA[i++], a: ai++, b: bi });
+      } else {
+        ops.push({ op: "+", text: midB[j++], a: ai, b: bi++ });
+      }
+    }
+  }
+
+  for (let k = a.length - suffix; k < a.length; k++) {
+    ops.push({ op: " ", text: a[k], a: ai++, b: bi++ });
+  }
+  return ops;
+}
+
+/**
+ * Shortens a diff for tool output
+ *
+ * @param diff - Unified diff
+ * @returns The diff, truncated to a readable length
+ * @public
+ */
+export function truncateDiff(diff: string): string {
+  return diff.length > MAX_DIFF_LENGTH ? `${diff.substring(0, MAX_DIFF_LENGTH)}\n...(truncated)` : diff;
+}
+
+/**
+ * Formats a change set for tool output
+ *
+ * @param changes - Change set from an agent run
+ * @returns Markdown section, or an empty string when nothing was tracked
+ * @public
+ */
+export function formatChanges(changes: ChangeSet | undefined): string {
+  if (!changes) return "";
+  const total = changes.added.length + changes.modified.length + changes.deleted.length;
+  if (total === 0) {
+    return "\n\n### Changed Files\n\nNo files changed.";
+  }
+
+  const list = [
+    ...changes.added.map((file) => `- ➕ ${file}`),
+    ...changes.modified.map((file) => `- ✏️ ${file}`),
+    ...changes.deleted.map((file) => `- 🗑️ ${file}`),
+  ].join("\n");
+
+  return `\n\n### Changed Files (${total})\n\n${list}\n\n\`\`\`diff\n${truncateDiff(changes.diff)}\n\`\`\``;
+}
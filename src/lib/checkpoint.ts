@@ -0,0 +1,186 @@
+/**
+ * Workspace Checkpoints for AgentMesh Workflows
+ * Records the working tree before workflow steps and rolls it back when a
+ * workflow fails
+ *
+ * A checkpoint is a commit of the whole working tree (tracked and untracked,
+ * minus ignored files) made without touching the index, HEAD or any branch.
+ * It is kept under `refs/agentmesh/checkpoints/<run>/` so it survives garbage
+ * collection. Rolling back also moves HEAD back when steps committed
+ * (autoCommit), and saves the discarded state as one more checkpoint first so
+ * nothing is lost.
+ *
+ * @module checkpoint
+ */
+
+import * as fs from "fs";
+import * as os from "os";
+import * as path from "path";
+import { snapshotWorkspace } from "./changes";
+import { git, identityArgs } from "./git";
+
+/** Ref namespace for checkpoints */
+export const CHECKPOINT_REF_PREFIX = "refs/agentmesh/checkpoints/";
+
+/**
+ * A recorded state of the working tree
+ * @interface Checkpoint
+ */
+export interface Checkpoint {
+  /** What the checkpoint precedes, e.g. "step 2 (Fix)" */
+  label: string;
+  repoRoot: string;
+  /** Commit checked out when the checkpoint was taken (unset before the first commit) */
+  head?: string;
+  /** Tree of the working tree */
+  tree: string;
+  /** Checkpoint commit */
+  commit: string;
+  /** Ref holding the checkpoint commit */
+  ref: string;
+}
+
+/**
+ * What a rollback reverted
+ * @interface RollbackReport
+ */
+export interface RollbackReport {
+  checkpoint: Checkpoint;
+  /** Files created after the checkpoint, now removed */
+  removed: string[];
+  /** Files changed after the checkpoint, now restored */
+  reverted: string[];
+  /** Files deleted after the checkpoint, now restored */
+  restored: string[];
+  /** Commits HEAD moved back over ("sha subject") */
+  commits: string[];
+  /** Checkpoint holding the state that was discarded */
+  discarded: Checkpoint;
+}
+
+async function headCommit(repoRoot: string): Promise<string | undefined> {
+  try {
+    return await git(["rev-parse", "-q", "--verify", "HEAD"], repoRoot);
+  } catch {
+    return undefined;
+  }
+}
+
+/**
+ * Records the working tree containing a directory
+ *
+ * @param dir - Any directory inside the repository
+ * @param ref - Ref name below `refs/agentmesh/checkpoints/`
+ * @param label - What the checkpoint precedes
+ * @returns The checkpoint
+ * @throws {Error} If the directory is not inside a git repository
+ * @public
+ */
+export async function createCheckpoint(dir: string, ref: string, label: string): Promise<Checkpoint> {
+  const snapshot = await snapshotWorkspace(dir);
+  if (snapshot?.kind !== "git") {
+    throw new Error(`Checkpoints need a git repository, but ${dir} is not inside one`);
+  }
+  const { repoRoot, tree } = snapshot;
+  const head = await headCommit(repoRoot);
+  const commit = await git([
+    ...(await identityArgs(repoRoot)),
+    "commit-tree", tree, ...(head ? ["-p", head] : []), "-m", `AgentMesh checkpoint before ${label}`,
+  ], repoRoot);
+  const fullRef = `${CHECKPOINT_REF_PREFIX}${ref}`;
+  await git(["update-ref", fullRef, commit], repoRoot);
+  return { label, repoRoot, head, tree, commit, ref: fullRef };
+}
+
+/**
+ * Puts the working tree (and HEAD, if it moved) back to a checkpoint
+ *
+ * @param checkpoint - Checkpoint to restore
+ * @param discardedRef - Ref name below `refs/agentmesh/checkpoints/` for the state being discarded
+ * @returns What was reverted
+ * @public
+ */
+export async function restoreCheckpoint(checkpoint: Checkpoint, discardedRef: string): Promise<RollbackReport> {
+  const { repoRoot } = checkpoint;
+  const discarded = await createCheckpoint(repoRoot, discardedRef, `rolling back to ${checkpoint.label}`);
+  const report: RollbackReport = { checkpoint, removed: [], reverted: [], restored: [], commits: [], discarded };
+
+  if (discarded.head !== checkpoint.head && discarded.head) {
+    const range = checkpoint.head ? `${checkpoint.head}..${discarded.head}` : discarded.head;
+    const log = await git(["log", "--format=%h %s", range], repoRoot);
+    report.commits = log.split("\n").filter(Boolean);
+    if (checkpoint.head) {
+      // Mixed reset: moves the branch and index, the files are handled below
+      await git(["reset", "-q", checkpoint.head], repoRoot);
+    } else {
+      // The repository had no commits yet: unborn the branch again and empty the index
+      await git(["update-ref", "-d", "HEAD"], repoRoot);
+      await git(["read-tree", "--empty"], repoRoot);
+    }
+  }
+
+  if (discarded.tree === checkpoint.tree) {
+    if (!report.commits.length) await dropCheckpoints([discarded]);
+    return report;
+  }
+  const status = await git(["diff-tree", "-r", "--no-renames", "--name-status", checkpoint.tree, discarded.tree], repoRoot);
+  for (const line of status.split("\n").filter(Boolean)) {
+    const [code, file] = line.split("\t");
+    if (code === "A") report.removed.push(file);
+    else if (code === "D") report.restored.push(file);
+    else report.reverted.push(file);
+  }
+
+  for (const file of report.removed) {
+    fs.rmSync(path.join(repoRoot, file), { force: true });
+  }
+  const files = [...report.reverted, ...report.restored];
+  if (files.length) {
+    // Write the files from the checkpoint tree through a temporary index
+    const tmp = fs.mkdtempSync(path.join(os.tmpdir(), "agentmesh-index-"));
+    const env = { GIT_INDEX_FILE: path.join(tmp, "index") };
+    try {
+      await git(["read-tree", checkpoint.tree], repoRoot, env);
+      await git(["checkout-index", "-f", "--", ...files], repoRoot, env);
+    } finally {
+      fs.rmSync(tmp, { recursive: true, force: true });
+    }
+  }
+  return report;
+}
+
+/**
+ * Deletes checkpoint refs
+ *
+ * @param checkpoints - Checkpoints to forget
+ * @public
+ */
+export async function dropCheckpoints(checkpoints: Checkpoint[]): Promise<void> {
+  for (const checkpoint of checkpoints) {
+    await git(["update-ref", "-d", checkpoint.ref], checkpoint.repoRoot);
+  }
+}
+
+/**
+ * Formats a rollback for tool output
+ *
+ * @param report - Rollback report
+ * @returns Markdown section
+ * @public
+ */
+export function formatRollback(report: RollbackReport): string {
+  const files = [
+    ...report.removed.map((file) => `- 🗑️ ${file} (added, removed)`),
+    ...report.reverted.map((file) => `- ↩️ ${file} (modified, reverted)`),
+    ...report.restored.map((file) => `- ♻️ ${file} (deleted, restored)`),
+  ];
+  let text = `\n### Rollback\n\n↩️ Rolled back to the checkpoint before ${report.checkpoint.label}\n`;
+  text += files.length ? `\n${files.join("\n")}\n` : "\nNo files needed reverting.\n";
+  if (report.commits.length) {
+    text += `\nCommits undone:\n${report.commits.map((c) => `- ${c}`).join("\n")}\n`;
+  }
+  if (files.length || report.commits.length) {
+    text += `\n💾 The discarded state is kept at \`${report.discarded.ref}\` (${report.discarded.commit.substring(0, 12)}).\n`;
+  }
+  return text;
+}
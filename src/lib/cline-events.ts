@@ -0,0 +1,288 @@
+/**
+ * Cline JSON Event Stream Parser for AgentMesh
+ * Incrementally parses the output of `cline --output-format json` into typed events
+ *
+ * Cline writes one JSON message per line, but long messages may be pretty-printed
+ * across lines and stdout can contain log noise between messages. The parser scans
+ * for balanced top-level objects (respecting strings and escapes) instead of
+ * splitting on newlines, so it copes with all three.
+ *
+ * @module cline-events
+ */
+
+/**
+ * Raw message as emitted by Cline (`say` / `ask` messages)
+ * @interface ClineMessage
+ */
+export interface ClineMessage {
+  type?: string;
+  say?: string;
+  ask?: string;
+  text?: string;
+  ts?: number;
+  partial?: boolean;
+  [key: string]: unknown;
+}
+
+/**
+ * Kinds of event AgentMesh distinguishes in a Cline run
+ */
+export type ClineEventKind =
+  | "text"
+  | "reasoning"
+  | "tool_use"
+  | "command"
+  | "command_output"
+  | "plan"
+  | "completion"
+  | "error"
+  | "other";
+
+/**
+ * A single typed event from a Cline run
+ * @interface ClineEvent
+ */
+export interface ClineEvent {
+  /** Event classification */
+  kind: ClineEventKind;
+  /** Fully unescaped text payload (answer text, command line, error message...) */
+  text: string;
+  /** Tool name for `tool_use` events (e.g. "editedExistingFile") */
+  tool?: string;
+  /** File path touched by a `tool_use` event, if reported */
+  path?: string;
+  /** Cline message timestamp, used to collapse partial updates */
+  ts?: number;
+  /** The original message */
+  raw: ClineMessage;
+}
+
+/**
+ * Parses a JSON string field that may itself contain JSON (tool and plan payloads)
+ * @internal
+ */
+function parseNested(text: string | undefined): Record<string, unknown> | undefined {
+  if (!text) return undefined;
+  try {
+    const value = JSON.parse(text);
+    return value && typeof value === "object" ? value : undefined;
+  } catch {
+    return undefined;
+  }
+}
+
+/**
+ * Maps a raw Cline message to a typed event
+ *
+ * @param message - Parsed Cline message
+ * @returns Typed event
+ * @public
+ */
+export function toClineEvent(message: ClineMessage): ClineEvent {
+  const text = typeof message.text === "string" ? message.text : "";
+  const base = { text, ts: message.ts, raw: message };
+  const kind = message.say ?? message.ask;
+
+  switch (kind) {
+    case "text":
+      return { ...base, kind: "text" };
+    case "reasoning":
+      return { ...base, kind: "reasoning" };
+    case "completion_result":
+      return { ...base, kind: "completion" };
+    case "plan_mode_respond": {
+      const payload = parseNested(text);
+      const response = typeof payload?.response === "string" ? payload.response : text;
+      return { ...base, kind: "plan", text: response };
+    }
+    case "tool": {
+      const payload = parseNested(text);
+      return {
+        ...base,
+        kind: "tool_use",
+        tool: typeof payload?.tool === "string" ? payload.tool : undefined,
+        path: typeof payload?.path === "string" ? payload.path : undefined,
+      };
+    }
+    case "command":
+      return { ...base, kind: "command" };
+    case "command_output":
+      return { ...base, kind: "command_output" };
+    case "error":
+    case "api_req_failed":
+    case "mistake_limit_reached":
+      return { ...base, kind: "error" };
+    default:
+      if (message.type === "error") {
+        return { ...base, kind: "error", text: text || String(message.message ?? "") };
+      }
+      return { ...base, kind: "other" };
+  }
+}
+
+/**
+ * Incremental parser for Cline's JSON output stream
+ *
+ * Feed stdout chunks with `push()` as they arrive and call `end()` once the
+ * process exits. Partial (streaming) updates of a message replace the previous
+ * event with the same timestamp, so the event list only holds final texts.
+ *
+ * @example
+ * const parser = new ClineEventParser((event) => console.log(event.kind));
+ * child.stdout.on("data", (chunk) => parser.push(chunk.toString()));
+ * const events = parser.end();
+ * @public
+ */
+export class ClineEventParser {
+  private buffer = "";
+  private start = -1;
+  private depth = 0;
+  private inString = false;
+  private escaped = false;
+  private scanned = 0;
+  private readonly collected: ClineEvent[] = [];
+  /** Fragments that looked like JSON objects but failed to parse */
+  readonly malformed: string[] = [];
+
+  constructor(private readonly onEvent?: (event: ClineEvent) => void) {}
+
+  /**
+   * Events parsed so far
+   */
+  get events(): readonly ClineEvent[] {
+    return this.collected;
+  }
+
+  /**
+   * Feeds a chunk of stdout into the parser
+   *
+   * @param chunk - Raw output text
+   */
+  push(chunk: string): void {
+    this.buffer += chunk;
+
+    for (let i = this.scanned; i < this.buffer.length; i++) {
+      const ch = this.buffer[i];
+
+      if (this.depth === 0) {
+        if (ch === "{") {
+          this.start = i;
+          this.depth = 1;
+          this.inString = false;
+          this.escaped = false;
+        }
+        continue;
+      }
+
+      if (this.inString) {
+        if (ch === "\n") {
+          // JSON strings cannot contain raw newlines: the object is truncated,
+          // so drop it and resynchronise on the next line
+          this.malformed.push(this.buffer.slice(this.start, i));
+          this.depth = 0;
+          this.inString = false;
+          this.start = -1;
+        } else if (this.escaped) this.escaped = false;
+        else if (ch === "\\") this.escaped = true;
+        else if (ch === '"') this.inString = false;
+        continue;
+      }
+
+      if (ch === '"') this.inString = true;
+      else if (ch === "{") this.depth++;
+      else if (ch === "}" && --this.depth === 0) {
+        this.emit(this.buffer.slice(this.start, i + 1));
+        this.start = -1;
+      }
+    }
+
+    // Drop everything that can no longer be part of an object
+    if (this.depth === 0) {
+      this.buffer = "";
+      this.scanned = 0;
+    } else {
+      this.buffer = this.buffer.slice(this.start);
+      this.scanned = this.buffer.length;
+      this.start = 0;
+    }
+  }
+
+  /**
+   * Flushes the parser at end of stream
+   *
+   * @returns All parsed events
+   */
+  end(): ClineEvent[] {
+    if (this.depth > 0 && this.buffer.trim()) {
+      this.malformed.push(this.buffer);
+    }
+    this.buffer = "";
+    this.scanned = 0;
+    this.depth = 0;
+    return [...this.collected];
+  }
+
+  private emit(fragment: string): void {
+    let message: unknown;
+    try {
+      message = JSON.parse(fragment);
+    } catch {
+      this.malformed.push(fragment);
+      return;
+    }
+    if (!message || typeof message !== "object" || Array.isArray(message)) {
+      this.malformed.push(fragment);
+      return;
+    }
+
+    const event = toClineEvent(message as ClineMessage);
+    const last = this.collected[this.collected.length - 1];
+    if (last && event.ts !== undefined && last.ts === event.ts && last.kind === event.kind) {
+      // Streaming update of the same message
+      this.collected[this.collected.length - 1] = event;
+    } else {
+      this.collected.push(event);
+    }
+    this.onEvent?.(event);
+  }
+}
+
+/**
+ * Parses a complete Cline output buffer
+ *
+ * @param stdout - Full stdout of a Cline run
+ * @returns Parsed events and any malformed fragments
+ * @public
+ */
+export function parseClineOutput(stdout: string): { events: ClineEvent[]; malformed: string[] } {
+  const parser = new ClineEventParser();
+  parser.push(stdout);
+  const events = parser.end();
+  return { events, malformed: parser.malformed };
+}
+
+/**
+ * Picks the answer text from a run's events
+ *
+ * Preference order: last completion result, last plan-mode response, then the
+ * longest substantial text message.
+ *
+ * @param events - Events from a Cline run
+ * @returns The answer text, or undefined if the run produced none
+ * @public
+ */
+export function extractAnswer(events: readonly ClineEvent[]): string | undefined {
+  const lastOf = (kind: ClineEventKind) =>
+    [...events].reverse().find((e) => e.kind === kind && e.text.trim().length > 0);
+
+  const completion = lastOf("completion");
+  if (completion) return completion.text;
+
+  const plan = lastOf("plan");
+  if (plan) return plan.text;
+
+  const texts = events
+    .filter((e) => e.kind === "text" && e.text.trim().length > 50)
+    .sort((a, b) => b.text.length - a.text.length);
+  return texts[0]?.text;
+}
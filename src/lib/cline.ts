@@ -3,11 +3,11 @@
  * Provides programmatic access to Cline's autonomous coding capabilities
  * 
  * Security Features:
- * - Input sanitization using whitelist approach
- * - Path traversal protection
+ * - Prompts delivered on stdin, never through a shell or argv
+ * - Explicit prompt policy (control characters, length limits)
+ * - Working directories confined to the workspace roots (see workspace module)
  * - Environment variable validation
- * - Regex timeout protection
- * - Input length limits
+ * - Structured parsing of Cline's JSON event stream
  * - Comprehensive error handling
  * 
  * @module cline
@@ -15,9 +15,14 @@
 
 import { execa, type ExecaError } from "execa";
 import * as fs from 'fs';
+import { ClineEventParser, extractAnswer } from "./cline-events";
+import { runAgentProcess } from "./agent-process";
+import { emitAgentEvent } from "./run-context";
+import { applyPromptPolicy } from "./prompt-policy";
+import type { AgentBackend, AgentOptions, AgentResult } from "./agent";
+import type { BackendConfig } from "./config";
 
 // Configuration constants
-const DEFAULT_TIMEOUT = 300000; // 5 minutes
 // Validate NODE_VERSION environment variable
 const NODE_VERSION = (() => {
   const version = process.env.NODE_VERSION;
@@ -31,111 +36,39 @@ const NODE_VERSION = (() => {
   
   return version;
 })();
-const OUTPUT_REGEX = {
-  COMPLETION: /"say"\s*:\s*"completion_result"[^}]*"text"\s*:\s*"([^"]+)"/,
-  PLAN_MODE: /"response"\s*:\s*"((?:[^"\\]|\\.)*)"/,
-  TEXT_RESPONSE: /"say"\s*:\s*"text"[^}]*?"text"\s*:\s*"([^"]+)"/g
-};
 
 /**
  * Configuration options for Cline CLI execution
- * @interface ClineOptions
  */
-export interface ClineOptions {
-  /** Enable auto-approve mode (default: true) */
-  yolo?: boolean;
-  /** Execution mode - "act" or "plan" */
-  mode?: "act" | "plan";
-  /** Working directory for command execution */
-  cwd?: string;
-  /** Custom timeout in milliseconds (default: 300000) */
-  timeout?: number;
-}
+export type ClineOptions = AgentOptions;
 
 /**
  * Result of a Cline CLI execution
- * @interface ClineResult
- */
-export interface ClineResult {
-  /** Whether the command executed successfully */
-  success: boolean;
-  /** Command output or processed response */
-  output: string;
-  /** Error message if command failed */
-  error?: string;
-}
-
-/**
- * Internal type for regex worker messaging
- * @internal
  */
-type RegexWorkerMessage = {
-  regex: RegExp;
-  text: string;
-};
+export type ClineResult = AgentResult;
 
 // Use full path to cline if nvm is used
 const CLINE_PATH = process.env.CLINE_PATH || "cline";
 
 /**
- * Validates a file path and protects against path traversal attacks
+ * Checks that an executable path exists
  * 
- * Security measures:
- * - Resolves and normalizes paths to prevent directory traversal
- * - Checks for '..' segments in paths
- * - Validates file existence
- * - Logs security-related failures
+ * Workspace paths are checked separately by the workspace module; this only
+ * guards the Cline executable lookup.
  * 
  * @param path - The file path to validate
- * @returns boolean indicating if path is safe and exists
+ * @returns boolean indicating if the path exists
  * @internal
  */
 function validatePath(path: string): boolean {
   try {
-    const resolvedPath = require('path').resolve(path);
-    const normalizedPath = require('path').normalize(path);
-    
-    // Protect against path traversal
-    if (normalizedPath.includes('..') || resolvedPath.includes('..')) {
-      console.error(`[AgentMesh] Path traversal attempt detected: ${path}`);
-      return false;
-    }
-    
-    return fs.existsSync(resolvedPath);
+    return fs.existsSync(require('path').resolve(path));
   } catch (error) {
     console.error(`[AgentMesh] Path validation failed:`, error);
     return false;
   }
 }
 
-/**
- * Sanitizes user input to prevent command injection and other attacks
- * 
- * Security measures:
- * - Whitelist approach for allowed characters
- * - Input type validation
- * - Input length limits to prevent DoS
- * - Throws errors for invalid inputs
- * 
- * @param input - The user input to sanitize
- * @returns Sanitized string safe for command execution
- * @throws {Error} If input is invalid or exceeds length limit
- * @internal
- */
-function sanitizeInput(input: string): string {
-  if (typeof input !== 'string') {
-    throw new Error('[AgentMesh] Invalid input type');
-  }
-  
-  // Limit input length to prevent DoS
-  const MAX_INPUT_LENGTH = 10000;
-  if (input.length > MAX_INPUT_LENGTH) {
-    throw new Error('[AgentMesh] Input exceeds maximum length');
-  }
-  
-  return input.replace(/[^a-zA-Z0-9\s\-_.,@/]/g, '');
-}
-
 /**
  * Checks if Cline CLI is installed and accessible
  * 
@@ -212,31 +145,33 @@ function getClinePath(): string {
  * Executes a task using the Cline CLI with comprehensive security measures
  * 
  * Security features:
- * - Input sanitization and validation
- * - Path validation
- * - Regex timeout protection
+ * - Prompt policy validation, prompt passed on stdin
+ * - Working directory checked against the workspace roots by runAgentTask
+ * - Structured parsing of the JSON event stream
  * - Error handling and logging
  * - Environment variable validation
  * 
  * @param prompt - The task prompt to execute
  * @param options - Configuration options for execution
+ * @param backendConfig - Backend settings (executable path, extra args)
  * @returns Promise resolving to execution result
- * @throws {Error} For invalid inputs or security violations
+ * @throws {Error} For invalid inputs
+ * @throws {PromptPolicyError} If the prompt violates the prompt policy
  * @public
  */
 export async function runClineTask(
   prompt: string,
-  options: ClineOptions = {}
+  options: ClineOptions = {},
+  backendConfig: Partial<BackendConfig> = {}
 ): Promise<ClineResult> {
   // Input validation
   if (!prompt || typeof prompt !== 'string') {
     throw new Error('Invalid prompt parameter');
   }
   
-  // Sanitize input
-  const sanitizedPrompt = sanitizeInput(prompt);
-  const clinePath = getClinePath();
-  const args: string[] = [];
+  const checkedPrompt = applyPromptPolicy(prompt);
+  const clinePath = backendConfig.path || getClinePath();
+  const args: string[] = [...(backendConfig.args ?? [])];
 
   // Add flags
   if (options.yolo !== false) args.push("-y"); // Default to yolo mode
@@ -244,94 +179,82 @@ export async function runClineTask(
   args.push("--output-format", "json"); // JSON output for parsing
   args.push("--oneshot"); // Complete after single response (avoid rate limits)
 
-  // Add sanitized prompt
-  args.push(sanitizedPrompt);
+  const parser = new ClineEventParser(emitAgentEvent);
+  const run = await runAgentProcess({
+    label: "Cline",
+    command: clinePath,
+    args,
+    // Prompt goes on stdin so it arrives byte-for-byte
+    input: checkedPrompt,
+    cwd: options.cwd,
+    timeout: options.timeout,
+    // Force plain output for easier parsing
+    env: { CLINE_OUTPUT_FORMAT: "plain" },
+    onStdout: (chunk) => parser.push(chunk),
+  });
+  const events = parser.end();
 
-  try {
-    console.log(`[AgentMesh] Running: ${clinePath} ${args.join(" ")}`);
-    
-    const { stdout, stderr } = await execa(clinePath, args, {
-      cwd: options.cwd || process.cwd(),
-      timeout: options.timeout || DEFAULT_TIMEOUT,
-      env: {
-        ...process.env,
-        // Force plain output for easier parsing
-        CLINE_OUTPUT_FORMAT: "plain",
-      },
-    });
+  if (parser.malformed.length > 0) {
+    console.warn(`[AgentMesh] Skipped ${parser.malformed.length} malformed JSON fragment(s) in Cline output`);
+  }
 
-    console.log(`[AgentMesh] Cline completed. Output length: ${stdout?.length || 0}`);
-    
-    // Extract the actual response from Cline's JSON output
-    let output = stdout || "Task completed (no output)";
-    
-    // Try to find the completion_result or plan_mode_respond in the output
-    try {
-      // Look for completion_result
-      const completionMatch = stdout.match(OUTPUT_REGEX.COMPLETION);
-      if (completionMatch) {
-        output = completionMatch[1].replace(/\\n/g, '\n').replace(/\\"/g, '"');
-      } else {
-        // Look for plan_mode_respond with the actual response
-        const planMatch = stdout.match(OUTPUT_REGEX.PLAN_MODE);
-        if (planMatch) {
-          output = planMatch[1].replace(/\\n/g, '\n').replace(/\\"/g, '"').replace(/\\\\/g, '\\');
-        } else {
-          // Try to find any meaningful text response
-          const textMatches = stdout.match(OUTPUT_REGEX.TEXT_RESPONSE);
-          if (textMatches && textMatches.length > 0) {
-            // Get the longest text response
-            const texts = textMatches.map((m: string) => {
-              const match = m.match(/"text"\s*:\s*"([^"]+)"/);
-              return match ? match[1] : '';
-            }).filter((t: string) => t.length > 50);
-            if (texts.length > 0) {
-              output = texts.sort((a: string, b: string) => b.length - a.length)[0]
-                .replace(/\\n/g, '\n').replace(/\\"/g, '"');
-            }
-          }
-        }
-      }
-    } catch {
-      // Parsing failed, use raw output
-      output = stdout.substring(0, 2000) + (stdout.length > 2000 ? '...' : '');
-    }
-    
-    return {
-      success: true,
-      output,
-      error: stderr || undefined,
-    };
-  } catch (err) {
-    // Improved error handling with type guards
-    let errorMessage: string;
-    let stdoutStr: string = "";
-    let stderrStr: string = "";
-    
-    if (err instanceof Error) {
-      errorMessage = err.message;
-      if ('stdout' in err) {
-        stdoutStr = String(err.stdout ?? "");
-      }
-      if ('stderr' in err) {
-        stderrStr = String(err.stderr ?? "");
-      }
-    } else {
-      errorMessage = String(err);
-    }
-    
-    // Log detailed error information
-    console.error('[AgentMesh] Cline execution failed:', {
-      error: errorMessage,
-      stdout: stdoutStr,
-      stderr: stderrStr,
-      timestamp: new Date().toISOString()
-    });
-    
+  if (!run.success) {
     return {
       success: false,
-      output: stdoutStr,
-      error: stderrStr || errorMessage,
+      output: extractAnswer(events) ?? run.stdout,
+      error: run.error,
+      events,
     };
   }
-}
\ No newline at end of file
+
+  // Extract the actual response from Cline's event stream
+  let output = extractAnswer(events);
+  if (output === undefined) {
+    // No recognisable answer, fall back to raw output
+    output = run.stdout
+      ? run.stdout.substring(0, 2000) + (run.stdout.length > 2000 ? '...' : '')
+      : "Task completed (no output)";
+  }
+
+  return {
+    success: true,
+    output,
+    error: run.stderr || undefined,
+    events,
+  };
+}
+
+/**
+ * Creates the Cline implementation of the agent backend interface
+ *
+ * @param name - Configured backend name
+ * @param config - Backend settings
+ * @returns Cline backend
+ * @public
+ */
+export function createClineBackend(name: string, config: BackendConfig): AgentBackend {
+  return {
+    name,
+    type: "cline",
+    command: config.path || getClinePath(),
+    async isInstalled() {
+      if (!config.path) return isClineInstalled();
+      try {
+        await execa(config.path, ["version"]);
+        return true;
+      } catch {
+        return false;
+      }
+    },
+    async getVersion() {
+      if (!config.path) return getClineVersion();
+      try {
+        const { stdout } = await execa(config.path, ["version"]);
+        return stdout.trim();
+      } catch {
+        return "unknown";
+      }
+    },
+    run: (prompt, options) => runClineTask(prompt, options, config),
+  };
+}
@@ -0,0 +1,179 @@
+/**
+ * Structured Results of kestra_code_intel
+ * The data every `kestra_code_intel` action returns as MCP structured
+ * content, next to the markdown report rendered from it
+ *
+ * Kestra flows and other agents read `structuredContent` instead of parsing
+ * the markdown. The schema is published as the tool's output schema, and
+ * every result is validated against it before it is returned.
+ *
+ * @module code-intel-result
+ */
+
+import { z } from "zod";
+import { HEALTH_LEVELS, METRICS, SEVERITIES, type MetricName } from "./health-rules";
+
+/** Actions of `kestra_code_intel` */
+export const CODE_INTEL_ACTIONS = [
+  "analyze-repo",      // Summarize repo health using Kestra AI
+  "analyze-local",     // Summarize the health of a local checkout from git alone
+  "setup-workflow",    // Generate Kestra workflow YAML
+  "process-summary",   // Process AI summary and decide actions
+  "execute-decision",  // Execute Cline based on AI decision
+  "deploy-workflow",   // Validate and save the workflow in Kestra
+  "list-flows",        // List the flows of a namespace
+  "delete-flow",       // Delete a flow
+  "fetch-results",     // Read results flows reported through kestra_callback
+] as const;
+
+export type CodeIntelAction = typeof CODE_INTEL_ACTIONS[number];
+
+const metricName = z.enum(Object.keys(METRICS) as [MetricName, ...MetricName[]]);
+
+const repositorySchema = z.object({
+  /** Forge the data came from, or "local" for a checkout */
+  source: z.enum(["github", "gitlab", "gitea", "local"]),
+  name: z.string(),
+  /** "owner/name" on the forge */
+  fullName: z.string().optional(),
+  url: z.string().optional(),
+  description: z.string().optional(),
+  language: z.string().optional(),
+  defaultBranch: z.string().optional(),
+  stars: z.number().optional(),
+  /** Checkout root and checked-out branch, for local analysis */
+  root: z.string().optional(),
+  branch: z.string().optional(),
+});
+
+const prioritySchema = z.object({
+  level: z.enum(SEVERITIES),
+  item: z.string(),
+  /** Tool that addresses it */
+  action: z.string().optional(),
+});
+
+const ruleOutcomeSchema = z.object({
+  id: z.string(),
+  severity: z.enum(SEVERITIES),
+  fired: z.boolean(),
+  /** Points taken off the score */
+  contribution: z.number(),
+  explanation: z.string(),
+  /** Metrics (or "text") the rule needed but did not get */
+  missing: z.array(z.string()),
+  tool: z.string().optional(),
+});
+
+const healthSchema = z.object({
+  score: z.number().min(0).max(100),
+  level: z.enum(HEALTH_LEVELS),
+  /** Rules file, or "built-in" */
+  source: z.string(),
+  rules: z.array(ruleOutcomeSchema),
+});
+
+const toolCallSchema = z.object({
+  /** Rule that chose the tool */
+  rule: z.string(),
+  severity: z.enum(SEVERITIES),
+  description: z.string(),
+  tool: z.string(),
+  arguments: z.record(z.unknown()),
+});
+
+const kestraSchema = z.object({
+  url: z.string(),
+  status: z.enum(["online", "unauthorized", "offline"]).optional(),
+  executionId: z.string().optional(),
+  /** Execution state, e.g. CREATED, RUNNING or SUCCESS */
+  state: z.string().optional(),
+  /** False when the wait timed out before a terminal state */
+  finished: z.boolean().optional(),
+  outputs: z.record(z.unknown()).optional(),
+  namespace: z.string().optional(),
+  flowId: z.string().optional(),
+  /** AgentMesh request the execution reports back to */
+  requestId: z.string().optional(),
+});
+
+const flowSchema = z.object({
+  namespace: z.string(),
+  id: z.string(),
+  revision: z.number().optional(),
+  description: z.string().optional(),
+  disabled: z.boolean().optional(),
+  /** deploy-workflow: created rather than updated */
+  created: z.boolean().optional(),
+  /** delete-flow: the flow was removed */
+  deleted: z.boolean().optional(),
+});
+
+const callbackSchema = z.object({
+  executionId: z.string(),
+  taskId: z.string().optional(),
+  state: z.string().optional(),
+  results: z.record(z.unknown()),
+  receivedAt: z.string(),
+});
+
+/**
+ * Schema of the structured content of every `kestra_code_intel` result
+ * @public
+ */
+export const codeIntelResultSchema = z.object({
+  action: z.enum(CODE_INTEL_ACTIONS),
+  /** False when the action failed; `error` says why */
+  ok: z.boolean(),
+  error: z.string().optional(),
+  repository: repositorySchema.optional(),
+  metrics: z.record(metricName, z.number()).optional(),
+  health: healthSchema.optional(),
+  priorities: z.array(prioritySchema).optional(),
+  recommendations: z.array(z.string()).optional(),
+  /** process-summary: tools the fired rules route to, most severe first */
+  actions: z.array(toolCallSchema).optional(),
+  /** execute-decision: the tool call and how it went */
+  decision: toolCallSchema.extend({
+    status: z.enum(["dry-run", "completed", "failed"]),
+    output: z.string().optional(),
+  }).optional(),
+  kestra: kestraSchema.optional(),
+  /** setup-workflow and deploy-workflow: the generated flow */
+  workflow: z.object({ namespace: z.string(), flowId: z.string(), yaml: z.string() }).optional(),
+  flows: z.array(flowSchema).optional(),
+  /** fetch-results: what flows reported for a request */
+  results: z.object({
+    action: z.string(),
+    repoUrl: z.string().optional(),
+    createdAt: z.string(),
+    callbacks: z.array(callbackSchema),
+  }).optional(),
+}).strict();
+
+export type CodeIntelResult = z.infer<typeof codeIntelResultSchema>;
+
+/**
+ * MCP tool result: the markdown report, and the data it was rendered from
+ * @interface CodeIntelToolResult
+ */
+export interface CodeIntelToolResult {
+  content: { type: "text"; text: string }[];
+  structuredContent: CodeIntelResult;
+}
+
+/**
+ * Builds a tool result from a markdown report and its data
+ *
+ * @param text - Markdown report
+ * @param data - Structured content
+ * @returns MCP tool result
+ * @throws {z.ZodError} If the data does not match the published schema
+ * @public
+ */
+export function codeIntelResult(text: string, data: CodeIntelResult): CodeIntelToolResult {
+  return {
+    content: [{ type: "text", text }],
+    structuredContent: codeIntelResultSchema.parse(data),
+  };
+}
@@ -0,0 +1,65 @@
+/**
+ * Codex CLI Backend for AgentMesh
+ * Runs tasks through `codex exec` in non-interactive mode
+ *
+ * @module codex
+ */
+
+import { applyPromptPolicy } from "./prompt-policy";
+import { probeVersion, runAgentProcess, textEvents } from "./agent-process";
+import type { AgentBackend } from "./agent";
+import type { BackendConfig } from "./config";
+
+/**
+ * Creates the Codex CLI implementation of the agent backend interface
+ *
+ * Mode mapping:
+ * - act: `--full-auto` when yolo is enabled, workspace-write sandbox otherwise
+ * - plan: read-only sandbox
+ *
+ * @param name - Configured backend name
+ * @param config - Backend settings
+ * @returns Codex backend
+ * @public
+ */
+export function createCodexBackend(name: string, config: BackendConfig): AgentBackend {
+  const command = config.path || process.env.CODEX_PATH || "codex";
+
+  return {
+    name,
+    type: "codex",
+    command,
+    isInstalled: async () => (await probeVersion(command)) !== undefined,
+    getVersion: async () => (await probeVersion(command)) ?? "unknown",
+    async run(prompt, options) {
+      const checkedPrompt = applyPromptPolicy(prompt);
+      const args = ["exec", ...(config.args ?? [])];
+      if (config.model) args.push("--model", config.model);
+      if (options.mode === "plan") {
+        args.push("--sandbox", "read-only");
+      } else if (options.yolo !== false) {
+        args.push("--full-auto");
+      } else {
+        args.push("--sandbox", "workspace-write");
+      }
+      args.push("--skip-git-repo-check");
+      args.push("-"); // Read the prompt from stdin
+
+      const run = await runAgentProcess({
+        label: "Codex",
+        command,
+        args,
+        input: checkedPrompt,
+        cwd: options.cwd,
+        timeout: options.timeout,
+      });
+
+      return {
+        success: run.success,
+        output: run.stdout.trim() || (run.success ? "Task completed (no output)" : ""),
+        error: run.success ? run.stderr || undefined : run.error,
+        events: textEvents(run.stdout),
+      };
+    },
+  };
+}
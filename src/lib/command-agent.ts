@@ -0,0 +1,81 @@
+/**
+ * Command Template Backend for AgentMesh
+ * Runs any agent CLI described by an argument template in the config
+ *
+ * Placeholders are substituted per argument, never through a shell:
+ * - {prompt}: the task prompt as an argument
+ * - {promptFile}: path of a private temp file holding the prompt
+ * - {mode}: "act" or "plan"
+ * - {cwd}: the working directory
+ *
+ * When the template uses neither {prompt} nor {promptFile}, the prompt is
+ * written to the agent's stdin.
+ *
+ * @module command-agent
+ */
+
+import { applyPromptPolicy, withPromptFile } from "./prompt-policy";
+import { probeVersion, runAgentProcess, textEvents } from "./agent-process";
+import type { AgentBackend } from "./agent";
+import type { BackendConfig } from "./config";
+
+/**
+ * Creates a backend from a command template
+ *
+ * @param name - Configured backend name
+ * @param config - Backend settings; `path` is required
+ * @returns Command backend
+ * @throws {Error} If `path` is missing
+ * @public
+ */
+export function createCommandBackend(name: string, config: BackendConfig): AgentBackend {
+  if (!config.path) {
+    throw new Error(`[AgentMesh] Command backend "${name}" needs a "path"`);
+  }
+  const command = config.path;
+  const template = config.template ?? [];
+  const usesPromptArg = template.some((arg) => arg.includes("{prompt}"));
+  const usesPromptFile = template.some((arg) => arg.includes("{promptFile}"));
+  const versionArgs = config.versionArgs ?? ["--version"];
+
+  return {
+    name,
+    type: "command",
+    command,
+    isInstalled: async () => (await probeVersion(command, versionArgs)) !== undefined,
+    getVersion: async () => (await probeVersion(command, versionArgs)) ?? "unknown",
+    async run(prompt, options) {
+      const checkedPrompt = applyPromptPolicy(prompt);
+      const invoke = (promptFile?: string) => {
+        const values: Record<string, string> = {
+          prompt: checkedPrompt,
+          promptFile: promptFile ?? "",
+          mode: options.mode ?? "act",
+          cwd: options.cwd || process.cwd(),
+        };
+        const args = [
+          ...(config.args ?? []),
+          ...template.map((arg) => arg.replace(/\{(prompt|promptFile|mode|cwd)\}/g, (_, key: string) => values[key])),
+        ];
+
+        return runAgentProcess({
+          label: name,
+          command,
+          args,
+          input: usesPromptArg || usesPromptFile ? undefined : checkedPrompt,
+          cwd: options.cwd,
+          timeout: options.timeout,
+        });
+      };
+
+      const run = usesPromptFile ? await withPromptFile(checkedPrompt, invoke) : await invoke();
+
+      return {
+        success: run.success,
+        output: run.stdout.trim() || (run.success ? "Task completed (no output)" : ""),
+        error: run.success ? run.stderr || undefined : run.error,
+        events: textEvents(run.stdout),
+      };
+    },
+  };
+}
@@ -0,0 +1,217 @@
+/**
+ * Step Conditions for AgentMesh Workflows
+ * Parses and evaluates the `when:` expressions of workflow steps
+ *
+ * The language is deliberately tiny and never executes code:
+ * - references: target, inputs.<name>, env.<NAME>,
+ *   steps.<id>.status|output|changedFiles (same rules as prompt templates)
+ * - literals: "text", 'text', numbers, true, false
+ * - operators: == != contains ! && || and parentheses
+ *
+ * ```yaml
+ * when: steps.scan.status == "succeeded" && steps.scan.output contains "CRITICAL"
+ * ```
+ *
+ * `contains` looks for a substring, or for an entry of a changedFiles list.
+ * Values compare as strings. Empty strings and lists, "false" and "0" count
+ * as false.
+ *
+ * @module condition
+ */
+
+import { TemplateError, resolveReference, type TemplateContext, type TemplateReference } from "./template";
+
+/**
+ * Raised when a condition cannot be parsed or evaluated
+ */
+export class ConditionError extends TemplateError {
+  constructor(message: string) {
+    super(message);
+    this.name = "ConditionError";
+  }
+}
+
+type BinaryOperator = "&&" | "||" | "==" | "!=" | "contains";
+type Value = string | number | boolean | string[];
+
+/**
+ * Parsed condition
+ */
+export type Condition =
+  | { kind: "literal"; value: string | number | boolean }
+  | { kind: "ref"; ref: TemplateReference }
+  | { kind: "not"; operand: Condition }
+  | { kind: "binary"; op: BinaryOperator; left: Condition; right: Condition };
+
+interface Token {
+  type: "op" | "string" | "number" | "word";
+  text: string;
+}
+
+const TOKEN = /\s*(?:(\|\||&&|==|!=|!|\(|\))|("(?:[^"\\]|\\.)*"|'(?:[^'\\]|\\.)*')|(-?\d+(?:\.\d+)?)|([A-Za-z_][A-Za-z0-9_-]*(?:\.[A-Za-z0-9_-]+)*))/y;
+const ROOTS = ["target", "inputs", "steps", "env"];
+
+function tokenize(source: string): Token[] {
+  const tokens: Token[] = [];
+  const pattern = new RegExp(TOKEN.source, "y");
+  let pos = 0;
+  while (source.slice(pos).trim()) {
+    pattern.lastIndex = pos;
+    const match = pattern.exec(source);
+    if (!match) {
+      throw new ConditionError(`Unexpected "${source.slice(pos).trim()[0]}" in condition "${source}"`);
+    }
+    pos = pattern.lastIndex;
+    if (match[1]) tokens.push({ type: "op", text: match[1] });
+    else if (match[2]) tokens.push({ type: "string", text: match[2] });
+    else if (match[3]) tokens.push({ type: "number", text: match[3] });
+    else tokens.push({ type: "word", text: match[4] });
+  }
+  return tokens;
+}
+
+/**
+ * Recursive descent parser; precedence from loosest: ||, &&, !, comparisons
+ * @internal
+ */
+class Parser {
+  private pos = 0;
+
+  constructor(private readonly source: string, private readonly tokens: Token[]) {}
+
+  parse(): Condition {
+    const condition = this.or();
+    const extra = this.tokens[this.pos];
+    if (extra) throw this.error(`Unexpected "${extra.text}"`);
+    return condition;
+  }
+
+  private error(message: string): ConditionError {
+    return new ConditionError(`${message} in condition "${this.source}"`);
+  }
+
+  private accept(text: string): boolean {
+    const token = this.tokens[this.pos];
+    if (token && (token.type === "op" || token.type === "word") && token.text === text) {
+      this.pos++;
+      return true;
+    }
+    return false;
+  }
+
+  private or(): Condition {
+    let left = this.and();
+    while (this.accept("||")) left = { kind: "binary", op: "||", left, right: this.and() };
+    return left;
+  }
+
+  private and(): Condition {
+    let left = this.unary();
+    while (this.accept("&&")) left = { kind: "binary", op: "&&", left, right: this.unary() };
+    return left;
+  }
+
+  private unary(): Condition {
+    if (this.accept("!")) return { kind: "not", operand: this.unary() };
+    const left = this.primary();
+    for (const op of ["==", "!=", "contains"] as const) {
+      if (this.accept(op)) return { kind: "binary", op, left, right: this.primary() };
+    }
+    return left;
+  }
+
+  private primary(): Condition {
+    const token = this.tokens[this.pos++];
+    if (!token) throw this.error("Unexpected end");
+    if (token.type === "op") {
+      if (token.text !== "(") throw this.error(`Unexpected "${token.text}"`);
+      const inner = this.or();
+      if (!this.accept(")")) throw this.error('Missing ")"');
+      return inner;
+    }
+    if (token.type === "string") {
+      return { kind: "literal", value: token.text.slice(1, -1).replace(/\\(.)/g, "$1") };
+    }
+    if (token.type === "number") return { kind: "literal", value: Number(token.text) };
+    if (token.text === "true" || token.text === "false") return { kind: "literal", value: token.text === "true" };
+
+    const [root, ...path] = token.text.split(".");
+    if (!ROOTS.includes(root)) throw this.error(`Unknown name "${token.text}"`);
+    return { kind: "ref", ref: { raw: token.text, root: root as TemplateReference["root"], path } };
+  }
+}
+
+/**
+ * Parses a condition
+ *
+ * @param source - Expression from a step's `when`
+ * @returns Parsed condition
+ * @throws {ConditionError} If the expression is malformed
+ * @public
+ */
+export function parseCondition(source: string): Condition {
+  return new Parser(source, tokenize(source)).parse();
+}
+
+/**
+ * Lists the references in a condition
+ *
+ * @param condition - Parsed condition
+ * @returns Every reference, in order
+ * @public
+ */
+export function conditionReferences(condition: Condition): TemplateReference[] {
+  switch (condition.kind) {
+    case "literal":
+      return [];
+    case "ref":
+      return [condition.ref];
+    case "not":
+      return conditionReferences(condition.operand);
+    case "binary":
+      return [...conditionReferences(condition.left), ...conditionReferences(condition.right)];
+  }
+}
+
+function text(value: Value): string {
+  return Array.isArray(value) ? value.join("\n") : String(value);
+}
+
+function truthy(value: Value): boolean {
+  if (Array.isArray(value)) return value.length > 0;
+  if (typeof value === "string") return value !== "" && value !== "false" && value !== "0";
+  return Boolean(value);
+}
+
+function evaluate(condition: Condition, context: TemplateContext): Value {
+  switch (condition.kind) {
+    case "literal":
+      return condition.value;
+    case "ref":
+      return resolveReference(condition.ref, context);
+    case "not":
+      return !truthy(evaluate(condition.operand, context));
+    case "binary": {
+      const left = evaluate(condition.left, context);
+      if (condition.op === "&&") return truthy(left) && truthy(evaluate(condition.right, context));
+      if (condition.op === "||") return truthy(left) || truthy(evaluate(condition.right, context));
+      const right = evaluate(condition.right, context);
+      if (condition.op === "==") return text(left) === text(right);
+      if (condition.op === "!=") return text(left) !== text(right);
+      return Array.isArray(left) ? left.includes(text(right)) : text(left).includes(text(right));
+    }
+  }
+}
+
+/**
+ * Evaluates a condition
+ *
+ * @param condition - Parsed condition
+ * @param context - Values of the workflow run so far
+ * @returns Whether the condition holds
+ * @throws {TemplateError} If a reference cannot be resolved
+ * @public
+ */
+export function evaluateCondition(condition: Condition, context: TemplateContext): boolean {
+  return truthy(evaluate(condition, context));
+}
@@ -0,0 +1,199 @@
+/**
+ * AgentMesh Configuration
+ * Loads `.agentmesh/config.json` (or the file named by AGENTMESH_CONFIG) and
+ * merges it over built-in defaults and environment overrides.
+ *
+ * @module config
+ */
+
+import * as fs from "fs";
+import * as path from "path";
+import { z } from "zod";
+
+const backendSchema = z.object({
+  /** Backend implementation */
+  type: z.enum(["cline", "aider", "codex", "command"]),
+  /** Executable to run (defaults to the backend's usual binary name) */
+  path: z.string().optional(),
+  /** Extra arguments appended before the prompt */
+  args: z.array(z.string()).optional(),
+  /** Model passed to backends that accept one */
+  model: z.string().optional(),
+  /**
+   * Argument template for `command` backends.
+   * Placeholders: {prompt}, {promptFile}, {mode}, {cwd}.
+   * Without {prompt} or {promptFile} the prompt is written to stdin.
+   */
+  template: z.array(z.string()).optional(),
+  /** Arguments used to query the version of a `command` backend */
+  versionArgs: z.array(z.string()).optional(),
+}).refine((backend) => backend.type !== "command" || !!backend.path, {
+  message: 'Backends of type "command" need a "path"',
+});
+
+const forgeSchema = z.object({
+  /** Where pull requests are opened; "local" records them in a JSON file */
+  type: z.enum(["github", "local"]),
+  /** Git remote branches are pushed to (default: origin) */
+  remote: z.string().optional(),
+  /** API base URL, e.g. for GitHub Enterprise */
+  apiUrl: z.string().optional(),
+  /** File the local forge records pull requests in */
+  path: z.string().optional(),
+});
+
+const kestraSchema = z.object({
+  /** Server URL (default: http://localhost:8080) */
+  url: z.string().optional(),
+  /** Tenant for multi-tenant servers; added to every API path */
+  tenant: z.string().optional(),
+  /**
+   * API authentication. Credentials left out here are read from
+   * KESTRA_USERNAME / KESTRA_PASSWORD (basic) or KESTRA_TOKEN (bearer).
+   */
+  auth: z.object({
+    type: z.enum(["basic", "bearer"]),
+    username: z.string().optional(),
+    password: z.string().optional(),
+    token: z.string().optional(),
+  }).optional(),
+});
+
+const githubSchema = z.object({
+  /** API token; GITHUB_TOKEN or GH_TOKEN are used when left out */
+  token: z.string().optional(),
+  /** API base URL, e.g. for GitHub Enterprise (default: https://api.github.com) */
+  apiUrl: z.string().optional(),
+  /** Directory of recorded responses answered instead of the network */
+  fixtures: z.string().optional(),
+  /** Call the API and write its responses to `fixtures` */
+  record: z.boolean().optional(),
+});
+
+const selfHostedForgeSchema = z.object({
+  /** API token; GITLAB_TOKEN or GITEA_TOKEN are used when left out */
+  token: z.string().optional(),
+  /** Self-hosted instances, by host name (e.g. "git.example.com") */
+  hosts: z.array(z.string()).optional(),
+  /** Directory of recorded responses answered instead of the network */
+  fixtures: z.string().optional(),
+  /** Call the API and write its responses to `fixtures` */
+  record: z.boolean().optional(),
+});
+
+const configSchema = z.object({
+  defaultBackend: z.string().optional(),
+  backends: z.record(backendSchema).optional(),
+  maxPromptLength: z.number().int().positive().optional(),
+  workspaceRoots: z.array(z.string()).min(1).optional(),
+  forge: forgeSchema.optional(),
+  templateEnv: z.array(z.string()).optional(),
+  kestra: kestraSchema.optional(),
+  github: githubSchema.optional(),
+  gitlab: selfHostedForgeSchema.optional(),
+  gitea: selfHostedForgeSchema.optional(),
+});
+
+export type BackendConfig = z.infer<typeof backendSchema>;
+export type ForgeConfig = z.infer<typeof forgeSchema>;
+export type KestraConfig = z.infer<typeof kestraSchema>;
+export type GitHubConfig = z.infer<typeof githubSchema>;
+export type SelfHostedForgeConfig = z.infer<typeof selfHostedForgeSchema>;
+
+/**
+ * Resolved AgentMesh configuration
+ * @interface AgentMeshConfig
+ */
+export interface AgentMeshConfig {
+  /** Backend used when a tool call does not name one */
+  defaultBackend: string;
+  /** Configured agent backends keyed by name */
+  backends: Record<string, BackendConfig>;
+  /** Longest prompt accepted by the prompt policy, in characters */
+  maxPromptLength: number;
+  /** Directories tools may work in; everything else is rejected */
+  workspaceRoots: string[];
+  /** Forge used to open pull requests */
+  forge: ForgeConfig;
+  /** Environment variables workflow templates may read as `{env.NAME}` */
+  templateEnv: string[];
+  /** Kestra server used by kestra_code_intel */
+  kestra: KestraConfig;
+  /** GitHub API access for repository analysis and pull requests */
+  github: GitHubConfig;
+  /** GitLab access (gitlab.com and self-hosted) for repository analysis */
+  gitlab: SelfHostedForgeConfig;
+  /** Gitea / Forgejo access (codeberg.org and self-hosted) for repository analysis */
+  gitea: SelfHostedForgeConfig;
+}
+
+const DEFAULT_BACKENDS: Record<string, BackendConfig> = {
+  cline: { type: "cline" },
+  aider: { type: "aider" },
+  codex: { type: "codex" },
+};
+
+// Default maximum prompt length in characters
+const DEFAULT_MAX_PROMPT_LENGTH = 100000;
+
+let cached: AgentMeshConfig | undefined;
+
+/**
+ * Path of the config file that will be loaded
+ * @public
+ */
+export function getConfigPath(): string {
+  return process.env.AGENTMESH_CONFIG || path.join(process.cwd(), ".agentmesh", "config.json");
+}
+
+/**
+ * Loads and caches the AgentMesh configuration
+ *
+ * @returns Resolved configuration
+ * @throws {Error} If the config file exists but is invalid
+ * @public
+ */
+export function loadConfig(): AgentMeshConfig {
+  if (cached) return cached;
+
+  const configPath = getConfigPath();
+  let fileConfig: z.infer<typeof configSchema> = {};
+  if (fs.existsSync(configPath)) {
+    const parsed = configSchema.safeParse(JSON.parse(fs.readFileSync(configPath, "utf8")));
+    if (!parsed.success) {
+      throw new Error(`[AgentMesh] Invalid config ${configPath}: ${parsed.error.message}`);
+    }
+    fileConfig = parsed.data;
+  }
+
+  const backends = { ...DEFAULT_BACKENDS, ...fileConfig.backends };
+  const defaultBackend = process.env.AGENTMESH_BACKEND || fileConfig.defaultBackend || "cline";
+  if (!backends[defaultBackend]) {
+    throw new Error(`[AgentMesh] Default backend "${defaultBackend}" is not configured`);
+  }
+
+  // AGENTMESH_WORKSPACE_ROOTS uses the platform path delimiter, like PATH
+  const envRoots = process.env.AGENTMESH_WORKSPACE_ROOTS?.split(path.delimiter).filter(Boolean);
+
+  cached = {
+    defaultBackend,
+    backends,
+    maxPromptLength: fileConfig.maxPromptLength ?? DEFAULT_MAX_PROMPT_LENGTH,
+    workspaceRoots: envRoots?.length ? envRoots : fileConfig.workspaceRoots ?? [process.cwd()],
+    forge: fileConfig.forge ?? { type: "github" },
+    templateEnv: fileConfig.templateEnv ?? [],
+    kestra: fileConfig.kestra ?? {},
+    github: fileConfig.github ?? {},
+    gitlab: fileConfig.gitlab ?? {},
+    gitea: fileConfig.gitea ?? {},
+  };
+  return cached;
+}
+
+/**
+ * Clears the cached configuration so the next `loadConfig()` re-reads it
+ * @public
+ */
+export function resetConfig(): void {
+  cached = undefined;
+}
@@ -0,0 +1,417 @@
+/**
+ * REST Client Base for Forge APIs
+ * The HTTP plumbing shared by the GitHub, GitLab and Gitea clients: token
+ * auth, Link-header pagination, rate-limit handling, retries and fixtures
+ *
+ * When a rate limit runs out the client waits for the reset if it is near,
+ * and otherwise fails with a RateLimitError. Network errors, timeouts and
+ * server errors of GET and HEAD requests are retried with exponential
+ * backoff; other methods are sent once, since the first attempt may have
+ * taken effect (a retried POST could open a second pull request).
+ *
+ * Fixture mode (a `fixtures` directory) answers requests from recorded JSON
+ * files and never touches the network. With `record` the real responses are
+ * written there first.
+ *
+ * @module forge-api
+ */
+
+import * as fs from "fs";
+import * as path from "path";
+
+// Time allowed for a single API request
+const REQUEST_TIMEOUT = 15000;
+const MAX_ATTEMPTS = 3;
+const DEFAULT_RETRY_DELAY = 1000;
+// Longest wait for a rate limit reset before giving up
+const DEFAULT_MAX_RATE_LIMIT_WAIT = 60000;
+// Response headers kept in fixtures
+const KEPT_HEADERS = ["link", "retry-after", "x-ratelimit-remaining", "x-ratelimit-reset", "ratelimit-remaining", "ratelimit-reset"];
+
+/**
+ * Raised when a forge API answers with an error status
+ */
+export class ForgeApiError extends Error {
+  /**
+   * @param status - HTTP status
+   * @param message - Full error message
+   * @param detail - Message the forge gave, if any
+   */
+  constructor(readonly status: number, message: string, readonly detail?: string) {
+    super(message);
+    this.name = "ForgeApiError";
+  }
+}
+
+/**
+ * Raised when the rate limit ran out and resets too late to wait for
+ */
+export class RateLimitError extends ForgeApiError {
+  constructor(status: number, label: string, readonly resetAt: Date | undefined, hint?: string) {
+    super(status, `${label} rate limit exceeded${resetAt ? `; it resets at ${resetAt.toISOString()}` : ""}${hint ? `. ${hint}` : ""}`);
+    this.name = "RateLimitError";
+  }
+}
+
+/**
+ * Where and how a client reaches its API
+ * @interface ForgeApiSettings
+ */
+export interface ForgeApiSettings {
+  apiUrl: string;
+  token?: string;
+  /** Directory of recorded responses answered instead of the network */
+  fixtures?: string;
+  /** Call the API and write its responses to `fixtures` */
+  record?: boolean;
+}
+
+/**
+ * Tuning knobs, mostly for tests
+ * @interface ForgeApiOptions
+ */
+export interface ForgeApiOptions {
+  /** Longest wait for a rate limit reset in ms (default: 60s) */
+  maxRateLimitWait?: number;
+  /** First retry delay after a server error of a GET or HEAD in ms, doubled per attempt (default: 1s) */
+  retryDelay?: number;
+}
+
+/**
+ * An API response as stored in a fixture file
+ * @interface ApiResponse
+ */
+export interface ApiResponse<T> {
+  status: number;
+  /** Lower-cased headers the client uses */
+  headers: Record<string, string>;
+  body: T;
+}
+
+/**
+ * A repository
+ * @interface ForgeRepo
+ */
+export interface ForgeRepo {
+  /** Owner, or the full group path on GitLab */
+  owner: string;
+  name: string;
+  fullName: string;
+  description?: string;
+  stars: number;
+  /** Open issues as the forge counts them (GitHub includes pull requests) */
+  openIssues: number;
+  language?: string;
+  defaultBranch: string;
+  updatedAt: string;
+  url: string;
+}
+
+/**
+ * An issue (pull requests are left out)
+ * @interface ForgeIssue
+ */
+export interface ForgeIssue {
+  number: number;
+  title: string;
+  state: string;
+  labels: string[];
+  author?: string;
+  createdAt: string;
+  url: string;
+}
+
+/**
+ * A pull request (a merge request on GitLab)
+ * @interface ForgePullRequest
+ */
+export interface ForgePullRequest {
+  number: number;
+  title: string;
+  state: string;
+  draft: boolean;
+  author?: string;
+  createdAt: string;
+  url: string;
+}
+
+/** CI run outcome, the same across forges */
+export type PipelineState = "success" | "failed" | "running" | "canceled" | "other";
+
+/**
+ * A CI run: a GitHub Actions run, GitLab pipeline or Gitea Actions task
+ * @interface ForgePipeline
+ */
+export interface ForgePipeline {
+  id: number;
+  /** Status as the forge reports it */
+  status: string;
+  state: PipelineState;
+  /** Branch or tag it ran for */
+  ref?: string;
+  createdAt: string;
+  url?: string;
+}
+
+/**
+ * Someone who committed to a repository
+ * @interface ForgeContributor
+ */
+export interface ForgeContributor {
+  name: string;
+  contributions: number;
+}
+
+/**
+ * Filters for list calls
+ * @interface ListOptions
+ */
+export interface ListOptions {
+  state?: "open" | "closed" | "all";
+  /** Stop paging once this many items were read */
+  maxItems?: number;
+}
+
+const PIPELINE_STATES: Record<string, PipelineState> = {
+  success: "success",
+  failed: "failed",
+  failure: "failed",
+  timed_out: "failed",
+  startup_failure: "failed",
+  error: "failed",
+  running: "running",
+  in_progress: "running",
+  queued: "running",
+  pending: "running",
+  waiting: "running",
+  requested: "running",
+  created: "running",
+  preparing: "running",
+  scheduled: "running",
+  waiting_for_resource: "running",
+  blocked: "running",
+  canceled: "canceled",
+  cancelled: "canceled",
+  canceling: "canceled",
+};
+
+/**
+ * Maps a forge's CI status to a common state
+ * @public
+ */
+export function pipelineState(status: string | undefined): PipelineState {
+  return PIPELINE_STATES[(status || "").toLowerCase()] || "other";
+}
+
+/**
+ * Main language from a language breakdown (bytes or percentages)
+ * @internal
+ */
+export function topLanguage(languages: Record<string, number> | undefined): string | undefined {
+  let top: string | undefined;
+  for (const [language, share] of Object.entries(languages ?? {})) {
+    if (top === undefined || share > (languages as Record<string, number>)[top]) top = language;
+  }
+  return top;
+}
+
+function sleep(ms: number): Promise<void> {
+  return new Promise((resolve) => setTimeout(resolve, ms));
+}
+
+/**
+ * URL of the next page from a Link header
+ * @internal
+ */
+export function nextPageUrl(link: string | undefined): string | undefined {
+  const match = link?.match(/<([^>]+)>;\s*rel="next"/);
+  return match ? match[1] : undefined;
+}
+
+/**
+ * Base class of the forge API clients
+ * @public
+ */
+export abstract class ForgeApiClient {
+  readonly apiUrl: string;
+  /** Directory of recorded responses, when fixture mode is on */
+  readonly fixtures?: string;
+  protected readonly token?: string;
+  /** Forge name used in error messages */
+  protected abstract readonly label: string;
+  /** Query parameter setting the page size */
+  protected readonly pageParam: string = "per_page";
+  /** Largest page the API serves */
+  protected readonly maxPageSize: number = 100;
+  private readonly record: boolean;
+  private readonly maxRateLimitWait: number;
+  private readonly retryDelay: number;
+
+  constructor(settings: ForgeApiSettings, options: ForgeApiOptions = {}) {
+    this.apiUrl = settings.apiUrl.replace(/\/$/, "");
+    this.fixtures = settings.fixtures || undefined;
+    this.record = !!settings.record;
+    this.token = settings.token || undefined;
+    this.maxRateLimitWait = options.maxRateLimitWait ?? DEFAULT_MAX_RATE_LIMIT_WAIT;
+    this.retryDelay = options.retryDelay ?? DEFAULT_RETRY_DELAY;
+  }
+
+  /** Whether requests carry a token */
+  get authenticated(): boolean {
+    return !!this.token;
+  }
+
+  /** Request headers, including auth */
+  protected abstract headers(): Record<string, string>;
+
+  /** Advice added to rate limit errors of anonymous clients */
+  protected tokenHint(): string | undefined {
+    return undefined;
+  }
+
+  /**
+   * Sends an API request
+   *
+   * @param method - HTTP method
+   * @param apiPath - Path below the API URL, or a full URL from a Link header
+   * @param body - JSON body
+   * @returns Status, headers and parsed body
+   * @throws {RateLimitError} If the rate limit ran out and resets too late
+   * @throws {ForgeApiError} If the server answers with another error status
+   */
+  async request<T>(method: string, apiPath: string, body?: unknown): Promise<ApiResponse<T>> {
+    const url = /^https?:\/\//.test(apiPath) ? apiPath : `${this.apiUrl}${apiPath}`;
+    if (this.fixtures && !this.record) {
+      return this.check(method, url, this.replay<T>(method, url));
+    }
+
+    const headers = this.headers();
+    if (body !== undefined) headers["Content-Type"] = "application/json";
+    const retryable = method === "GET" || method === "HEAD";
+
+    for (let attempt = 1; ; attempt++) {
+      let res: Response;
+      try {
+        res = await fetch(url, {
+          method,
+          headers,
+          body: body === undefined ? undefined : JSON.stringify(body),
+          signal: AbortSignal.timeout(REQUEST_TIMEOUT),
+        });
+      } catch (error) {
+        if (!retryable || attempt >= MAX_ATTEMPTS) throw error;
+        await sleep(this.retryDelay * 2 ** (attempt - 1));
+        continue;
+      }
+
+      const response: ApiResponse<T> = { status: res.status, headers: {}, body: undefined as T };
+      for (const name of KEPT_HEADERS) {
+        const value = res.headers.get(name);
+        if (value !== null) response.headers[name] = value;
+      }
+      const text = await res.text();
+      try {
+        response.body = text ? JSON.parse(text) : undefined;
+      } catch {
+        response.body = { message: text } as T;
+      }
+
+      const wait = this.rateLimitWait(response);
+      if (wait !== undefined && wait <= this.maxRateLimitWait && attempt < MAX_ATTEMPTS) {
+        console.error(`[AgentMesh] ${this.label} rate limit hit; retrying in ${Math.ceil(wait / 1000)}s`);
+        await sleep(wait);
+        continue;
+      }
+      if (res.status >= 500 && retryable && attempt < MAX_ATTEMPTS) {
+        await sleep(this.retryDelay * 2 ** (attempt - 1));
+        continue;
+      }
+
+      if (this.fixtures) this.save(method, url, response);
+      return this.check(method, url, response);
+    }
+  }
+
+  /**
+   * Reads every page of a list endpoint
+   *
+   * @param apiPath - Path of the first page, without paging parameters
+   * @param maxItems - Stop once this many items were read
+   * @param items - Picks the items out of a page that is not a plain list
+   * @returns Items of all pages read
+   */
+  async paginate<T>(apiPath: string, maxItems = Infinity, items?: (body: unknown) => T[] | undefined): Promise<T[]> {
+    const all: T[] = [];
+    const pageSize = Math.min(this.maxPageSize, maxItems);
+    let next: string | undefined = `${apiPath}${apiPath.includes("?") ? "&" : "?"}${this.pageParam}=${pageSize}`;
+    while (next && all.length < maxItems) {
+      const res: ApiResponse<unknown> = await this.request<unknown>("GET", next);
+      const page = items ? items(res.body) : res.body;
+      if (!Array.isArray(page)) {
+        throw new ForgeApiError(res.status, `${this.label} GET ${next} did not return a list`);
+      }
+      all.push(...(page as T[]));
+      // An empty page ends the list even if the server still links a next one
+      next = page.length ? nextPageUrl(res.headers.link) : undefined;
+    }
+    return all.slice(0, maxItems);
+  }
+
+  /** Delay before retrying a rate-limited response, or undefined if it was not rate limited */
+  private rateLimitWait(response: ApiResponse<unknown>): number | undefined {
+    if (response.status !== 403 && response.status !== 429) return undefined;
+    const retryAfter = response.headers["retry-after"];
+    if (retryAfter !== undefined) return Number(retryAfter) * 1000;
+    const remaining = response.headers["x-ratelimit-remaining"] ?? response.headers["ratelimit-remaining"];
+    if (remaining === "0") return Math.max(0, this.resetTime(response) - Date.now());
+    // GitLab and Gitea answer 429 without headers when they throttle
+    return response.status === 429 ? this.retryDelay : undefined;
+  }
+
+  /** Epoch ms the rate limit resets at (NaN if unknown) */
+  private resetTime(response: ApiResponse<unknown>): number {
+    return Number(response.headers["x-ratelimit-reset"] ?? response.headers["ratelimit-reset"]) * 1000;
+  }
+
+  /** Turns error responses into exceptions */
+  private check<T>(method: string, url: string, response: ApiResponse<T>): ApiResponse<T> {
+    if (response.status < 400) return response;
+    if (this.rateLimitWait(response) !== undefined) {
+      const reset = this.resetTime(response);
+      throw new RateLimitError(
+        response.status,
+        this.label,
+        isNaN(reset) ? undefined : new Date(reset),
+        this.authenticated ? undefined : this.tokenHint(),
+      );
+    }
+    const raw = (response.body as { message?: unknown; error?: unknown } | undefined);
+    const message = raw?.message ?? raw?.error;
+    const detail = message === undefined ? undefined : typeof message === "string" ? message : JSON.stringify(message);
+    throw new ForgeApiError(
+      response.status,
+      `${this.label} ${method} ${url.replace(this.apiUrl, "")} failed (${response.status})${detail ? `: ${detail}` : ""}`,
+      detail,
+    );
+  }
+
+  /** Fixture file holding the response to a request */
+  private fixtureFile(method: string, url: string): string {
+    const name = url.replace(this.apiUrl, "").replace(/^\/+/, "").replace(/[^A-Za-z0-9.-]+/g, "_");
+    return path.join(this.fixtures as string, `${method === "GET" ? "" : `${method}_`}${name}.json`);
+  }
+
+  private replay<T>(method: string, url: string): ApiResponse<T> {
+    const file = this.fixtureFile(method, url);
+    if (!fs.existsSync(file)) {
+      throw new Error(`No ${this.label} fixture for ${method} ${url.replace(this.apiUrl, "")} (expected ${file})`);
+    }
+    return JSON.parse(fs.readFileSync(file, "utf8"));
+  }
+
+  private save(method: string, url: string, response: ApiResponse<unknown>): void {
+    const file = this.fixtureFile(method, url);
+    fs.mkdirSync(path.dirname(file), { recursive: true });
+    fs.writeFileSync(file, JSON.stringify(response, null, 2) + "\n");
+  }
+}
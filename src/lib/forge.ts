@@ -0,0 +1,165 @@
+/**
+ * Forge Clients for AgentMesh
+ * Opens pull requests for branches produced by agent workflows
+ *
+ * The forge is chosen by the `forge` section of the config:
+ * - github: pushes the branch and opens a PR through the GitHub REST API
+ *   (token from `github.token`, GITHUB_TOKEN or GH_TOKEN)
+ * - local: records PRs in a JSON file; a stand-in for tests and offline use
+ *
+ * @module forge
+ */
+
+import * as fs from "fs";
+import * as path from "path";
+import { getConfigPath, loadConfig, type ForgeConfig } from "./config";
+import { git } from "./git";
+import { ForgeApiError } from "./forge-api";
+import { getGitHubClient } from "./github";
+
+/**
+ * Pull request to open
+ * @interface PullRequestSpec
+ */
+export interface PullRequestSpec {
+  /** Any directory inside the repository */
+  dir: string;
+  /** Branch with the changes */
+  head: string;
+  /** Branch to merge into */
+  base: string;
+  title: string;
+  body: string;
+}
+
+/**
+ * An opened pull request
+ * @interface PullRequest
+ */
+export interface PullRequest {
+  number: number;
+  url: string;
+}
+
+/**
+ * A code hosting service AgentMesh can open pull requests on
+ * @interface ForgeClient
+ */
+export interface ForgeClient {
+  /** Forge type, for messages */
+  readonly name: string;
+  /** Publishes `head` and opens a pull request into `base` */
+  createPullRequest(spec: PullRequestSpec): Promise<PullRequest>;
+}
+
+/**
+ * Extracts owner and repository name from a GitHub remote URL
+ *
+ * @param url - SSH or HTTPS remote URL
+ * @returns Owner and repository, or undefined if the URL is not recognised
+ * @public
+ */
+export function parseGitHubRemote(url: string): { owner: string; repo: string } | undefined {
+  const match = url.match(/^(?:git@[^:]+:|(?:https?|ssh|git):\/\/(?:[^@/]+@)?[^/]+\/)([^/]+)\/(.+?)(?:\.git)?\/?$/);
+  return match ? { owner: match[1], repo: match[2] } : undefined;
+}
+
+/**
+ * GitHub implementation of the forge interface
+ * @public
+ */
+export class GitHubForge implements ForgeClient {
+  readonly name = "github";
+
+  constructor(private readonly config: ForgeConfig = { type: "github" }) {}
+
+  async createPullRequest(spec: PullRequestSpec): Promise<PullRequest> {
+    const github = getGitHubClient(this.config.apiUrl ? { apiUrl: this.config.apiUrl } : {});
+    if (!github.authenticated) {
+      throw new Error("GITHUB_TOKEN is not set; it is needed to open pull requests");
+    }
+
+    const remote = this.config.remote || "origin";
+    const remoteUrl = await git(["remote", "get-url", remote], spec.dir);
+    const repo = parseGitHubRemote(remoteUrl);
+    if (!repo) {
+      throw new Error(`Remote "${remote}" (${remoteUrl}) is not a GitHub repository`);
+    }
+
+    await git(["push", "-u", remote, spec.head], spec.dir);
+
+    try {
+      return await github.createPullRequest(repo.owner, repo.repo, { title: spec.title, body: spec.body, head: spec.head, base: spec.base });
+    } catch (error) {
+      if (error instanceof ForgeApiError) {
+        throw new Error(`GitHub refused the pull request: ${error.detail || error.message}`);
+      }
+      throw error;
+    }
+  }
+}
+
+/**
+ * Pull request recorded by the local forge
+ * @interface LocalPullRequest
+ */
+export interface LocalPullRequest extends PullRequest {
+  title: string;
+  body: string;
+  head: string;
+  base: string;
+  /** Commit at the head of the branch when the PR was opened */
+  headSha: string;
+  repoDir: string;
+  createdAt: string;
+}
+
+/**
+ * Forge that records pull requests in a JSON file instead of calling a service
+ * @public
+ */
+export class LocalForge implements ForgeClient {
+  readonly name = "local";
+  readonly file: string;
+
+  constructor(config: ForgeConfig = { type: "local" }) {
+    this.file = config.path || path.join(path.dirname(getConfigPath()), "pull-requests.json");
+  }
+
+  /** Pull requests recorded so far */
+  list(): LocalPullRequest[] {
+    return fs.existsSync(this.file) ? JSON.parse(fs.readFileSync(this.file, "utf8")) : [];
+  }
+
+  async createPullRequest(spec: PullRequestSpec): Promise<PullRequest> {
+    const headSha = await git(["rev-parse", "--verify", spec.head], spec.dir);
+    const repoDir = await git(["rev-parse", "--show-toplevel"], spec.dir);
+    const all = this.list();
+    const number = all.length + 1;
+    const pr: LocalPullRequest = {
+      number,
+      url: `local://${path.basename(repoDir)}/pull/${number}`,
+      title: spec.title,
+      body: spec.body,
+      head: spec.head,
+      base: spec.base,
+      headSha,
+      repoDir,
+      createdAt: new Date().toISOString(),
+    };
+    fs.mkdirSync(path.dirname(this.file), { recursive: true });
+    fs.writeFileSync(this.file, JSON.stringify([...all, pr], null, 2));
+    return { number: pr.number, url: pr.url };
+  }
+}
+
+/**
+ * Forge client for the configured forge
+ *
+ * @param config - Forge settings (defaults to the loaded config)
+ * @returns Forge client
+ * @public
+ */
+export function getForge(config: ForgeConfig = loadConfig().forge): ForgeClient {
+  return config.type === "local" ? new LocalForge(config) : new GitHubForge(config);
+}
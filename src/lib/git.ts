@@ -0,0 +1,80 @@
+/**
+ * Git helpers shared by the worktree, change tracking and forge modules
+ *
+ * @module git
+ */
+
+import { execa } from "execa";
+
+/**
+ * Runs git and returns its trimmed stdout
+ *
+ * @param args - Git arguments
+ * @param cwd - Directory to run in
+ * @param env - Extra environment variables
+ * @returns Trimmed stdout
+ * @throws {Error} If git exits with a non-zero code
+ * @public
+ */
+export async function git(args: string[], cwd: string, env?: Record<string, string>): Promise<string> {
+  const { stdout } = await execa("git", args, { cwd, env });
+  return stdout.trim();
+}
+
+/**
+ * Finds the top level of the repository containing a directory
+ *
+ * @param dir - Any directory
+ * @returns Repository root, or undefined outside git
+ * @public
+ */
+export async function findRepoRoot(dir: string): Promise<string | undefined> {
+  try {
+    return await git(["rev-parse", "--show-toplevel"], dir);
+  } catch {
+    return undefined;
+  }
+}
+
+/**
+ * Name of the checked-out branch
+ *
+ * @param dir - Any directory inside the repository
+ * @returns Branch name ("HEAD" when detached)
+ * @public
+ */
+export async function currentBranch(dir: string): Promise<string> {
+  return git(["rev-parse", "--abbrev-ref", "HEAD"], dir);
+}
+
+/**
+ * Arguments that add a fallback identity when the repository has none
+ * configured, so commits made for the user never fail on a fresh machine
+ *
+ * @param dir - Any directory inside the repository
+ * @returns `-c` options to place before the git subcommand
+ * @public
+ */
+export async function identityArgs(dir: string): Promise<string[]> {
+  try {
+    await git(["config", "user.email"], dir);
+    return [];
+  } catch {
+    return ["-c", "user.name=AgentMesh", "-c", "user.email=agentmesh@localhost"];
+  }
+}
+
+/**
+ * Stages everything and commits it
+ *
+ * @param dir - Any directory inside the repository
+ * @param message - Commit message
+ * @returns SHA of the new commit, or undefined when there was nothing to commit
+ * @public
+ */
+export async function commitAll(dir: string, message: string): Promise<string | undefined> {
+  await git(["add", "-A"], dir);
+  if (!(await git(["status", "--porcelain"], dir))) return undefined;
+  await git([...(await identityArgs(dir)), "commit", "-q", "--no-verify", "-m", message], dir);
+  return git(["rev-parse", "HEAD"], dir);
+}
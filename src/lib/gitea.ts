@@ -0,0 +1,206 @@
+/**
+ * Gitea / Forgejo API Client for AgentMesh
+ * Reads repositories, issues, pull requests, Actions tasks and contributors
+ * through the Gitea REST API, which Forgejo (e.g. codeberg.org) serves too
+ *
+ * The token comes from `gitea.token` in the config, then GITEA_TOKEN.
+ * Gitea has no contributors endpoint, so contributors are counted from the
+ * latest commits. Paging, rate limits, retries and fixtures are handled by
+ * the forge-api base class.
+ *
+ * @module gitea
+ */
+
+import { loadConfig, type SelfHostedForgeConfig } from "./config";
+import {
+  ForgeApiClient,
+  pipelineState,
+  topLanguage,
+  type ForgeApiOptions,
+  type ForgeContributor,
+  type ForgeIssue,
+  type ForgePipeline,
+  type ForgePullRequest,
+  type ForgeRepo,
+  type ListOptions,
+} from "./forge-api";
+
+// Commits read to count contributors
+const CONTRIBUTOR_COMMITS = 200;
+
+interface RawUser {
+  login?: string;
+}
+
+interface RawRepo {
+  name: string;
+  full_name: string;
+  owner?: RawUser;
+  description?: string;
+  stars_count?: number;
+  open_issues_count?: number;
+  default_branch?: string;
+  updated_at: string;
+  html_url: string;
+}
+
+interface RawIssue {
+  number: number;
+  title: string;
+  state: string;
+  labels?: Array<{ name?: string }> | null;
+  user?: RawUser | null;
+  created_at: string;
+  html_url: string;
+  draft?: boolean;
+}
+
+interface RawTask {
+  id: number;
+  status: string;
+  head_branch?: string;
+  created_at: string;
+  url?: string;
+}
+
+interface RawCommit {
+  author?: RawUser | null;
+  commit?: { author?: { name?: string } };
+}
+
+/**
+ * Client for the Gitea / Forgejo REST API
+ * @public
+ */
+export class GiteaClient extends ForgeApiClient {
+  protected readonly label = "Gitea";
+  protected readonly pageParam = "limit";
+  protected readonly maxPageSize = 50;
+
+  /**
+   * @param origin - Instance URL, e.g. https://codeberg.org
+   * @param config - Token and fixture settings
+   * @param options - Retry tuning
+   */
+  constructor(origin: string, config: SelfHostedForgeConfig = {}, options: ForgeApiOptions = {}) {
+    super({
+      apiUrl: `${origin.replace(/\/$/, "")}/api/v1`,
+      token: config.token || process.env.GITEA_TOKEN,
+      fixtures: config.fixtures,
+      record: config.record,
+    }, options);
+  }
+
+  protected headers(): Record<string, string> {
+    const headers: Record<string, string> = { Accept: "application/json" };
+    if (this.token) headers.Authorization = `token ${this.token}`;
+    return headers;
+  }
+
+  protected tokenHint(): string {
+    return "Set GITEA_TOKEN (or gitea.token in the config)";
+  }
+
+  /**
+   * Reads a repository, with its main language
+   */
+  async getRepo(owner: string, repo: string): Promise<ForgeRepo> {
+    const [{ body: raw }, { body: languages }] = await Promise.all([
+      this.request<RawRepo>("GET", `/repos/${owner}/${repo}`),
+      this.request<Record<string, number>>("GET", `/repos/${owner}/${repo}/languages`),
+    ]);
+    return {
+      owner: raw.owner?.login ?? owner,
+      name: raw.name,
+      fullName: raw.full_name,
+      description: raw.description || undefined,
+      stars: raw.stars_count ?? 0,
+      openIssues: raw.open_issues_count ?? 0,
+      language: topLanguage(languages),
+      defaultBranch: raw.default_branch ?? "main",
+      updatedAt: raw.updated_at,
+      url: raw.html_url,
+    };
+  }
+
+  /**
+   * Lists the issues of a repository (pull requests are left out)
+   */
+  async listIssues(owner: string, repo: string, options: ListOptions = {}): Promise<ForgeIssue[]> {
+    const raw = await this.paginate<RawIssue>(
+      `/repos/${owner}/${repo}/issues?state=${options.state ?? "open"}&type=issues`, options.maxItems,
+    );
+    return raw.map((issue) => ({
+      number: issue.number,
+      title: issue.title,
+      state: issue.state,
+      labels: (issue.labels ?? []).map((l) => l.name ?? "").filter(Boolean),
+      author: issue.user?.login,
+      createdAt: issue.created_at,
+      url: issue.html_url,
+    }));
+  }
+
+  /**
+   * Lists the pull requests of a repository
+   */
+  async listPullRequests(owner: string, repo: string, options: ListOptions = {}): Promise<ForgePullRequest[]> {
+    const raw = await this.paginate<RawIssue>(`/repos/${owner}/${repo}/pulls?state=${options.state ?? "open"}`, options.maxItems);
+    return raw.map((pr) => ({
+      number: pr.number,
+      title: pr.title,
+      state: pr.state,
+      // Versions without a draft flag mark work in progress in the title
+      draft: pr.draft ?? /^\s*(\[wip\]|wip:|draft:)/i.test(pr.title),
+      author: pr.user?.login,
+      createdAt: pr.created_at,
+      url: pr.html_url,
+    }));
+  }
+
+  /**
+   * Lists the latest Actions tasks of a repository, newest first
+   */
+  async listPipelines(owner: string, repo: string, options: ListOptions = {}): Promise<ForgePipeline[]> {
+    const raw = await this.paginate<RawTask>(
+      `/repos/${owner}/${repo}/actions/tasks`, options.maxItems, (body) => (body as { workflow_runs?: RawTask[] })?.workflow_runs,
+    );
+    return raw.map((task) => ({
+      id: task.id,
+      status: task.status,
+      state: pipelineState(task.status),
+      ref: task.head_branch,
+      createdAt: task.created_at,
+      url: task.url,
+    }));
+  }
+
+  /**
+   * Counts the authors of the latest commits, most commits first
+   */
+  async listContributors(owner: string, repo: string, options: ListOptions = {}): Promise<ForgeContributor[]> {
+    const commits = await this.paginate<RawCommit>(
+      `/repos/${owner}/${repo}/commits?stat=false&verification=false&files=false`, CONTRIBUTOR_COMMITS,
+    );
+    const counts = new Map<string, number>();
+    for (const commit of commits) {
+      const name = commit.author?.login || commit.commit?.author?.name || "unknown";
+      counts.set(name, (counts.get(name) ?? 0) + 1);
+    }
+    return [...counts.entries()]
+      .map(([name, contributions]) => ({ name, contributions }))
+      .sort((a, b) => b.contributions - a.contributions)
+      .slice(0, options.maxItems ?? Infinity);
+  }
+}
+
+/**
+ * Client for a Gitea / Forgejo instance, with the configured token
+ *
+ * @param origin - Instance URL, e.g. https://codeberg.org
+ * @returns Gitea client
+ * @public
+ */
+export function getGiteaClient(origin: string): GiteaClient {
+  return new GiteaClient(origin, loadConfig().gitea);
+}
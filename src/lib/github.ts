@@ -0,0 +1,212 @@
+/**
+ * GitHub API Client for AgentMesh
+ * Reads repositories, issues, pull requests, Actions runs and contributors
+ * through the GitHub REST API and opens pull requests for the github forge
+ *
+ * The token comes from `github.token` in the config, then GITHUB_TOKEN or
+ * GH_TOKEN; without one requests are anonymous (60 per hour). Paging, rate
+ * limits, retries and fixtures are handled by the forge-api base class;
+ * fixture mode is also switched on by AGENTMESH_GITHUB_FIXTURES.
+ *
+ * @module github
+ */
+
+import { loadConfig, type GitHubConfig } from "./config";
+import {
+  ForgeApiClient,
+  pipelineState,
+  type ForgeApiOptions,
+  type ForgeContributor,
+  type ForgeIssue,
+  type ForgePipeline,
+  type ForgePullRequest,
+  type ForgeRepo,
+  type ListOptions,
+} from "./forge-api";
+
+const DEFAULT_API_URL = "https://api.github.com";
+
+interface RawUser {
+  login?: string;
+}
+
+interface RawRepo {
+  name: string;
+  full_name: string;
+  owner?: RawUser;
+  description?: string | null;
+  stargazers_count?: number;
+  open_issues_count?: number;
+  language?: string | null;
+  default_branch?: string;
+  updated_at: string;
+  html_url: string;
+}
+
+interface RawIssue {
+  number: number;
+  title: string;
+  state: string;
+  labels?: Array<string | { name?: string }>;
+  user?: RawUser | null;
+  created_at: string;
+  html_url: string;
+  pull_request?: unknown;
+  draft?: boolean;
+}
+
+interface RawWorkflowRun {
+  id: number;
+  status: string;
+  conclusion?: string | null;
+  head_branch?: string;
+  created_at: string;
+  html_url: string;
+}
+
+/**
+ * Extracts owner and repository from a github.com URL
+ *
+ * @param url - Repository URL, e.g. https://github.com/owner/repo
+ * @returns Owner and repository, or undefined if the URL is not recognised
+ * @public
+ */
+export function parseGitHubUrl(url: string): { owner: string; repo: string } | undefined {
+  const match = url.match(/github\.com[/:]([^/]+)\/([^/#?]+)/);
+  return match ? { owner: match[1], repo: match[2].replace(/\.git$/, "") } : undefined;
+}
+
+/**
+ * Client for the GitHub REST API
+ * @public
+ */
+export class GitHubClient extends ForgeApiClient {
+  protected readonly label = "GitHub";
+
+  constructor(config: GitHubConfig = {}, options: ForgeApiOptions = {}) {
+    super({
+      apiUrl: config.apiUrl || DEFAULT_API_URL,
+      token: config.token || process.env.GITHUB_TOKEN || process.env.GH_TOKEN,
+      fixtures: config.fixtures || process.env.AGENTMESH_GITHUB_FIXTURES,
+      record: config.record,
+    }, options);
+  }
+
+  protected headers(): Record<string, string> {
+    const headers: Record<string, string> = {
+      Accept: "application/vnd.github+json",
+      "X-GitHub-Api-Version": "2022-11-28",
+    };
+    if (this.token) headers.Authorization = `Bearer ${this.token}`;
+    return headers;
+  }
+
+  protected tokenHint(): string {
+    return "Set GITHUB_TOKEN (or github.token in the config) for a higher limit";
+  }
+
+  /**
+   * Reads a repository
+   */
+  async getRepo(owner: string, repo: string): Promise<ForgeRepo> {
+    const { body: raw } = await this.request<RawRepo>("GET", `/repos/${owner}/${repo}`);
+    return {
+      owner: raw.owner?.login ?? owner,
+      name: raw.name,
+      fullName: raw.full_name,
+      description: raw.description ?? undefined,
+      stars: raw.stargazers_count ?? 0,
+      openIssues: raw.open_issues_count ?? 0,
+      language: raw.language ?? undefined,
+      defaultBranch: raw.default_branch ?? "main",
+      updatedAt: raw.updated_at,
+      url: raw.html_url,
+    };
+  }
+
+  /**
+   * Lists the issues of a repository
+   *
+   * GitHub returns pull requests from the same endpoint; they count towards
+   * `maxItems` but are dropped from the result.
+   */
+  async listIssues(owner: string, repo: string, options: ListOptions = {}): Promise<ForgeIssue[]> {
+    const raw = await this.paginate<RawIssue>(`/repos/${owner}/${repo}/issues?state=${options.state ?? "open"}`, options.maxItems);
+    return raw
+      .filter((issue) => !issue.pull_request)
+      .map((issue) => ({
+        number: issue.number,
+        title: issue.title,
+        state: issue.state,
+        labels: (issue.labels ?? []).map((l) => (typeof l === "string" ? l : l.name ?? "")).filter(Boolean),
+        author: issue.user?.login,
+        createdAt: issue.created_at,
+        url: issue.html_url,
+      }));
+  }
+
+  /**
+   * Lists the pull requests of a repository
+   */
+  async listPullRequests(owner: string, repo: string, options: ListOptions = {}): Promise<ForgePullRequest[]> {
+    const raw = await this.paginate<RawIssue>(`/repos/${owner}/${repo}/pulls?state=${options.state ?? "open"}`, options.maxItems);
+    return raw.map((pr) => ({
+      number: pr.number,
+      title: pr.title,
+      state: pr.state,
+      draft: pr.draft ?? false,
+      author: pr.user?.login,
+      createdAt: pr.created_at,
+      url: pr.html_url,
+    }));
+  }
+
+  /**
+   * Lists the latest GitHub Actions runs of a repository, newest first
+   */
+  async listPipelines(owner: string, repo: string, options: ListOptions = {}): Promise<ForgePipeline[]> {
+    const raw = await this.paginate<RawWorkflowRun>(
+      `/repos/${owner}/${repo}/actions/runs`, options.maxItems, (body) => (body as { workflow_runs?: RawWorkflowRun[] })?.workflow_runs,
+    );
+    return raw.map((run) => {
+      // Runs only get a conclusion once they completed
+      const status = run.conclusion || run.status;
+      return { id: run.id, status, state: pipelineState(status), ref: run.head_branch, createdAt: run.created_at, url: run.html_url };
+    });
+  }
+
+  /**
+   * Lists the contributors of a repository, most commits first
+   */
+  async listContributors(owner: string, repo: string, options: ListOptions = {}): Promise<ForgeContributor[]> {
+    const raw = await this.paginate<{ login?: string; name?: string; contributions: number }>(
+      `/repos/${owner}/${repo}/contributors`, options.maxItems,
+    );
+    return raw.map((c) => ({ name: c.login || c.name || "anonymous", contributions: c.contributions }));
+  }
+
+  /**
+   * Opens a pull request
+   *
+   * @returns Number and web URL of the pull request
+   */
+  async createPullRequest(
+    owner: string,
+    repo: string,
+    spec: { title: string; body: string; head: string; base: string }
+  ): Promise<{ number: number; url: string }> {
+    const { body } = await this.request<{ number: number; html_url: string }>("POST", `/repos/${owner}/${repo}/pulls`, spec);
+    return { number: body.number, url: body.html_url };
+  }
+}
+
+/**
+ * Client for the configured GitHub API
+ *
+ * @param overrides - Settings overriding the config
+ * @returns GitHub client
+ * @public
+ */
+export function getGitHubClient(overrides: GitHubConfig = {}): GitHubClient {
+  return new GitHubClient({ ...loadConfig().github, ...overrides });
+}
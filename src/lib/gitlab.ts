@@ -0,0 +1,199 @@
+/**
+ * GitLab API Client for AgentMesh
+ * Reads projects, issues, merge requests, pipelines and contributors through
+ * the GitLab REST API (v4) of gitlab.com or a self-hosted instance
+ *
+ * Projects are addressed by their full path ("group/subgroup/project").
+ * The token comes from `gitlab.token` in the config, then GITLAB_TOKEN, and
+ * is sent as PRIVATE-TOKEN. Paging, rate limits, retries and fixtures are
+ * handled by the forge-api base class.
+ *
+ * @module gitlab
+ */
+
+import { loadConfig, type SelfHostedForgeConfig } from "./config";
+import {
+  ForgeApiClient,
+  pipelineState,
+  topLanguage,
+  type ForgeApiOptions,
+  type ForgeContributor,
+  type ForgeIssue,
+  type ForgePipeline,
+  type ForgePullRequest,
+  type ForgeRepo,
+  type ListOptions,
+} from "./forge-api";
+
+// GitLab calls open items "opened"
+const STATES = { open: "opened", closed: "closed", all: "all" };
+
+interface RawUser {
+  username?: string;
+}
+
+interface RawProject {
+  name: string;
+  path_with_namespace: string;
+  namespace?: { full_path?: string };
+  description?: string | null;
+  star_count?: number;
+  open_issues_count?: number;
+  default_branch?: string;
+  last_activity_at: string;
+  web_url: string;
+}
+
+interface RawIssue {
+  iid: number;
+  title: string;
+  state: string;
+  labels?: string[];
+  author?: RawUser | null;
+  created_at: string;
+  web_url: string;
+  draft?: boolean;
+  work_in_progress?: boolean;
+}
+
+interface RawPipeline {
+  id: number;
+  status: string;
+  ref?: string;
+  created_at: string;
+  web_url?: string;
+}
+
+/**
+ * Client for the GitLab REST API
+ * @public
+ */
+export class GitLabClient extends ForgeApiClient {
+  protected readonly label = "GitLab";
+
+  /**
+   * @param origin - Instance URL, e.g. https://gitlab.com
+   * @param config - Token and fixture settings
+   * @param options - Retry tuning
+   */
+  constructor(origin: string, config: SelfHostedForgeConfig = {}, options: ForgeApiOptions = {}) {
+    super({
+      apiUrl: `${origin.replace(/\/$/, "")}/api/v4`,
+      token: config.token || process.env.GITLAB_TOKEN,
+      fixtures: config.fixtures,
+      record: config.record,
+    }, options);
+  }
+
+  protected headers(): Record<string, string> {
+    const headers: Record<string, string> = { Accept: "application/json" };
+    if (this.token) headers["PRIVATE-TOKEN"] = this.token;
+    return headers;
+  }
+
+  protected tokenHint(): string {
+    return "Set GITLAB_TOKEN (or gitlab.token in the config)";
+  }
+
+  /**
+   * Reads a project, with its main language
+   *
+   * @param projectPath - Full path, e.g. "group/project"
+   */
+  async getProject(projectPath: string): Promise<ForgeRepo> {
+    const [{ body: raw }, { body: languages }] = await Promise.all([
+      this.request<RawProject>("GET", project(projectPath)),
+      this.request<Record<string, number>>("GET", `${project(projectPath)}/languages`),
+    ]);
+    return {
+      owner: raw.namespace?.full_path ?? projectPath.slice(0, projectPath.lastIndexOf("/")),
+      name: raw.name,
+      fullName: raw.path_with_namespace,
+      description: raw.description ?? undefined,
+      stars: raw.star_count ?? 0,
+      openIssues: raw.open_issues_count ?? 0,
+      language: topLanguage(languages),
+      defaultBranch: raw.default_branch ?? "main",
+      updatedAt: raw.last_activity_at,
+      url: raw.web_url,
+    };
+  }
+
+  /**
+   * Lists the issues of a project
+   */
+  async listIssues(projectPath: string, options: ListOptions = {}): Promise<ForgeIssue[]> {
+    const raw = await this.paginate<RawIssue>(
+      `${project(projectPath)}/issues?state=${STATES[options.state ?? "open"]}`, options.maxItems,
+    );
+    return raw.map((issue) => ({
+      number: issue.iid,
+      title: issue.title,
+      state: issue.state,
+      labels: issue.labels ?? [],
+      author: issue.author?.username,
+      createdAt: issue.created_at,
+      url: issue.web_url,
+    }));
+  }
+
+  /**
+   * Lists the merge requests of a project
+   */
+  async listMergeRequests(projectPath: string, options: ListOptions = {}): Promise<ForgePullRequest[]> {
+    const raw = await this.paginate<RawIssue>(
+      `${project(projectPath)}/merge_requests?state=${STATES[options.state ?? "open"]}`, options.maxItems,
+    );
+    return raw.map((mr) => ({
+      number: mr.iid,
+      title: mr.title,
+      state: mr.state,
+      // work_in_progress is the name older GitLab versions use
+      draft: mr.draft ?? mr.work_in_progress ?? false,
+      author: mr.author?.username,
+      createdAt: mr.created_at,
+      url: mr.web_url,
+    }));
+  }
+
+  /**
+   * Lists the latest pipelines of a project, newest first
+   */
+  async listPipelines(projectPath: string, options: ListOptions = {}): Promise<ForgePipeline[]> {
+    const raw = await this.paginate<RawPipeline>(`${project(projectPath)}/pipelines?order_by=id&sort=desc`, options.maxItems);
+    return raw.map((p) => ({
+      id: p.id,
+      status: p.status,
+      state: pipelineState(p.status),
+      ref: p.ref,
+      createdAt: p.created_at,
+      url: p.web_url,
+    }));
+  }
+
+  /**
+   * Lists the contributors of a project, most commits first
+   */
+  async listContributors(projectPath: string, options: ListOptions = {}): Promise<ForgeContributor[]> {
+    const raw = await this.paginate<{ name: string; commits: number }>(
+      `${project(projectPath)}/repository/contributors?order_by=commits&sort=desc`, options.maxItems,
+    );
+    return raw.map((c) => ({ name: c.name, contributions: c.commits }));
+  }
+}
+
+/** API path of a project */
+function project(projectPath: string): string {
+  return `/projects/${encodeURIComponent(projectPath)}`;
+}
+
+/**
+ * Client for a GitLab instance, with the configured token
+ *
+ * @param origin - Instance URL, e.g. https://gitlab.com
+ * @returns GitLab client
+ * @public
+ */
+export function getGitLabClient(origin: string): GitLabClient {
+  return new GitLabClient(origin, loadConfig().gitlab);
+}
@@ -0,0 +1,500 @@
+/**
+ * Repository Health Rules for AgentMesh
+ * Scores repository metrics and routes findings to AgentMesh tools
+ *
+ * Rules come from `.agentmesh/health-rules.yaml` next to the config file;
+ * without one the built-in rules below apply. A user file replaces the
+ * built-in rules as a whole.
+ *
+ * ```yaml
+ * labels:                       # issue labels counted, by substring
+ *   bugReports: [bug, defect]
+ * rules:
+ *   - id: bug-reports
+ *     description: Bug reports
+ *     when: { metric: bugReports, above: 0 }
+ *     severity: HIGH            # CRITICAL, HIGH, MEDIUM or LOW
+ *     weight: 20                # points off the health score (default by severity)
+ *     tool: fix_issues
+ *     arguments: { target: "." }
+ *     item: "{bugReports} bug(s) reported"
+ *     recommendation: Address bug fixes before new features
+ * ```
+ *
+ * Predicates:
+ * - `{ metric, above | atLeast | below | atMost | equals }` compares a metric;
+ *   every bound given must hold
+ * - `{ mentions: [...] }` looks for whole words or phrases in a free-text
+ *   summary (case-insensitive). Once any metric is measured the metrics
+ *   decide and `mentions` never holds, since "no bugs" mentions "bugs" too
+ * - `{ all: [...] }` and `{ any: [...] }` combine predicates
+ *
+ * A predicate on a metric that was not measured, or on text when there is
+ * none, is left undecided and the rule does not fire. The health score
+ * starts at 100 and loses each fired rule's weight.
+ *
+ * @module health-rules
+ */
+
+import * as fs from "fs";
+import * as path from "path";
+import { parse as parseYaml } from "yaml";
+import { z } from "zod";
+import { getConfigPath } from "./config";
+import { INVOCABLE_TOOLS } from "./tool-registry";
+
+/**
+ * Metrics rules can test, with the label they are printed under in summaries
+ */
+export const METRICS = {
+  openIssues: "Open Issues",
+  securityIssues: "Security Issues",
+  bugReports: "Bug Reports",
+  pendingPRs: "Pending PRs",
+  failedCiRuns: "Failed CI Runs",
+  ciRuns: "CI Runs",
+  contributors: "Contributors",
+  activeContributors: "Active Contributors",
+  commits: "Commits",
+  recentCommits: "Recent Commits",
+  daysSinceLastCommit: "Days Since Last Commit",
+  branches: "Branches",
+  staleBranches: "Stale Branches",
+  tags: "Tags",
+  todoMarkers: "TODO Markers",
+  fixmeMarkers: "FIXME Markers",
+  sourceFiles: "Source Files",
+  testFiles: "Test Files",
+  testRatio: "Test Ratio",
+  lockfileAgeDays: "Lockfile Age (days)",
+} as const;
+
+export type MetricName = keyof typeof METRICS;
+/** Measured metrics; unmeasured ones are left out */
+export type Metrics = Partial<Record<MetricName, number>>;
+
+export const SEVERITIES = ["CRITICAL", "HIGH", "MEDIUM", "LOW"] as const;
+export type Severity = typeof SEVERITIES[number];
+
+/** Overall ratings, best first */
+export const HEALTH_LEVELS = ["HEALTHY", "MODERATE", "NEEDS_ATTENTION", "CRITICAL"] as const;
+export type HealthLevel = typeof HEALTH_LEVELS[number];
+
+// Points a fired rule takes off the score unless it sets a weight
+const DEFAULT_WEIGHTS: Record<Severity, number> = { CRITICAL: 40, HIGH: 20, MEDIUM: 10, LOW: 5 };
+
+const RULE_ID = /^[a-z0-9][a-z0-9_-]*$/i;
+
+/**
+ * A rule condition
+ */
+export type Predicate =
+  | { metric: MetricName; above?: number; atLeast?: number; below?: number; atMost?: number; equals?: number }
+  | { mentions: string[] }
+  | { all: Predicate[] }
+  | { any: Predicate[] };
+
+const BOUNDS = { above: ">", atLeast: ">=", below: "<", atMost: "<=", equals: "==" } as const;
+
+const metricPredicateSchema = z.object({
+  metric: z.enum(Object.keys(METRICS) as [MetricName, ...MetricName[]]),
+  above: z.number().optional(),
+  atLeast: z.number().optional(),
+  below: z.number().optional(),
+  atMost: z.number().optional(),
+  equals: z.number().optional(),
+}).strict().refine(
+  (p) => Object.keys(BOUNDS).some((bound) => p[bound as keyof typeof BOUNDS] !== undefined),
+  { message: "give at least one of above, atLeast, below, atMost or equals" },
+);
+
+const predicateSchema: z.ZodType<Predicate> = z.lazy(() => z.union([
+  metricPredicateSchema,
+  z.object({ mentions: z.array(z.string().min(1)).min(1) }).strict(),
+  z.object({ all: z.array(predicateSchema).min(1) }).strict(),
+  z.object({ any: z.array(predicateSchema).min(1) }).strict(),
+]));
+
+const ruleSchema = z.object({
+  id: z.string().regex(RULE_ID, "use letters, digits, \"-\" and \"_\""),
+  /** What the rule looks for, shown when it fires */
+  description: z.string().min(1),
+  when: predicateSchema,
+  severity: z.enum(SEVERITIES),
+  /** Points taken off the health score when the rule fires (default by severity) */
+  weight: z.number().min(0).max(100).optional(),
+  /** AgentMesh tool execute-decision runs for the rule */
+  tool: z.enum(INVOCABLE_TOOLS).optional(),
+  /** Arguments for the tool, exactly as for a direct call */
+  arguments: z.record(z.unknown()).optional(),
+  /** Priority line; {metric} placeholders are replaced by values (default: the description) */
+  item: z.string().min(1).optional(),
+  recommendation: z.string().min(1).optional(),
+}).strict();
+
+const labelsSchema = z.object({
+  securityIssues: z.array(z.string().min(1)).min(1).optional(),
+  bugReports: z.array(z.string().min(1)).min(1).optional(),
+}).strict();
+
+const rulesFileSchema = z.object({
+  /** Issue label substrings (case-insensitive) counted as security issues and bug reports */
+  labels: labelsSchema.optional(),
+  rules: z.array(ruleSchema),
+}).strict().superRefine((file, ctx) => {
+  const seen = new Set<string>();
+  file.rules.forEach((rule, index) => {
+    if (seen.has(rule.id)) {
+      ctx.addIssue({ code: z.ZodIssueCode.custom, path: ["rules", index, "id"], message: `Duplicate rule id "${rule.id}"` });
+    }
+    seen.add(rule.id);
+  });
+});
+
+export type HealthRule = z.infer<typeof ruleSchema>;
+export type IssueLabels = Required<z.infer<typeof labelsSchema>>;
+
+/**
+ * A set of rules and where it came from
+ * @interface HealthRules
+ */
+export interface HealthRules {
+  labels: IssueLabels;
+  rules: HealthRule[];
+  /** File the rules were loaded from, or "built-in" */
+  source: string;
+}
+
+/** Issue labels counted when the rules file sets none */
+export const DEFAULT_ISSUE_LABELS: IssueLabels = { securityIssues: ["security"], bugReports: ["bug"] };
+
+/** Rules used when no rules file exists; they reproduce the original priorities */
+export const DEFAULT_HEALTH_RULES: HealthRule[] = [
+  {
+    id: "security-issues",
+    description: "Open security issues",
+    when: { metric: "securityIssues", above: 0 },
+    severity: "CRITICAL",
+    tool: "security_audit",
+    arguments: { target: "." },
+    item: "{securityIssues} security issue(s)",
+    recommendation: "Run security audit immediately",
+  },
+  {
+    id: "bug-reports",
+    description: "Open bug reports",
+    when: { metric: "bugReports", above: 0 },
+    severity: "HIGH",
+    tool: "fix_issues",
+    arguments: { target: "." },
+    item: "{bugReports} bug(s) reported",
+    recommendation: "Address bug fixes before new features",
+  },
+  {
+    id: "failing-ci",
+    description: "CI runs failing",
+    when: { metric: "failedCiRuns", atLeast: 2 },
+    severity: "HIGH",
+    tool: "fix_issues",
+    arguments: { target: ".", issueTypes: ["lint", "types"] },
+    item: "{failedCiRuns} of the last {ciRuns} CI runs failed",
+    recommendation: "Get the CI pipeline green again",
+  },
+  {
+    id: "fixme-markers",
+    description: "FIXME markers in the code",
+    when: { metric: "fixmeMarkers", above: 0 },
+    severity: "HIGH",
+    weight: 10,
+    tool: "fix_issues",
+    arguments: { target: "." },
+    item: "{fixmeMarkers} FIXME marker(s) in the code",
+    recommendation: "Resolve FIXME markers before new features",
+  },
+  {
+    id: "review-backlog",
+    description: "Pull requests awaiting review",
+    when: { any: [{ metric: "pendingPRs", above: 3 }, { mentions: ["review", "pull request", "pull requests", "PRs"] }] },
+    severity: "MEDIUM",
+    tool: "review_code",
+    arguments: { target: "." },
+    item: "{pendingPRs} PRs awaiting review",
+    recommendation: "Review and merge pending PRs to reduce backlog",
+  },
+  {
+    id: "stale-lockfile",
+    description: "Dependencies not updated for half a year",
+    when: { metric: "lockfileAgeDays", above: 180 },
+    severity: "MEDIUM",
+    tool: "security_audit",
+    arguments: { target: ".", scanTypes: ["dependencies"] },
+    item: "Lockfile not updated in {lockfileAgeDays} days",
+    recommendation: "Update dependencies and audit them for known vulnerabilities",
+  },
+  {
+    id: "missing-tests",
+    description: "Few tests for the code",
+    when: {
+      any: [
+        { all: [{ metric: "sourceFiles", above: 0 }, { metric: "testRatio", below: 0.2 }] },
+        { mentions: ["untested", "missing tests", "no tests", "test coverage"] },
+      ],
+    },
+    severity: "MEDIUM",
+    tool: "generate_tests",
+    arguments: { target: "." },
+    item: "{testFiles} test file(s) for {sourceFiles} source file(s)",
+    recommendation: "Add tests for the untested modules",
+  },
+  {
+    id: "issue-backlog",
+    description: "Large issue backlog",
+    when: { metric: "openIssues", above: 20 },
+    severity: "LOW",
+    weight: 10,
+    item: "{openIssues} open issues",
+    recommendation: "Triage the issue backlog",
+  },
+  {
+    id: "stale-branches",
+    description: "Branches without commits for 90 days",
+    when: { metric: "staleBranches", above: 5 },
+    severity: "LOW",
+    tool: "git_assist",
+    arguments: { operation: "explain-history" },
+    item: "{staleBranches} branches without commits in 90 days",
+    recommendation: "Merge or delete stale branches",
+  },
+];
+
+/**
+ * File user-defined rules are loaded from
+ * @public
+ */
+export function getHealthRulesPath(): string {
+  return path.join(path.dirname(getConfigPath()), "health-rules.yaml");
+}
+
+/**
+ * Loads the health rules
+ *
+ * @param file - Rules file to use instead of the default location
+ * @returns The rules of `file`, else of `.agentmesh/health-rules.yaml`, else the built-in rules
+ * @throws {Error} If the rules file is not valid YAML or does not match the schema
+ * @public
+ */
+export function loadHealthRules(file?: string): HealthRules {
+  const target = file ?? getHealthRulesPath();
+  if (!file && !fs.existsSync(target)) {
+    return { labels: DEFAULT_ISSUE_LABELS, rules: DEFAULT_HEALTH_RULES, source: "built-in" };
+  }
+
+  let raw: unknown;
+  try {
+    raw = parseYaml(fs.readFileSync(target, "utf8"));
+  } catch (error) {
+    throw new Error(`[AgentMesh] Invalid health rules ${target}: ${(error as Error).message}`);
+  }
+  const parsed = rulesFileSchema.safeParse(raw);
+  if (!parsed.success) {
+    const issues = parsed.error.issues.map((i) => `${i.path.join(".") || "(root)"}: ${i.message}`).join("; ");
+    throw new Error(`[AgentMesh] Invalid health rules ${target}: ${issues}`);
+  }
+  return { labels: { ...DEFAULT_ISSUE_LABELS, ...parsed.data.labels }, rules: parsed.data.rules, source: target };
+}
+
+/**
+ * Formats metrics as summary lines ("Label: value"), in catalogue order
+ *
+ * @param metrics - Measured metrics
+ * @returns One line per measured metric
+ * @public
+ */
+export function formatMetrics(metrics: Metrics): string[] {
+  return (Object.keys(METRICS) as MetricName[])
+    .filter((name) => metrics[name] !== undefined)
+    .map((name) => `${METRICS[name]}: ${Number((metrics[name] as number).toFixed(2))}`);
+}
+
+/**
+ * Reads metrics back from a summary
+ *
+ * Lines written by `formatMetrics` ("Bug Reports: 3") become metrics; the
+ * rest of the text is kept for `mentions` predicates.
+ *
+ * @param summary - Summary from analyze-repo, analyze-local or a Kestra flow
+ * @returns Metrics found and the remaining text
+ * @public
+ */
+export function parseSummary(summary: string): { metrics: Metrics; text: string } {
+  const byLabel = new Map<string, MetricName>();
+  for (const name of Object.keys(METRICS) as MetricName[]) byLabel.set(METRICS[name].toLowerCase(), name);
+
+  const metrics: Metrics = {};
+  const rest: string[] = [];
+  for (const line of summary.split("\n")) {
+    const match = line.match(/^\s*(?:[-*]\s*)?([^:]+?)\s*:\s*(-?\d+(?:\.\d+)?)\s*$/);
+    const name = match ? byLabel.get(match[1].toLowerCase()) : undefined;
+    if (match && name) metrics[name] = Number(match[2]);
+    else rest.push(line);
+  }
+  return { metrics, text: rest.join("\n") };
+}
+
+/**
+ * How a rule turned out
+ * @interface RuleResult
+ */
+export interface RuleResult {
+  rule: HealthRule;
+  fired: boolean;
+  /** The predicate with the values it saw, e.g. "bugReports = 3 > 0" */
+  explanation: string;
+  /** Metrics (or "text") the predicate needed but did not get */
+  missing: string[];
+  /** Points taken off the health score */
+  contribution: number;
+  /** Priority line with metric values filled in */
+  item: string;
+}
+
+/**
+ * Outcome of scoring a repository
+ * @interface HealthEvaluation
+ */
+export interface HealthEvaluation {
+  /** 0 (worst) to 100 */
+  score: number;
+  health: HealthLevel;
+  /** Every rule, in file order */
+  results: RuleResult[];
+  /** Fired rules, most severe first */
+  fired: RuleResult[];
+  /** Where the rules came from */
+  source: string;
+}
+
+interface Outcome {
+  /** undefined when the inputs were missing */
+  value: boolean | undefined;
+  explanation: string;
+  missing: string[];
+}
+
+function escapeRegExp(text: string): string {
+  return text.replace(/[.*+?^${}()|[\]\\]/g, "\\$&");
+}
+
+function check(predicate: Predicate, metrics: Metrics, text: string | undefined): Outcome {
+  if ("metric" in predicate) {
+    const value = metrics[predicate.metric];
+    const bounds = (Object.keys(BOUNDS) as Array<keyof typeof BOUNDS>).filter((bound) => predicate[bound] !== undefined);
+    const expected = bounds.map((bound) => `${BOUNDS[bound]} ${predicate[bound]}`).join(" and ");
+    if (value === undefined) {
+      return { value: undefined, explanation: `${predicate.metric} ${expected} (not measured)`, missing: [predicate.metric] };
+    }
+    const holds = bounds.every((bound) => {
+      const limit = predicate[bound] as number;
+      switch (bound) {
+        case "above": return value > limit;
+        case "atLeast": return value >= limit;
+        case "below": return value < limit;
+        case "atMost": return value <= limit;
+        case "equals": return value === limit;
+      }
+    });
+    return { value: holds, explanation: `${predicate.metric} = ${value} ${expected}`, missing: [] };
+  }
+
+  if ("mentions" in predicate) {
+    const words = predicate.mentions.map((w) => `"${w}"`).join(", ");
+    if (Object.keys(metrics).length) return { value: false, explanation: `mentions ${words} (metrics decide)`, missing: [] };
+    if (text === undefined) return { value: undefined, explanation: `mentions ${words} (no text)`, missing: ["text"] };
+    const found = predicate.mentions.filter((word) => new RegExp(`\\b${escapeRegExp(word)}\\b`, "i").test(text));
+    return found.length
+      ? { value: true, explanation: `mentions "${found[0]}"`, missing: [] }
+      : { value: false, explanation: `mentions none of ${words}`, missing: [] };
+  }
+
+  const all = "all" in predicate;
+  const outcomes = (all ? (predicate as { all: Predicate[] }).all : (predicate as { any: Predicate[] }).any)
+    .map((p) => check(p, metrics, text));
+  // all: one false decides; any: one true decides; otherwise missing inputs leave it open
+  const decisive = outcomes.filter((o) => o.value === !all);
+  const missing = outcomes.reduce<string[]>((names, o) => names.concat(o.missing), []);
+  let value: boolean | undefined;
+  if (decisive.length) value = !all;
+  else if (outcomes.some((o) => o.value === undefined)) value = undefined;
+  else value = all;
+  const shown = decisive.length ? decisive : outcomes;
+  return {
+    value,
+    explanation: shown.map((o) => (shown.length > 1 ? `(${o.explanation})` : o.explanation)).join(all ? " and " : " or "),
+    missing: value === undefined ? missing : [],
+  };
+}
+
+function fillItem(rule: HealthRule, metrics: Metrics): string {
+  return (rule.item ?? rule.description).replace(/\{(\w+)\}/g, (placeholder, name: string) => {
+    const value = metrics[name as MetricName];
+    return name in METRICS ? (value === undefined ? "?" : String(Number(value.toFixed(2)))) : placeholder;
+  });
+}
+
+/**
+ * Scores metrics (and optionally a free-text summary) against rules
+ *
+ * @param rules - Rules to apply
+ * @param input - Measured metrics, and text for `mentions` predicates
+ * @returns Score, health level and the outcome of every rule
+ * @public
+ */
+export function evaluateHealthRules(rules: HealthRules, input: { metrics: Metrics; text?: string }): HealthEvaluation {
+  const results = rules.rules.map((rule): RuleResult => {
+    const outcome = check(rule.when, input.metrics, input.text);
+    const fired = outcome.value === true;
+    return {
+      rule,
+      fired,
+      explanation: outcome.explanation,
+      missing: outcome.missing,
+      contribution: fired ? rule.weight ?? DEFAULT_WEIGHTS[rule.severity] : 0,
+      item: fillItem(rule, input.metrics),
+    };
+  });
+
+  const fired = results
+    .filter((r) => r.fired)
+    .sort((a, b) => SEVERITIES.indexOf(a.rule.severity) - SEVERITIES.indexOf(b.rule.severity));
+  const score = Math.max(0, 100 - results.reduce((sum, r) => sum + r.contribution, 0));
+
+  let health: HealthLevel = "HEALTHY";
+  if (score < 50 || fired.some((r) => r.rule.severity === "CRITICAL")) health = "CRITICAL";
+  else if (score < 75) health = "NEEDS_ATTENTION";
+  else if (score < 90) health = "MODERATE";
+
+  return { score, health, results, fired, source: rules.source };
+}
+
+/**
+ * Explains which rules fired and why, for dry runs
+ *
+ * @param evaluation - Result of `evaluateHealthRules`
+ * @returns Markdown list of every rule
+ * @public
+ */
+export function explainHealthRules(evaluation: HealthEvaluation): string {
+  const lines = evaluation.results.map((r) => {
+    const route = r.rule.tool ? ` → \`${r.rule.tool}\`` : "";
+    if (r.fired) {
+      return `- ✅ **${r.rule.id}** (${r.rule.severity}, -${r.contribution}): ${r.explanation}${route}`;
+    }
+    if (r.missing.length) {
+      return `- ⏭️ **${r.rule.id}**: not evaluated, ${r.missing.join(", ")} missing`;
+    }
+    return `- ▫️ **${r.rule.id}**: ${r.explanation}`;
+  });
+  return `## Rules (${evaluation.source})\n` +
+    `Health Score: ${evaluation.score}/100 (${evaluation.fired.length} of ${evaluation.results.length} rules fired)\n\n` +
+    lines.join("\n");
+}
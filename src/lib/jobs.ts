@@ -0,0 +1,236 @@
+/**
+ * Background Jobs for AgentMesh
+ * Runs long agent tasks outside the MCP request so clients can poll or cancel
+ *
+ * Jobs live in memory for the lifetime of the server process. A workflow step
+ * that needs approval parks its job in "awaiting_approval" until
+ * `decideApproval` is called.
+ *
+ * @module jobs
+ */
+
+import { randomUUID } from "crypto";
+import type { AgentEvent } from "./agent";
+import { withRunContext, type ApprovalDecision, type ApprovalRequest, type StepInfo } from "./run-context";
+
+// Keep at most this many finished jobs around for polling
+const MAX_FINISHED_JOBS = 100;
+
+export type JobState = "running" | "awaiting_approval" | "completed" | "failed" | "cancelled";
+
+/**
+ * A background tool invocation
+ * @interface Job
+ */
+export interface Job {
+  /** Job identifier */
+  id: string;
+  /** Tool being run */
+  tool: string;
+  /** Arguments the tool was called with */
+  args: Record<string, unknown>;
+  /** Current state; "completed" means the tool returned, not that the task succeeded */
+  state: JobState;
+  createdAt: Date;
+  finishedAt?: Date;
+  /** Agent events seen so far (partial updates collapsed) */
+  events: AgentEvent[];
+  /** Current step of multi-step tools */
+  step?: StepInfo;
+  /** Step waiting for a decision (while "awaiting_approval") */
+  approval?: ApprovalRequest & { requestedAt: Date };
+  /** Final tool output */
+  output?: string;
+  /** Error thrown by the tool */
+  error?: string;
+}
+
+interface JobEntry {
+  job: Job;
+  controller: AbortController;
+  done: Promise<void>;
+  /** Settles the pending approval */
+  decide?: (decision: ApprovalDecision | Error) => void;
+}
+
+const jobs = new Map<string, JobEntry>();
+
+function recordEvent(job: Job, event: AgentEvent): void {
+  const last = job.events[job.events.length - 1];
+  if (last && event.ts !== undefined && last.ts === event.ts && last.kind === event.kind) {
+    job.events[job.events.length - 1] = event;
+  } else {
+    job.events.push(event);
+  }
+}
+
+/**
+ * Whether a job has not finished yet
+ * @public
+ */
+export function isJobActive(job: Job): boolean {
+  return job.state === "running" || job.state === "awaiting_approval";
+}
+
+function prune(): void {
+  const finished = [...jobs.values()].filter((e) => !isJobActive(e.job));
+  for (const entry of finished.slice(0, Math.max(0, finished.length - MAX_FINISHED_JOBS))) {
+    jobs.delete(entry.job.id);
+  }
+}
+
+/**
+ * Starts a job in the background
+ *
+ * @param tool - Tool name, for display
+ * @param args - Tool arguments, for display
+ * @param run - The work; agent runs inside it are cancellable and report events
+ * @returns The new job
+ * @public
+ */
+export function startJob(tool: string, args: Record<string, unknown>, run: () => Promise<string>): Job {
+  const controller = new AbortController();
+  const job: Job = {
+    id: randomUUID(),
+    tool,
+    args,
+    state: "running",
+    createdAt: new Date(),
+    events: [],
+  };
+
+  const entry: JobEntry = { job, controller, done: Promise.resolve() };
+
+  entry.done = withRunContext({
+    signal: controller.signal,
+    onEvent: (e) => recordEvent(job, e),
+    onStep: (step) => { job.step = step; },
+    onApproval: (request) => new Promise<ApprovalDecision>((resolve, reject) => {
+      job.state = "awaiting_approval";
+      job.approval = { ...request, requestedAt: new Date() };
+      entry.decide = (decision) => {
+        entry.decide = undefined;
+        job.approval = undefined;
+        if (decision instanceof Error) {
+          reject(decision);
+        } else {
+          job.state = "running";
+          resolve(decision);
+        }
+      };
+    }),
+  }, run)
+    .then((output) => {
+      job.output = output;
+      if (job.state === "running") job.state = "completed";
+    })
+    .catch((err) => {
+      job.error = err instanceof Error ? err.message : String(err);
+      if (job.state === "running") job.state = "failed";
+    })
+    .then(() => {
+      // Runs after either handler above (Promise.prototype.finally is ES2018)
+      job.finishedAt = new Date();
+      prune();
+    });
+
+  jobs.set(job.id, entry);
+  return job;
+}
+
+/**
+ * Looks up a job
+ * @public
+ */
+export function getJob(id: string): Job | undefined {
+  return jobs.get(id)?.job;
+}
+
+/**
+ * Lists jobs, newest first
+ * @public
+ */
+export function listJobs(state?: JobState): Job[] {
+  return [...jobs.values()]
+    .map((e) => e.job)
+    .filter((job) => !state || job.state === state)
+    .sort((a, b) => b.createdAt.getTime() - a.createdAt.getTime());
+}
+
+/**
+ * Cancels a running job, killing its agent process
+ *
+ * @param id - Job identifier
+ * @returns The job, or undefined if unknown
+ * @public
+ */
+export function cancelJob(id: string): Job | undefined {
+  const entry = jobs.get(id);
+  if (!entry) return undefined;
+  if (isJobActive(entry.job)) {
+    entry.job.state = "cancelled";
+    entry.controller.abort();
+    entry.decide?.(new Error("Job cancelled while awaiting approval"));
+  }
+  return entry.job;
+}
+
+/**
+ * Answers the approval a job is waiting for
+ *
+ * @param id - Job identifier
+ * @param decision - Reviewer's decision
+ * @returns The job (unchanged unless it was awaiting approval), or undefined if unknown
+ * @public
+ */
+export function decideApproval(id: string, decision: ApprovalDecision): Job | undefined {
+  const entry = jobs.get(id);
+  entry?.decide?.(decision);
+  return entry?.job;
+}
+
+/**
+ * Waits until a job finishes or the timeout elapses
+ *
+ * @param id - Job identifier
+ * @param timeoutMs - Maximum time to wait
+ * @returns The job, or undefined if unknown
+ * @public
+ */
+export async function waitForJob(id: string, timeoutMs: number): Promise<Job | undefined> {
+  const entry = jobs.get(id);
+  if (!entry) return undefined;
+  let timer: NodeJS.Timeout | undefined;
+  await Promise.race([
+    entry.done,
+    new Promise<void>((resolve) => { timer = setTimeout(resolve, timeoutMs); }),
+  ]);
+  clearTimeout(timer);
+  return entry.job;
+}
+
+/**
+ * Latest human-readable text an agent produced in this job
+ * @public
+ */
+export function latestJobText(job: Job): string | undefined {
+  return [...job.events].reverse()
+    .find((e) => ["text", "completion", "plan"].includes(e.kind) && e.text.trim())?.text;
+}
+
+const STATE_EMOJI: Record<JobState, string> = {
+  running: "⏳",
+  awaiting_approval: "⏸️",
+  completed: "✅",
+  failed: "❌",
+  cancelled: "🛑",
+};
+
+/**
+ * One-line summary of a job
+ * @public
+ */
+export function formatJobLine(job: Job): string {
+  const finished = job.finishedAt ? `, finished ${job.finishedAt.toISOString()}` : "";
+  return `${STATE_EMOJI[job.state]} \`${job.id}\` ${job.tool} (${job.state}, started ${job.createdAt.toISOString()}${finished})`;
+}
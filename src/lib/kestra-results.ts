@@ -0,0 +1,238 @@
+/**
+ * Kestra Result Store for AgentMesh
+ * Correlates Kestra executions with the AgentMesh request that started them
+ * and keeps the results flows report back through the `kestra_callback` tool
+ *
+ * Each request is a JSON file under `.agentmesh/kestra` (next to the config
+ * file), and `executions/` maps execution ids to requests. A callback for an
+ * execution AgentMesh did not start, e.g. a scheduled run, gets a request of
+ * its own. Only the newest requests are kept, so hourly schedules do not fill
+ * the directory.
+ *
+ * @module kestra-results
+ */
+
+import * as fs from "fs";
+import * as path from "path";
+import { createHash, randomUUID } from "crypto";
+import { getConfigPath } from "./config";
+import { parseRepositoryUrl } from "./repository";
+
+const REQUEST_ID = /^[0-9a-f]{8}-[0-9a-f]{4}-[0-9a-f]{4}-[0-9a-f]{4}-[0-9a-f]{12}$/;
+// Requests kept; older ones are deleted when a new one is stored
+export const MAX_KESTRA_REQUESTS = 200;
+
+/**
+ * Results a flow reported for an execution
+ * @interface KestraCallback
+ */
+export interface KestraCallback {
+  executionId: string;
+  /** Task that sent the callback */
+  taskId?: string;
+  /** State the flow reported, e.g. SUCCESS */
+  state?: string;
+  results: Record<string, unknown>;
+  receivedAt: string;
+}
+
+/**
+ * An AgentMesh request that started (or received results of) a Kestra execution
+ * @interface KestraRequestRecord
+ */
+export interface KestraRequestRecord {
+  id: string;
+  /** `kestra_code_intel` action that triggered the execution, or "callback" */
+  action: string;
+  repoUrl?: string;
+  executionId?: string;
+  namespace?: string;
+  flowId?: string;
+  createdAt: string;
+  /** Callbacks in the order they arrived */
+  callbacks: KestraCallback[];
+}
+
+/**
+ * Directory request records are stored in
+ * @public
+ */
+export function getKestraResultsDir(): string {
+  return path.join(path.dirname(getConfigPath()), "kestra");
+}
+
+// Execution ids come from Kestra, so they are hashed before becoming file names
+function executionIndexFile(executionId: string): string {
+  return path.join(getKestraResultsDir(), "executions", createHash("sha1").update(executionId).digest("hex"));
+}
+
+// Write then rename so readers never see half a file
+function writeAtomic(file: string, content: string): void {
+  fs.mkdirSync(path.dirname(file), { recursive: true });
+  fs.writeFileSync(`${file}.tmp`, content);
+  fs.renameSync(`${file}.tmp`, file);
+}
+
+// Compares repository URLs without trailing slashes, `.git` or deep links
+function sameRepository(a: string | undefined, b: string): boolean {
+  const normalise = (url: string) => parseRepositoryUrl(url)?.url ?? url.replace(/\/+$/, "");
+  return a !== undefined && normalise(a) === normalise(b);
+}
+
+function findIndexedRequest(executionId: string): string | undefined {
+  const file = executionIndexFile(executionId);
+  return fs.existsSync(file) ? fs.readFileSync(file, "utf8").trim() : undefined;
+}
+
+/**
+ * Deletes the oldest requests beyond MAX_KESTRA_REQUESTS
+ *
+ * @param keep - Request just stored, kept even when it ties with older ones
+ * @internal
+ */
+function prune(keep: string): void {
+  const stale = listKestraRequests(Infinity).filter((record) => record.id !== keep).slice(MAX_KESTRA_REQUESTS - 1);
+  for (const record of stale) {
+    fs.rmSync(path.join(getKestraResultsDir(), `${record.id}.json`), { force: true });
+    if (record.executionId && findIndexedRequest(record.executionId) === record.id) {
+      fs.rmSync(executionIndexFile(record.executionId), { force: true });
+    }
+  }
+}
+
+/**
+ * Writes a request record
+ * @public
+ */
+export function saveKestraRequest(record: KestraRequestRecord): void {
+  const file = path.join(getKestraResultsDir(), `${record.id}.json`);
+  const isNew = !fs.existsSync(file);
+  writeAtomic(file, JSON.stringify(record, null, 2));
+  if (record.executionId) writeAtomic(executionIndexFile(record.executionId), record.id);
+  if (isNew) prune(record.id);
+}
+
+/**
+ * Loads a request record
+ *
+ * @param id - Request identifier
+ * @returns The record, or undefined if there is none
+ * @public
+ */
+export function loadKestraRequest(id: string): KestraRequestRecord | undefined {
+  // Ids become file names, so only accept the format randomUUID produces
+  if (!REQUEST_ID.test(id)) return undefined;
+  const file = path.join(getKestraResultsDir(), `${id}.json`);
+  return fs.existsSync(file) ? JSON.parse(fs.readFileSync(file, "utf8")) : undefined;
+}
+
+/**
+ * Lists stored requests, newest first
+ *
+ * @param limit - Maximum number of requests
+ * @returns Request records
+ * @public
+ */
+export function listKestraRequests(limit = 50): KestraRequestRecord[] {
+  const dir = getKestraResultsDir();
+  if (!fs.existsSync(dir)) return [];
+  return fs.readdirSync(dir)
+    .filter((entry) => entry.endsWith(".json"))
+    .map((entry) => loadKestraRequest(entry.replace(/\.json$/, "")))
+    .filter((record): record is KestraRequestRecord => !!record)
+    .sort((a, b) => b.createdAt.localeCompare(a.createdAt))
+    .slice(0, limit);
+}
+
+/**
+ * Finds the newest request matching every given field
+ *
+ * Lookups by execution id go through the index; the others scan the
+ * stored requests.
+ *
+ * @param query - Execution id and/or repository URL
+ * @returns The request, or undefined if none matches
+ * @public
+ */
+export function findKestraRequest(query: { executionId?: string; repoUrl?: string }): KestraRequestRecord | undefined {
+  if (query.executionId) {
+    const id = findIndexedRequest(query.executionId);
+    const record = id ? loadKestraRequest(id) : undefined;
+    return record && (!query.repoUrl || sameRepository(record.repoUrl, query.repoUrl)) ? record : undefined;
+  }
+  return listKestraRequests(Infinity).find((record) => !query.repoUrl || sameRepository(record.repoUrl, query.repoUrl));
+}
+
+/**
+ * Records the request that started an execution
+ *
+ * A callback can beat the trigger response; its record is then taken over
+ * instead of creating a second one.
+ *
+ * @param fields - Request details, including the execution id
+ * @returns The stored record
+ * @public
+ */
+export function trackKestraExecution(
+  fields: Omit<KestraRequestRecord, "id" | "createdAt" | "callbacks"> & { executionId: string }
+): KestraRequestRecord {
+  const existing = findKestraRequest({ executionId: fields.executionId });
+  const record: KestraRequestRecord = existing
+    ? { ...existing, ...fields }
+    : { id: randomUUID(), ...fields, createdAt: new Date().toISOString(), callbacks: [] };
+  saveKestraRequest(record);
+  return record;
+}
+
+/**
+ * Stores results a flow reported
+ *
+ * A `repoUrl` in the results is kept on the request when it has none, so
+ * scheduled runs can be found by repository.
+ *
+ * @param callback - Execution id and results
+ * @param requestId - Request to store them under (default: the one that started the execution)
+ * @returns The updated record, or undefined if `requestId` is unknown
+ * @public
+ */
+export function recordKestraCallback(
+  callback: Omit<KestraCallback, "receivedAt">,
+  requestId?: string
+): KestraRequestRecord | undefined {
+  let record = requestId ? loadKestraRequest(requestId) : findKestraRequest({ executionId: callback.executionId });
+  if (requestId && !record) return undefined;
+  if (!record) {
+    record = { id: randomUUID(), action: "callback", executionId: callback.executionId, createdAt: new Date().toISOString(), callbacks: [] };
+  }
+  record.executionId = record.executionId || callback.executionId;
+  const reported = callback.results.repoUrl;
+  if (!record.repoUrl && typeof reported === "string" && reported) {
+    record.repoUrl = parseRepositoryUrl(reported)?.url ?? reported;
+  }
+  record.callbacks.push({ ...callback, receivedAt: new Date().toISOString() });
+  saveKestraRequest(record);
+  return record;
+}
+
+/**
+ * Formats a request and its callbacks for tool output
+ *
+ * @param record - Request record
+ * @returns Markdown section
+ * @public
+ */
+export function formatKestraResults(record: KestraRequestRecord): string {
+  let text = `## Kestra Results\n\nRequest \`${record.id}\` (${record.action}${record.repoUrl ? ` ${record.repoUrl}` : ""}, started ${record.createdAt})\n`;
+  if (record.executionId) {
+    text += `Execution: \`${record.executionId}\`${record.namespace ? ` (${record.namespace}.${record.flowId})` : ""}\n`;
+  }
+  if (record.callbacks.length === 0) {
+    return text + "\n⏳ The flow has not reported any results yet.\n";
+  }
+  for (const callback of record.callbacks) {
+    const source = [callback.taskId, callback.state].filter(Boolean).join(", ");
+    text += `\n### ${callback.receivedAt}${source ? ` (${source})` : ""}\n\n` +
+      `\`\`\`json\n${JSON.stringify(callback.results, null, 2)}\n\`\`\`\n`;
+  }
+  return text;
+}
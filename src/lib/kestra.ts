@@ -0,0 +1,421 @@
+/**
+ * Kestra API Client for AgentMesh
+ * Talks to a Kestra server: deploys and lists flows, triggers them, waits for
+ * executions and reads their logs and outputs
+ *
+ * The server URL comes from `kestra.url` in the config, then KESTRA_URL.
+ * Requests authenticate with the `kestra.auth` section of the config (basic
+ * or bearer). Credentials missing from the config are read from
+ * KESTRA_USERNAME / KESTRA_PASSWORD or KESTRA_TOKEN, which also enable auth
+ * on their own when the config has no `auth` section.
+ *
+ * @module kestra
+ */
+
+import { loadConfig, type KestraConfig } from "./config";
+
+const DEFAULT_KESTRA_URL = "http://localhost:8080";
+// Time allowed for a single API request
+const REQUEST_TIMEOUT = 15000;
+const DEFAULT_POLL_INTERVAL = 2000;
+
+/** Execution states Kestra never leaves */
+export const TERMINAL_STATES = ["SUCCESS", "WARNING", "FAILED", "KILLED", "CANCELLED", "SKIPPED"];
+
+/**
+ * Raised when the Kestra API answers with an error status
+ */
+export class KestraError extends Error {
+  constructor(readonly status: number, message: string) {
+    super(message);
+    this.name = "KestraError";
+  }
+}
+
+/**
+ * A flow execution
+ * @interface KestraExecution
+ */
+export interface KestraExecution {
+  id: string;
+  namespace: string;
+  flowId: string;
+  /** Current state, e.g. RUNNING or SUCCESS */
+  state: string;
+  startDate?: string;
+  endDate?: string;
+  /** Flow outputs (set once the execution finished) */
+  outputs: Record<string, unknown>;
+}
+
+/**
+ * One log line of an execution
+ * @interface KestraLog
+ */
+export interface KestraLog {
+  timestamp?: string;
+  level: string;
+  taskId?: string;
+  message: string;
+}
+
+/**
+ * Result of waiting for an execution
+ * @interface ExecutionResult
+ */
+export interface ExecutionResult {
+  execution: KestraExecution;
+  /** False when the wait timed out before a terminal state */
+  finished: boolean;
+  logs: KestraLog[];
+}
+
+/**
+ * Settings for `waitForExecution`
+ * @interface WaitOptions
+ */
+export interface WaitOptions {
+  /** Longest wait in ms */
+  timeout: number;
+  /** Delay between polls in ms (default: 2s) */
+  interval?: number;
+  /** Stops waiting early */
+  signal?: AbortSignal;
+}
+
+/**
+ * A flow stored in Kestra
+ * @interface KestraFlow
+ */
+export interface KestraFlow {
+  id: string;
+  namespace: string;
+  /** Bumped by Kestra on every update */
+  revision: number;
+  description?: string;
+  disabled: boolean;
+}
+
+/**
+ * Result of `deployFlow`
+ * @interface FlowDeployment
+ */
+export interface FlowDeployment {
+  flow: KestraFlow;
+  /** False when an existing flow was updated */
+  created: boolean;
+}
+
+/** How a Kestra server answered a ping */
+export type KestraStatus = "online" | "unauthorized" | "offline";
+
+interface RawExecution {
+  id: string;
+  namespace: string;
+  flowId: string;
+  state?: { current?: string; startDate?: string; endDate?: string };
+  outputs?: Record<string, unknown>;
+}
+
+function toExecution(raw: RawExecution): KestraExecution {
+  return {
+    id: raw.id,
+    namespace: raw.namespace,
+    flowId: raw.flowId,
+    state: raw.state?.current ?? "UNKNOWN",
+    startDate: raw.state?.startDate,
+    endDate: raw.state?.endDate,
+    outputs: raw.outputs ?? {},
+  };
+}
+
+interface RawFlow {
+  id: string;
+  namespace: string;
+  revision?: number;
+  description?: string;
+  disabled?: boolean;
+}
+
+function toFlow(raw: RawFlow): KestraFlow {
+  return {
+    id: raw.id,
+    namespace: raw.namespace,
+    revision: raw.revision ?? 1,
+    description: raw.description,
+    disabled: raw.disabled ?? false,
+  };
+}
+
+function flowPath(namespace: string, flowId: string, prefix = "/flows"): string {
+  return `${prefix}/${encodeURIComponent(namespace)}/${encodeURIComponent(flowId)}`;
+}
+
+function sleep(ms: number, signal?: AbortSignal): Promise<void> {
+  return new Promise((resolve) => {
+    const timer = setTimeout(done, ms);
+    function done() {
+      clearTimeout(timer);
+      signal?.removeEventListener("abort", done);
+      resolve();
+    }
+    signal?.addEventListener("abort", done);
+  });
+}
+
+/**
+ * Client for the Kestra REST API
+ * @public
+ */
+export class KestraClient {
+  readonly url: string;
+
+  constructor(private readonly config: KestraConfig = {}) {
+    this.url = (config.url || process.env.KESTRA_URL || DEFAULT_KESTRA_URL).replace(/\/$/, "");
+  }
+
+  /** Authorization header for the configured credentials */
+  private authorization(): string | undefined {
+    const auth = this.config.auth;
+    const username = auth?.username ?? process.env.KESTRA_USERNAME;
+    const password = auth?.password ?? process.env.KESTRA_PASSWORD;
+    const token = auth?.token ?? process.env.KESTRA_TOKEN;
+    const type = auth?.type ?? (token ? "bearer" : username ? "basic" : undefined);
+
+    if (type === "bearer") {
+      if (!token) throw new Error("Kestra bearer auth needs kestra.auth.token or KESTRA_TOKEN");
+      return `Bearer ${token}`;
+    }
+    if (type === "basic") {
+      if (!username || password === undefined) {
+        throw new Error("Kestra basic auth needs a username and password (kestra.auth or KESTRA_USERNAME / KESTRA_PASSWORD)");
+      }
+      return `Basic ${Buffer.from(`${username}:${password}`).toString("base64")}`;
+    }
+    return undefined;
+  }
+
+  /**
+   * URL of an API path, with the tenant when one is configured
+   *
+   * @param apiPath - Path below /api/v1, e.g. "/executions/abc"
+   */
+  apiUrl(apiPath: string): string {
+    const tenant = this.config.tenant ? `/${encodeURIComponent(this.config.tenant)}` : "";
+    return `${this.url}/api/v1${tenant}${apiPath}`;
+  }
+
+  /**
+   * Sends an API request
+   *
+   * @param method - HTTP method
+   * @param apiPath - Path below /api/v1
+   * @param body - JSON body, or a string sent as-is with `contentType`
+   * @param contentType - Content type of a string body
+   * @returns Parsed JSON response (undefined for empty responses)
+   * @throws {KestraError} If the server answers with an error status
+   */
+  async request<T>(method: string, apiPath: string, body?: unknown, contentType = "application/json"): Promise<T> {
+    const headers: Record<string, string> = { Accept: "application/json" };
+    const authorization = this.authorization();
+    if (authorization) headers.Authorization = authorization;
+    if (body !== undefined) headers["Content-Type"] = contentType;
+
+    const res = await fetch(this.apiUrl(apiPath), {
+      method,
+      headers,
+      body: body === undefined ? undefined : typeof body === "string" ? body : JSON.stringify(body),
+      signal: AbortSignal.timeout(REQUEST_TIMEOUT),
+    });
+    const text = await res.text();
+    if (!res.ok) {
+      let message = text || res.statusText;
+      try {
+        message = (JSON.parse(text) as { message?: string }).message || message;
+      } catch {
+        // Not JSON; keep the raw text
+      }
+      throw new KestraError(res.status, `Kestra ${method} ${apiPath} failed (${res.status}): ${message}`);
+    }
+    return (text ? JSON.parse(text) : undefined) as T;
+  }
+
+  /**
+   * Checks whether the server is reachable and accepts the credentials
+   *
+   * @returns "unauthorized" when the server answers 401
+   * @throws {Error} If the configured credentials are incomplete
+   */
+  async ping(): Promise<KestraStatus> {
+    try {
+      await this.request("GET", "/configs");
+      return "online";
+    } catch (error) {
+      if (error instanceof KestraError) return error.status === 401 ? "unauthorized" : "online";
+      // fetch fails with a TypeError when nothing answers, and a TimeoutError when it hangs
+      if (error instanceof TypeError || (error as Error).name === "TimeoutError") return "offline";
+      throw error;
+    }
+  }
+
+  /**
+   * Validates flow YAML without saving it
+   *
+   * @param source - Flow YAML
+   * @returns Constraint violations reported by Kestra (empty when valid)
+   */
+  async validateFlow(source: string): Promise<string[]> {
+    const results = await this.request<Array<{ constraints?: string }>>("POST", "/flows/validate", source, "application/x-yaml");
+    const violations: string[] = [];
+    for (const result of Array.isArray(results) ? results : []) {
+      // Kestra joins the violations of one flow with newlines
+      if (result.constraints) violations.push(...result.constraints.split("\n").filter((c) => c.trim()));
+    }
+    return violations;
+  }
+
+  /**
+   * Reads a flow
+   *
+   * @returns The flow, or undefined when it does not exist
+   */
+  async getFlow(namespace: string, flowId: string): Promise<KestraFlow | undefined> {
+    try {
+      return toFlow(await this.request<RawFlow>("GET", flowPath(namespace, flowId)));
+    } catch (error) {
+      if (error instanceof KestraError && error.status === 404) return undefined;
+      throw error;
+    }
+  }
+
+  /**
+   * Creates a flow, or updates it when one with the same id exists
+   *
+   * @param namespace - Namespace declared in the YAML
+   * @param flowId - Flow id declared in the YAML
+   * @param source - Flow YAML
+   * @returns The stored flow and whether it was new
+   */
+  async deployFlow(namespace: string, flowId: string, source: string): Promise<FlowDeployment> {
+    const existing = await this.getFlow(namespace, flowId);
+    const raw = existing
+      ? await this.request<RawFlow>("PUT", flowPath(namespace, flowId), source, "application/x-yaml")
+      : await this.request<RawFlow>("POST", "/flows", source, "application/x-yaml");
+    return { flow: toFlow(raw), created: !existing };
+  }
+
+  /**
+   * Lists the flows of a namespace
+   *
+   * @param namespace - Namespace (sub-namespaces are included)
+   * @param size - Most flows to return
+   */
+  async listFlows(namespace: string, size = 100): Promise<KestraFlow[]> {
+    const page = await this.request<{ results?: RawFlow[] }>(
+      "GET", `/flows/search?namespace=${encodeURIComponent(namespace)}&size=${size}&sort=id:asc`,
+    );
+    return (page?.results ?? []).map(toFlow);
+  }
+
+  /**
+   * Deletes a flow
+   *
+   * @throws {KestraError} With status 404 if the flow does not exist
+   */
+  async deleteFlow(namespace: string, flowId: string): Promise<void> {
+    await this.request("DELETE", flowPath(namespace, flowId));
+  }
+
+  /**
+   * Starts a flow through one of its webhook triggers
+   *
+   * @param namespace - Flow namespace
+   * @param flowId - Flow id
+   * @param key - Webhook key
+   * @param inputs - Payload passed to the flow
+   * @returns The new execution
+   */
+  async triggerWebhook(namespace: string, flowId: string, key: string, inputs: Record<string, unknown>): Promise<KestraExecution> {
+    const path = `${flowPath(namespace, flowId, "/executions/webhook")}/${encodeURIComponent(key)}`;
+    return toExecution(await this.request<RawExecution>("POST", path, inputs));
+  }
+
+  /**
+   * Reads an execution
+   */
+  async getExecution(id: string): Promise<KestraExecution> {
+    return toExecution(await this.request<RawExecution>("GET", `/executions/${encodeURIComponent(id)}`));
+  }
+
+  /**
+   * Reads the logs of an execution
+   */
+  async getLogs(id: string): Promise<KestraLog[]> {
+    const logs = await this.request<KestraLog[]>("GET", `/logs/${encodeURIComponent(id)}`);
+    return Array.isArray(logs)
+      ? logs.map((l) => ({ timestamp: l.timestamp, level: l.level ?? "INFO", taskId: l.taskId, message: l.message ?? "" }))
+      : [];
+  }
+
+  /**
+   * Polls an execution until it reaches a terminal state or the timeout runs out
+   *
+   * @param id - Execution id
+   * @param options - Timeout, poll interval and abort signal
+   * @returns The last state seen, with the logs so far
+   */
+  async waitForExecution(id: string, options: WaitOptions): Promise<ExecutionResult> {
+    const deadline = Date.now() + options.timeout;
+    const interval = options.interval ?? DEFAULT_POLL_INTERVAL;
+    let execution = await this.getExecution(id);
+    while (!TERMINAL_STATES.includes(execution.state) && Date.now() < deadline && !options.signal?.aborted) {
+      await sleep(Math.min(interval, Math.max(0, deadline - Date.now())), options.signal);
+      execution = await this.getExecution(id);
+    }
+    return { execution, finished: TERMINAL_STATES.includes(execution.state), logs: await this.getLogs(id) };
+  }
+}
+
+/**
+ * Client for the configured Kestra server
+ *
+ * @param url - Server URL overriding the config
+ * @returns Kestra client
+ * @public
+ */
+export function getKestraClient(url?: string): KestraClient {
+  const config = loadConfig().kestra;
+  return new KestraClient(url ? { ...config, url } : config);
+}
+
+/**
+ * Formats an execution result for tool output
+ *
+ * @param result - Result of `waitForExecution`
+ * @param maxLogs - Log lines to include (the last ones)
+ * @returns Markdown section
+ * @public
+ */
+export function formatExecution(result: ExecutionResult, maxLogs = 20): string {
+  const { execution, logs } = result;
+  const emoji = execution.state === "SUCCESS" ? "✅" : result.finished ? "❌" : "⏳";
+  let text = `## Kestra Execution\n\n${emoji} \`${execution.id}\` (${execution.namespace}.${execution.flowId}): **${execution.state}**\n`;
+  if (!result.finished) {
+    text += "Still running when the wait timed out; check again later.\n";
+  }
+  if (execution.startDate && execution.endDate) {
+    const seconds = (new Date(execution.endDate).getTime() - new Date(execution.startDate).getTime()) / 1000;
+    text += `Duration: ${seconds.toFixed(1)}s\n`;
+  }
+  if (Object.keys(execution.outputs).length) {
+    text += `\n### Outputs\n\n\`\`\`json\n${JSON.stringify(execution.outputs, null, 2)}\n\`\`\`\n`;
+  }
+  if (logs.length) {
+    const shown = logs.slice(-maxLogs);
+    const skipped = logs.length - shown.length;
+    text += `\n### Logs${skipped ? ` (last ${shown.length} of ${logs.length})` : ""}\n\n\`\`\`\n` +
+      shown.map((l) => `${l.level.padEnd(5)} ${l.taskId ? `[${l.taskId}] ` : ""}${l.message.trim()}`).join("\n") +
+      "\n```\n";
+  }
+  return text;
+}
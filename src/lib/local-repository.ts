@@ -0,0 +1,226 @@
+/**
+ * Local Repository Analysis for AgentMesh
+ * Reads the health signals of a local git checkout without any forge API:
+ * commit activity, contributors, branches, tags, TODO/FIXME markers, the
+ * ratio of test files to source files and the age of dependency lockfiles
+ *
+ * Everything comes from git and the working tree, so the analysis works on
+ * air-gapped machines and for private repositories without a forge token.
+ * Only tracked files are considered; ages are taken from the last commit
+ * that touched a file.
+ *
+ * @module local-repository
+ */
+
+import * as path from "path";
+import { findRepoRoot, git } from "./git";
+import type { ForgeContributor } from "./forge-api";
+
+const DAY = 24 * 60 * 60 * 1000;
+// Window for "recent" commits
+const RECENT_DAYS = 30;
+// Contributors and branches without commits for this long count as inactive
+const ACTIVE_DAYS = 90;
+
+// Files holding resolved dependency versions
+const LOCKFILES = new Set([
+  "package-lock.json", "npm-shrinkwrap.json", "yarn.lock", "pnpm-lock.yaml", "bun.lockb", "bun.lock",
+  "Cargo.lock", "go.sum", "poetry.lock", "Pipfile.lock", "uv.lock", "Gemfile.lock", "composer.lock",
+  "mix.lock", "pubspec.lock", "Package.resolved", "flake.lock",
+]);
+
+// Source extensions and the language they count towards
+const LANGUAGES: Record<string, string> = {
+  ".ts": "TypeScript", ".tsx": "TypeScript", ".mts": "TypeScript", ".cts": "TypeScript",
+  ".js": "JavaScript", ".jsx": "JavaScript", ".mjs": "JavaScript", ".cjs": "JavaScript",
+  ".py": "Python", ".go": "Go", ".rs": "Rust", ".java": "Java", ".kt": "Kotlin", ".scala": "Scala",
+  ".rb": "Ruby", ".php": "PHP", ".cs": "C#", ".c": "C", ".h": "C", ".cc": "C++", ".cpp": "C++",
+  ".hpp": "C++", ".swift": "Swift", ".ex": "Elixir", ".exs": "Elixir", ".dart": "Dart",
+};
+
+// Test directories and the usual test file names across languages
+const TEST_FILE = /(^|\/)(tests?|__tests__|spec)\/|[._-](test|spec)\.[^/]+$|_test\.go$|(^|\/)test_[^/]+\.py$|Tests?\.(java|kt|cs|swift)$/;
+// Directories holding third-party or generated code
+const VENDORED_DIRS = ["node_modules", "vendor", "third_party", "dist", "build"];
+const VENDORED = new RegExp(`(^|/)(${VENDORED_DIRS.join("|")})/`);
+
+/**
+ * A lockfile and how long ago it last changed
+ * @interface LockfileAge
+ */
+export interface LockfileAge {
+  /** Path relative to the repository root */
+  file: string;
+  updatedAt: string;
+  ageDays: number;
+}
+
+/**
+ * Health signals of a local checkout
+ * @interface LocalRepositoryData
+ */
+export interface LocalRepositoryData {
+  root: string;
+  name: string;
+  /** Checked-out branch ("HEAD" when detached) */
+  branch: string;
+  /** Most common source language by file count */
+  language?: string;
+  commits: {
+    total: number;
+    /** Commits in the last 30 days */
+    recent: number;
+    lastCommitAt: string;
+  };
+  /** Commit authors, most commits first */
+  contributors: ForgeContributor[];
+  /** Authors with a commit in the last 90 days */
+  activeContributors: number;
+  branches: {
+    total: number;
+    /** Local branches without commits in the last 90 days */
+    stale: string[];
+  };
+  tags: {
+    total: number;
+    latest?: { name: string; date: string };
+  };
+  markers: {
+    todo: number;
+    fixme: number;
+  };
+  tests: {
+    sourceFiles: number;
+    testFiles: number;
+    /** Test files per source file, 0 without sources */
+    ratio: number;
+  };
+  lockfiles: LockfileAge[];
+}
+
+// Splits git output into lines, dropping empty ones
+function lines(output: string): string[] {
+  return output.split("\n").filter(Boolean);
+}
+
+// Runs a git command that exits with 1 when it finds nothing
+async function gitSearch(args: string[], cwd: string): Promise<string> {
+  try {
+    return await git(args, cwd);
+  } catch (error) {
+    if ((error as { exitCode?: number }).exitCode === 1) return "";
+    throw error;
+  }
+}
+
+// Commit counts per author, most commits first
+async function shortlog(root: string, since?: string): Promise<ForgeContributor[]> {
+  const output = await git(["shortlog", "-sn", ...(since ? [`--since=${since}`] : []), "HEAD"], root);
+  return lines(output).map((line) => {
+    const [, count, name] = line.match(/^\s*(\d+)\s+(.*)$/) || [];
+    return { name: name ?? line.trim(), contributions: Number(count) || 0 };
+  });
+}
+
+async function countMarkers(root: string): Promise<{ todo: number; fixme: number }> {
+  const excluded = VENDORED_DIRS.map((dir) => `:(exclude,glob)**/${dir}/**`);
+  const output = await gitSearch(["grep", "-I", "-o", "-w", "-h", "-E", "TODO|FIXME", "--", ".", ...excluded], root);
+  const found = lines(output);
+  return {
+    todo: found.filter((m) => m === "TODO").length,
+    fixme: found.filter((m) => m === "FIXME").length,
+  };
+}
+
+function classifyFiles(files: string[]): { language?: string; sourceFiles: number; testFiles: number } {
+  const byLanguage = new Map<string, number>();
+  let sourceFiles = 0;
+  let testFiles = 0;
+  for (const file of files) {
+    const language = LANGUAGES[path.extname(file).toLowerCase()];
+    if (!language || VENDORED.test(file)) continue;
+    if (TEST_FILE.test(file)) {
+      testFiles++;
+    } else {
+      sourceFiles++;
+    }
+    byLanguage.set(language, (byLanguage.get(language) ?? 0) + 1);
+  }
+
+  let language: string | undefined;
+  for (const [name, count] of byLanguage) {
+    if (language === undefined || count > (byLanguage.get(language) as number)) language = name;
+  }
+  return { language, sourceFiles, testFiles };
+}
+
+async function lockfileAges(root: string, files: string[], now: number): Promise<LockfileAge[]> {
+  const lockfiles = files.filter((file) => LOCKFILES.has(path.basename(file)) && !VENDORED.test(file));
+  const ages = await Promise.all(lockfiles.map(async (file) => {
+    const updatedAt = await git(["log", "-1", "--format=%cI", "--", file], root);
+    // Lockfiles staged but not committed yet have no age
+    if (!updatedAt) return undefined;
+    return { file, updatedAt, ageDays: Math.floor((now - Date.parse(updatedAt)) / DAY) };
+  }));
+  return ages.filter((age): age is LockfileAge => age !== undefined);
+}
+
+/**
+ * Reads the health signals of a local git checkout
+ *
+ * @param dir - Any directory inside the checkout
+ * @param now - Reference time for ages (default: now)
+ * @returns Repository signals
+ * @throws {Error} If the directory is not inside a git repository or it has no commits
+ * @public
+ */
+export async function analyzeLocalRepository(dir: string, now: number = Date.now()): Promise<LocalRepositoryData> {
+  const root = await findRepoRoot(dir);
+  if (!root) {
+    throw new Error(`${dir} is not inside a git repository`);
+  }
+  const lastCommitAt = await git(["log", "-1", "--format=%cI"], root).catch(() => "");
+  if (!lastCommitAt) {
+    throw new Error(`${root} has no commits yet`);
+  }
+
+  const since = (days: number) => new Date(now - days * DAY).toISOString();
+  const [branch, total, recent, contributors, active, branchRefs, tagRefs, markers, tracked] = await Promise.all([
+    git(["rev-parse", "--abbrev-ref", "HEAD"], root),
+    git(["rev-list", "--count", "HEAD"], root),
+    git(["rev-list", "--count", `--since=${since(RECENT_DAYS)}`, "HEAD"], root),
+    shortlog(root),
+    shortlog(root, since(ACTIVE_DAYS)),
+    git(["for-each-ref", "--format=%(refname:short)%09%(committerdate:iso-strict)", "refs/heads"], root),
+    git(["for-each-ref", "--sort=-creatordate", "--format=%(refname:short)%09%(creatordate:iso-strict)", "refs/tags"], root),
+    countMarkers(root),
+    git(["ls-files"], root),
+  ]);
+
+  const files = lines(tracked);
+  const { language, sourceFiles, testFiles } = classifyFiles(files);
+  const staleBefore = now - ACTIVE_DAYS * DAY;
+  const branches = lines(branchRefs).map((line) => line.split("\t"));
+  const tags = lines(tagRefs).map((line) => line.split("\t"));
+
+  return {
+    root,
+    name: path.basename(root),
+    branch,
+    language,
+    commits: { total: Number(total), recent: Number(recent), lastCommitAt },
+    contributors,
+    activeContributors: active.length,
+    branches: {
+      total: branches.length,
+      stale: branches.filter(([, date]) => Date.parse(date) < staleBefore).map(([name]) => name),
+    },
+    tags: {
+      total: tags.length,
+      latest: tags.length ? { name: tags[0][0], date: tags[0][1] } : undefined,
+    },
+    markers,
+    tests: { sourceFiles, testFiles, ratio: sourceFiles ? testFiles / sourceFiles : 0 },
+    lockfiles: await lockfileAges(root, files, now),
+  };
+}
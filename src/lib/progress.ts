@@ -0,0 +1,139 @@
+/**
+ * MCP Progress Notifications for AgentMesh
+ * Turns agent events and workflow steps into `notifications/progress` messages
+ *
+ * Notifications are only sent when the client asked for them by passing a
+ * `progressToken` in the request `_meta`. They are sent through the request's
+ * own `sendNotification`, so over the HTTP transport they travel on the
+ * response stream of the tool call that produced them.
+ *
+ * @module progress
+ */
+
+import type { z } from "zod";
+import type { AgentEvent } from "./agent";
+import { withRunContext, type StepInfo } from "./run-context";
+
+// Minimum delay between two agent-text notifications
+const TEXT_THROTTLE_MS = 500;
+// Longest message forwarded to the client
+const MAX_MESSAGE_LENGTH = 300;
+
+/**
+ * The parts of the xmcp / MCP SDK request extra that AgentMesh uses
+ * @interface ToolExtra
+ */
+export interface ToolExtra {
+  /** Aborted when the client cancels the request */
+  signal?: AbortSignal;
+  /** Request metadata */
+  _meta?: { progressToken?: string | number };
+  /** Sends a notification related to the current request */
+  sendNotification?: (notification: {
+    method: "notifications/progress";
+    params: { progressToken: string | number; progress: number; total?: number; message?: string };
+  }) => Promise<void>;
+  /** Sends a request to the client (used for elicitation) */
+  sendRequest?: <T extends z.ZodTypeAny>(
+    request: { method: "elicitation/create"; params: Record<string, unknown> },
+    resultSchema: T,
+    options?: { signal?: AbortSignal; timeout?: number }
+  ) => Promise<z.infer<T>>;
+}
+
+const REPORTED_KINDS = new Set<AgentEvent["kind"]>(["text", "tool_use", "command", "plan", "completion"]);
+
+/**
+ * Sends monotonic progress notifications for one tool call
+ *
+ * Progress advances through the furthest step started so far; messages are
+ * labelled with the step that produced them, so parallel steps stay apart.
+ *
+ * @public
+ */
+export class ProgressReporter {
+  private progress = 0;
+  // Furthest step started, which positions progress
+  private step?: StepInfo;
+  private stepUpdates = 0;
+  private lastTextAt = 0;
+
+  constructor(private readonly extra: ToolExtra) {}
+
+  /** Whether the client asked for progress */
+  get enabled(): boolean {
+    return this.extra._meta?.progressToken !== undefined && !!this.extra.sendNotification;
+  }
+
+  /**
+   * Reports the start of a workflow step
+   */
+  onStep(step: StepInfo): void {
+    if (!this.step || step.index > this.step.index) {
+      this.step = step;
+      this.stepUpdates = 0;
+    }
+    this.send(`Step ${step.index}/${step.total}: ${step.name}`);
+  }
+
+  /**
+   * Reports an agent event; text updates are throttled
+   *
+   * @param event - Agent event
+   * @param step - Step whose agent produced the event
+   */
+  onEvent(event: AgentEvent, step?: StepInfo): void {
+    if (!REPORTED_KINDS.has(event.kind) || !event.text.trim()) return;
+
+    const now = Date.now();
+    if (event.kind === "text" && now - this.lastTextAt < TEXT_THROTTLE_MS) return;
+    this.lastTextAt = now;
+
+    const detail = event.kind === "tool_use"
+      ? `${event.tool ?? "tool"}${event.path ? ` ${event.path}` : ""}`
+      : event.text.replace(/\s+/g, " ").trim();
+    const prefix = step ? `Step ${step.index}/${step.total} (${step.name}): ` : "";
+    this.send(prefix + detail);
+  }
+
+  private send(message: string): void {
+    if (!this.enabled) return;
+
+    // Progress must strictly increase. Within step i it moves from i-1 towards
+    // i without reaching it; without steps it simply counts updates.
+    if (this.step) {
+      this.progress = this.step.index - 1 + this.stepUpdates / (this.stepUpdates + 1);
+      this.stepUpdates++;
+    } else {
+      this.progress++;
+    }
+    const params = {
+      progressToken: this.extra._meta!.progressToken!,
+      progress: this.progress,
+      total: this.step?.total,
+      message: message.length > MAX_MESSAGE_LENGTH ? message.substring(0, MAX_MESSAGE_LENGTH) + "..." : message,
+    };
+
+    this.extra.sendNotification!({ method: "notifications/progress", params }).catch((error) => {
+      console.warn("[AgentMesh] Failed to send progress notification:", error);
+    });
+  }
+}
+
+/**
+ * Runs a tool body with progress reporting and client cancellation wired in
+ *
+ * @param extra - Request extra passed by xmcp as the handler's second argument
+ * @param fn - Tool body
+ * @returns Result of `fn`
+ * @public
+ */
+export function withProgress<T>(extra: ToolExtra | undefined, fn: () => Promise<T>): Promise<T> {
+  if (!extra) return fn();
+  const reporter = new ProgressReporter(extra);
+  return withRunContext({
+    signal: extra.signal,
+    onEvent: (event, step) => reporter.onEvent(event, step),
+    onStep: (step) => reporter.onStep(step),
+  }, fn);
+}
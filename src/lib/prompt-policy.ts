@@ -0,0 +1,99 @@
+/**
+ * Prompt Policy for AgentMesh
+ * Decides what a prompt may contain before it is handed to an agent CLI
+ *
+ * Prompts never pass through a shell: backends deliver them on stdin or in a
+ * private temp file, so quotes, backticks, braces, globs and the like are
+ * preserved exactly. The policy only removes what no legitimate prompt needs:
+ *
+ * - C0/C1 control characters other than tab and newline (terminal escapes, NUL)
+ * - Unicode bidirectional overrides and isolates (hidden-text attacks)
+ *
+ * and rejects prompts that are empty or longer than the configured limit.
+ *
+ * @module prompt-policy
+ */
+
+import * as fs from "fs";
+import * as os from "os";
+import * as path from "path";
+import { loadConfig } from "./config";
+
+// C0 controls except \t and \n, DEL, C1 controls
+const CONTROL_CHARS = /[\u0000-\u0008\u000B-\u001F\u007F-\u009F]/g;
+// LRE, RLE, PDF, LRO, RLO, LRI, RLI, FSI, PDI
+const BIDI_CONTROLS = /[\u202A-\u202E\u2066-\u2069]/g;
+
+/**
+ * Raised when a prompt violates the policy
+ * @public
+ */
+export class PromptPolicyError extends Error {
+  constructor(message: string) {
+    super(`[AgentMesh] ${message}`);
+    this.name = "PromptPolicyError";
+  }
+}
+
+/**
+ * Policy settings
+ * @interface PromptPolicy
+ */
+export interface PromptPolicy {
+  /** Maximum length in characters after normalisation */
+  maxLength: number;
+}
+
+/**
+ * Applies the prompt policy
+ *
+ * @param prompt - Prompt supplied by the caller
+ * @param policy - Policy settings (default: from the AgentMesh config)
+ * @returns The prompt with CRLF normalised and control characters removed
+ * @throws {PromptPolicyError} If the prompt is not a string, is empty, or is too long
+ * @public
+ */
+export function applyPromptPolicy(
+  prompt: string,
+  policy: PromptPolicy = { maxLength: loadConfig().maxPromptLength }
+): string {
+  if (typeof prompt !== "string") {
+    throw new PromptPolicyError("Invalid prompt type");
+  }
+
+  const cleaned = prompt
+    .replace(/\r\n?/g, "\n")
+    .replace(CONTROL_CHARS, "")
+    .replace(BIDI_CONTROLS, "");
+
+  if (!cleaned.trim()) {
+    throw new PromptPolicyError("Prompt is empty");
+  }
+  if (cleaned.length > policy.maxLength) {
+    throw new PromptPolicyError(`Prompt exceeds maximum length (${cleaned.length} > ${policy.maxLength} characters)`);
+  }
+
+  return cleaned;
+}
+
+/**
+ * Writes the prompt to a private temp file for the duration of `fn`
+ *
+ * The file is created with mode 0600 in a fresh directory and removed
+ * afterwards, even if `fn` throws.
+ *
+ * @param prompt - Prompt text
+ * @param fn - Receives the file path
+ * @returns Result of `fn`
+ * @public
+ */
+export async function withPromptFile<T>(prompt: string, fn: (file: string) => Promise<T>): Promise<T> {
+  const dir = fs.mkdtempSync(path.join(os.tmpdir(), "agentmesh-prompt-"));
+  const file = path.join(dir, "prompt.md");
+  try {
+    fs.writeFileSync(file, prompt, { mode: 0o600 });
+    return await fn(file);
+  } finally {
+    fs.rmSync(dir, { recursive: true, force: true });
+  }
+}
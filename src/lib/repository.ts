@@ -0,0 +1,237 @@
+/**
+ * Repository Sources for AgentMesh
+ * Recognises repository URLs on GitHub, GitLab and Gitea / Forgejo and reads
+ * the same repository data from whichever forge hosts them
+ *
+ * github.com, gitlab.com and codeberg.org are known. Self-hosted instances
+ * are recognised from `gitlab.hosts` / `gitea.hosts` in the config, or from
+ * a host name containing "gitlab", "gitea" or "forgejo".
+ *
+ * @module repository
+ */
+
+import { loadConfig } from "./config";
+import {
+  ForgeApiError,
+  RateLimitError,
+  type ForgeContributor,
+  type ForgeIssue,
+  type ForgePipeline,
+  type ForgePullRequest,
+  type ForgeRepo,
+} from "./forge-api";
+import { getGiteaClient } from "./gitea";
+import { getGitHubClient } from "./github";
+import { getGitLabClient } from "./gitlab";
+
+// Issues and pull requests read per repository
+const MAX_ITEMS = 500;
+// CI runs read per repository
+const RECENT_PIPELINES = 20;
+const MAX_CONTRIBUTORS = 100;
+
+/** Forges repositories can be read from */
+export type ForgeKind = "github" | "gitlab" | "gitea";
+
+/** Display names of the forges */
+export const FORGE_NAMES: Record<ForgeKind, string> = {
+  github: "GitHub",
+  gitlab: "GitLab",
+  gitea: "Gitea",
+};
+
+/**
+ * A repository on a forge
+ * @interface RepositoryRef
+ */
+export interface RepositoryRef {
+  forge: ForgeKind;
+  /** Instance URL, e.g. https://gitlab.example.com */
+  origin: string;
+  /** Owner, or the full group path on GitLab */
+  owner: string;
+  name: string;
+  /** "owner/name", with every subgroup on GitLab */
+  path: string;
+  /** Web URL of the repository */
+  url: string;
+}
+
+/**
+ * What repository analysis reads from a forge
+ * @interface RepositoryData
+ */
+export interface RepositoryData {
+  forge: ForgeKind;
+  repo: ForgeRepo;
+  issues: ForgeIssue[];
+  pullRequests: ForgePullRequest[];
+  /** Latest CI runs, newest first; undefined if CI is off or hidden */
+  pipelines?: ForgePipeline[];
+  /** Undefined if the forge would not list them */
+  contributors?: ForgeContributor[];
+}
+
+// Rewrites scp-like and ssh clone URLs (git@host:owner/repo.git) as https
+function toUrl(url: string): URL | undefined {
+  const ssh = url.trim().match(/^(?:ssh:\/\/)?[\w.-]+@([^:/]+)(?::\d+)?[:/](.+)$/);
+  try {
+    return new URL(ssh ? `https://${ssh[1]}/${ssh[2]}` : url.trim());
+  } catch {
+    return undefined;
+  }
+}
+
+// Forge serving a host
+function detectForge(url: URL): ForgeKind | undefined {
+  const config = loadConfig();
+  const host = url.hostname.toLowerCase();
+  const listed = (hosts: string[] | undefined) =>
+    (hosts ?? []).some((h) => [host, url.host.toLowerCase()].includes(h.toLowerCase()));
+
+  if (host === "github.com" || host === "www.github.com") return "github";
+  if (host === "gitlab.com" || listed(config.gitlab.hosts)) return "gitlab";
+  if (host === "codeberg.org" || listed(config.gitea.hosts)) return "gitea";
+  if (host.includes("gitlab")) return "gitlab";
+  if (host.includes("gitea") || host.includes("forgejo")) return "gitea";
+  return undefined;
+}
+
+/**
+ * Recognises a repository URL
+ *
+ * Accepts web and clone URLs, including links to pages inside the
+ * repository (issues, merge requests, files).
+ *
+ * @param url - Repository URL, e.g. https://gitlab.com/group/subgroup/project
+ * @returns The repository, or undefined if no known forge hosts it
+ * @public
+ */
+export function parseRepositoryUrl(url: string): RepositoryRef | undefined {
+  const parsed = toUrl(url);
+  if (!parsed || !/^https?:$/.test(parsed.protocol)) return undefined;
+  const forge = detectForge(parsed);
+  if (!forge) return undefined;
+
+  let segments = parsed.pathname.split("/").filter(Boolean);
+  if (forge === "gitlab") {
+    // GitLab pages inside a project sit below "/-/"
+    const dash = segments.indexOf("-");
+    if (dash >= 0) segments = segments.slice(0, dash);
+  } else {
+    segments = segments.slice(0, 2);
+  }
+  if (segments.length < 2) return undefined;
+
+  const name = segments[segments.length - 1].replace(/\.git$/, "");
+  const owner = segments.slice(0, -1).join("/");
+  const origin = `${parsed.protocol}//${parsed.host}`;
+  return { forge, origin, owner, name, path: `${owner}/${name}`, url: `${origin}/${owner}/${name}` };
+}
+
+/**
+ * API endpoints a Kestra flow reads open issues and pull requests from
+ *
+ * @param ref - Repository
+ * @returns URLs and the Accept header they expect
+ * @public
+ */
+export function repositoryApiUrls(ref: RepositoryRef): { issues: string; pullRequests: string; accept: string } {
+  switch (ref.forge) {
+    case "github":
+      return {
+        issues: `https://api.github.com/repos/${ref.path}/issues?state=open`,
+        pullRequests: `https://api.github.com/repos/${ref.path}/pulls?state=open`,
+        accept: "application/vnd.github.v3+json",
+      };
+    case "gitlab": {
+      const project = `${ref.origin}/api/v4/projects/${encodeURIComponent(ref.path)}`;
+      return {
+        issues: `${project}/issues?state=opened`,
+        pullRequests: `${project}/merge_requests?state=opened`,
+        accept: "application/json",
+      };
+    }
+    case "gitea":
+      return {
+        issues: `${ref.origin}/api/v1/repos/${ref.path}/issues?state=open&type=issues`,
+        pullRequests: `${ref.origin}/api/v1/repos/${ref.path}/pulls?state=open`,
+        accept: "application/json",
+      };
+  }
+}
+
+// Reads data some repositories hide (disabled CI, private statistics);
+// rate limits and other failures still fail the analysis
+async function optional<T>(read: Promise<T>): Promise<T | undefined> {
+  try {
+    return await read;
+  } catch (error) {
+    if (error instanceof ForgeApiError && !(error instanceof RateLimitError) && [401, 403, 404].includes(error.status)) {
+      return undefined;
+    }
+    throw error;
+  }
+}
+
+/**
+ * Reads a repository with its open issues, open pull requests, latest CI
+ * runs and contributors
+ *
+ * @param ref - Repository
+ * @param options - maxItems caps issues and pull requests (default: 500)
+ * @returns Repository data
+ * @throws {ForgeApiError} If the forge refuses the repository, issues or pull requests
+ * @public
+ */
+export async function fetchRepository(ref: RepositoryRef, options: { maxItems?: number } = {}): Promise<RepositoryData> {
+  const open = { state: "open" as const, maxItems: options.maxItems ?? MAX_ITEMS };
+  const runs = { maxItems: RECENT_PIPELINES };
+  const people = { maxItems: MAX_CONTRIBUTORS };
+
+  let reads: [Promise<ForgeRepo>, Promise<ForgeIssue[]>, Promise<ForgePullRequest[]>, Promise<ForgePipeline[]>, Promise<ForgeContributor[]>];
+  switch (ref.forge) {
+    case "github": {
+      const github = getGitHubClient();
+      reads = [
+        github.getRepo(ref.owner, ref.name),
+        github.listIssues(ref.owner, ref.name, open),
+        github.listPullRequests(ref.owner, ref.name, open),
+        github.listPipelines(ref.owner, ref.name, runs),
+        github.listContributors(ref.owner, ref.name, people),
+      ];
+      break;
+    }
+    case "gitlab": {
+      const gitlab = getGitLabClient(ref.origin);
+      reads = [
+        gitlab.getProject(ref.path),
+        gitlab.listIssues(ref.path, open),
+        gitlab.listMergeRequests(ref.path, open),
+        gitlab.listPipelines(ref.path, runs),
+        gitlab.listContributors(ref.path, people),
+      ];
+      break;
+    }
+    case "gitea": {
+      const gitea = getGiteaClient(ref.origin);
+      reads = [
+        gitea.getRepo(ref.owner, ref.name),
+        gitea.listIssues(ref.owner, ref.name, open),
+        gitea.listPullRequests(ref.owner, ref.name, open),
+        gitea.listPipelines(ref.owner, ref.name, runs),
+        gitea.listContributors(ref.owner, ref.name, people),
+      ];
+      break;
+    }
+  }
+
+  const [repo, issues, pullRequests, pipelines, contributors] = await Promise.all([
+    reads[0],
+    reads[1],
+    reads[2],
+    optional(reads[3]),
+    optional(reads[4]),
+  ]);
+  return { forge: ref.forge, repo, issues, pullRequests, pipelines, contributors };
+}
@@ -0,0 +1,118 @@
+/**
+ * Ambient context for agent runs
+ * Carries cancellation and event listeners from the caller (a background job,
+ * an MCP request) down to the agent process without threading them through
+ * every tool's arguments.
+ *
+ * @module run-context
+ */
+
+import { AsyncLocalStorage } from "async_hooks";
+import type { AgentEvent } from "./agent";
+
+/**
+ * Position within a multi-step run (e.g. a workflow)
+ * @interface StepInfo
+ */
+export interface StepInfo {
+  /** 1-based step index */
+  index: number;
+  /** Total number of steps */
+  total: number;
+  /** Step name */
+  name: string;
+}
+
+/**
+ * A workflow step waiting for a human decision
+ * @interface ApprovalRequest
+ */
+export interface ApprovalRequest {
+  workflow: string;
+  /** Step name */
+  step: string;
+  /** What the reviewer should look at (the step's output, e.g. a plan) */
+  summary: string;
+}
+
+/**
+ * A reviewer's answer to an approval request
+ * @interface ApprovalDecision
+ */
+export interface ApprovalDecision {
+  approved: boolean;
+  /** Comments; a rejection with feedback re-runs the step with them */
+  feedback?: string;
+  /** Why the step was not approved when no reviewer decided */
+  reason?: string;
+}
+
+/**
+ * Context visible to every agent run started inside `withRunContext()`
+ * @interface RunContext
+ */
+export interface RunContext {
+  /** Aborting this signal kills the running agent process */
+  signal?: AbortSignal;
+  /** Receives each agent event as it is parsed, with the step that produced it */
+  onEvent?: (event: AgentEvent, step?: StepInfo) => void;
+  /** Receives step transitions of multi-step runs */
+  onStep?: (step: StepInfo) => void;
+  /** Asks a human to approve a workflow step; an inner handler replaces the outer one */
+  onApproval?: (request: ApprovalRequest) => Promise<ApprovalDecision>;
+  /** Workflow step the agent runs in this context belong to; parallel steps each have their own */
+  step?: StepInfo;
+}
+
+const storage = new AsyncLocalStorage<RunContext>();
+
+/**
+ * Returns the context of the current async call chain
+ * @public
+ */
+export function getRunContext(): RunContext {
+  return storage.getStore() ?? {};
+}
+
+/**
+ * Runs `fn` with additional context. Event listeners are chained with the
+ * enclosing context; an inner signal, approval handler or step replaces the outer one.
+ *
+ * @param context - Context to add
+ * @param fn - Work to run inside the context
+ * @returns Result of `fn`
+ * @public
+ */
+export function withRunContext<T>(context: RunContext, fn: () => Promise<T>): Promise<T> {
+  const parent = getRunContext();
+
+  return storage.run({
+    signal: context.signal ?? parent.signal,
+    onEvent: chain(parent.onEvent, context.onEvent),
+    onStep: chain(parent.onStep, context.onStep),
+    onApproval: context.onApproval ?? parent.onApproval,
+    step: context.step ?? parent.step,
+  }, fn);
+}
+
+function chain<A extends unknown[]>(...listeners: (((...args: A) => void) | undefined)[]): ((...args: A) => void) | undefined {
+  const active = listeners.filter((l): l is (...args: A) => void => !!l);
+  return active.length > 0 ? (...args) => active.forEach((l) => l(...args)) : undefined;
+}
+
+/**
+ * Publishes an agent event to the current context
+ * @public
+ */
+export function emitAgentEvent(event: AgentEvent): void {
+  const context = getRunContext();
+  context.onEvent?.(event, context.step);
+}
+
+/**
+ * Publishes a step transition to the current context
+ * @public
+ */
+export function reportStep(step: StepInfo): void {
+  getRunContext().onStep?.(step);
+}
@@ -0,0 +1,252 @@
+/**
+ * Workflow Run History for AgentMesh
+ * Stores every `agent_workflow` run as a JSON file under `.agentmesh/runs`
+ * (next to the config file) so failed runs can be resumed and past runs
+ * browsed through the `runs://` resources
+ *
+ * A record is rewritten after each step, so a crash loses at most the step
+ * that was running.
+ *
+ * @module runs
+ */
+
+import * as fs from "fs";
+import * as path from "path";
+import { randomUUID } from "crypto";
+import { getConfigPath } from "./config";
+import { truncateDiff } from "./changes";
+import type { StepStatus } from "./template";
+import type { StepReport } from "./workflow-runner";
+import type { Worktree } from "./worktree";
+import { stepId } from "./workflows";
+
+const RUN_ID = /^[0-9a-f]{8}-[0-9a-f]{4}-[0-9a-f]{4}-[0-9a-f]{4}-[0-9a-f]{12}$/;
+
+export type RunStatus = "running" | "completed" | "failed";
+
+/**
+ * One step of a stored run
+ * @interface StepRecord
+ */
+export interface StepRecord {
+  id: string;
+  name: string;
+  /** 1-based position in the workflow file */
+  index: number;
+  status: StepStatus;
+  /** Taken over from the run this one resumed */
+  reused?: boolean;
+  /** Rendered prompt, or the command of a `run` step */
+  input?: string;
+  output: string;
+  error?: string;
+  /** Why the step was skipped */
+  reason?: string;
+  changedFiles: string[];
+  /** Unified diff of the step's changes (truncated) */
+  diff?: string;
+  /** Commit holding the step's changes (with autoCommit) */
+  commit?: string;
+  attempts: number;
+  startedAt?: string;
+  finishedAt: string;
+  durationMs?: number;
+}
+
+/**
+ * A stored workflow run
+ * @interface WorkflowRunRecord
+ */
+export interface WorkflowRunRecord {
+  id: string;
+  workflow: string;
+  target: string;
+  inputs: Record<string, string>;
+  options: {
+    autoCommit?: boolean;
+    createPR?: boolean;
+    deploy?: boolean;
+    rollbackOnFailure?: "none" | "lastGood" | "original";
+  };
+  workingDirectory?: string;
+  backend?: string;
+  isolation: "none" | "worktree";
+  /** Isolation worktree the steps ran in */
+  worktree?: Worktree;
+  /** Branch the steps were committed on */
+  branch?: string;
+  baseBranch?: string;
+  pullRequest?: string;
+  status: RunStatus;
+  /** Error that aborted the run */
+  error?: string;
+  /** Checkpoint the workspace was rolled back to after a failure */
+  rolledBack?: "lastGood" | "original";
+  /** Run this one continued */
+  resumedFrom?: string;
+  createdAt: string;
+  finishedAt?: string;
+  /** Steps that finished, in file order */
+  steps: StepRecord[];
+}
+
+// Runs executing in this process; a "running" record not listed here was interrupted
+const activeRuns = new Set<string>();
+
+/**
+ * Directory run records are stored in
+ * @public
+ */
+export function getRunsDir(): string {
+  return path.join(path.dirname(getConfigPath()), "runs");
+}
+
+/**
+ * Creates and stores a new run record
+ *
+ * @param fields - Arguments of the run
+ * @returns The record, marked active until `finishRun`
+ * @public
+ */
+export function createRun(
+  fields: Omit<WorkflowRunRecord, "id" | "status" | "createdAt" | "steps">
+): WorkflowRunRecord {
+  const record: WorkflowRunRecord = {
+    id: randomUUID(),
+    ...fields,
+    status: "running",
+    createdAt: new Date().toISOString(),
+    steps: [],
+  };
+  activeRuns.add(record.id);
+  saveRun(record);
+  return record;
+}
+
+/**
+ * Writes a run record
+ * @public
+ */
+export function saveRun(record: WorkflowRunRecord): void {
+  const file = path.join(getRunsDir(), `${record.id}.json`);
+  fs.mkdirSync(path.dirname(file), { recursive: true });
+  // Write then rename so readers never see half a file
+  fs.writeFileSync(`${file}.tmp`, JSON.stringify(record, null, 2));
+  fs.renameSync(`${file}.tmp`, file);
+}
+
+/**
+ * Adds a finished step to a run record and stores it
+ *
+ * @param record - Run record
+ * @param report - Step report from the workflow runner
+ * @param commit - Commit made for the step
+ * @public
+ */
+export function recordStep(record: WorkflowRunRecord, report: StepReport, commit?: string): void {
+  const step: StepRecord = {
+    id: stepId(report.step),
+    name: report.step.name,
+    index: report.index,
+    status: report.status,
+    reused: report.reused || undefined,
+    input: report.input,
+    output: report.output,
+    error: report.error,
+    reason: report.reason,
+    changedFiles: report.changedFiles,
+    diff: report.diff ? truncateDiff(report.diff) : undefined,
+    commit,
+    attempts: report.attempts,
+    startedAt: report.startedAt?.toISOString(),
+    finishedAt: report.finishedAt.toISOString(),
+    durationMs: report.startedAt ? report.finishedAt.getTime() - report.startedAt.getTime() : undefined,
+  };
+  record.steps = [...record.steps.filter((s) => s.id !== step.id), step].sort((a, b) => a.index - b.index);
+  saveRun(record);
+}
+
+/**
+ * Marks a run as finished and stores it
+ *
+ * @param record - Run record
+ * @param status - Final status
+ * @param error - Error that aborted the run
+ * @public
+ */
+export function finishRun(record: WorkflowRunRecord, status: Exclude<RunStatus, "running">, error?: string): void {
+  record.status = status;
+  record.error = error;
+  record.finishedAt = new Date().toISOString();
+  activeRuns.delete(record.id);
+  saveRun(record);
+}
+
+/**
+ * Whether a run is executing in this server process
+ * @public
+ */
+export function isRunActive(id: string): boolean {
+  return activeRuns.has(id);
+}
+
+/**
+ * Loads a run record
+ *
+ * @param id - Run identifier
+ * @returns The record, or undefined if there is none
+ * @public
+ */
+export function loadRun(id: string): WorkflowRunRecord | undefined {
+  // Ids become file names, so only accept the format createRun produces
+  if (!RUN_ID.test(id)) return undefined;
+  const file = path.join(getRunsDir(), `${id}.json`);
+  return fs.existsSync(file) ? JSON.parse(fs.readFileSync(file, "utf8")) : undefined;
+}
+
+/**
+ * Lists stored runs, newest first
+ *
+ * @param limit - Maximum number of runs
+ * @returns Run records
+ * @public
+ */
+export function listRuns(limit = 50): WorkflowRunRecord[] {
+  const dir = getRunsDir();
+  if (!fs.existsSync(dir)) return [];
+  return fs.readdirSync(dir)
+    .filter((entry) => entry.endsWith(".json"))
+    .map((entry) => loadRun(entry.replace(/\.json$/, "")))
+    .filter((record): record is WorkflowRunRecord => !!record)
+    .sort((a, b) => b.createdAt.localeCompare(a.createdAt))
+    .slice(0, limit);
+}
+
+const STATUS_EMOJI: Record<RunStatus | "interrupted", string> = {
+  running: "⏳",
+  completed: "✅",
+  failed: "❌",
+  interrupted: "⚠️",
+};
+
+/**
+ * Status of a run as seen by this process
+ *
+ * @param record - Run record
+ * @returns The stored status, or "interrupted" for a run that stopped without finishing
+ * @public
+ */
+export function runStatus(record: WorkflowRunRecord): RunStatus | "interrupted" {
+  return record.status === "running" && !isRunActive(record.id) ? "interrupted" : record.status;
+}
+
+/**
+ * One-line summary of a run
+ * @public
+ */
+export function formatRunLine(record: WorkflowRunRecord): string {
+  const status = runStatus(record);
+  const done = record.steps.filter((s) => s.status === "succeeded").length;
+  return `${STATUS_EMOJI[status]} \`${record.id}\` ${record.workflow} "${record.target}" ` +
+    `(${status}, ${done} step(s) succeeded, started ${record.createdAt})`;
+}
@@ -0,0 +1,169 @@
+/**
+ * Prompt Templates for AgentMesh Workflows
+ * Substitutes workflow context into step prompts
+ *
+ * Placeholders (every occurrence is replaced):
+ * - {target}: the tool's target argument
+ * - {inputs.<name>}: a workflow input
+ * - {steps.<id>.output}: the answer of an earlier step
+ * - {steps.<id>.changedFiles}: files an earlier step changed, one per line
+ * - {steps.<id>.status}: "succeeded", "failed" or "skipped"
+ * - {env.<NAME>}: an environment variable listed in `templateEnv` in the config
+ *
+ * Braces that do not start with one of these roots are left alone, so code
+ * and JSON in prompts pass through untouched.
+ *
+ * @module template
+ */
+
+/**
+ * Raised when a template references something that is not available
+ */
+export class TemplateError extends Error {
+  constructor(message: string) {
+    super(message);
+    this.name = "TemplateError";
+  }
+}
+
+/** How a workflow step ended */
+export type StepStatus = "succeeded" | "failed" | "skipped";
+
+/**
+ * What a finished step exposes to later steps
+ * @interface StepOutputs
+ */
+export interface StepOutputs {
+  status: StepStatus;
+  output: string;
+  changedFiles: string[];
+}
+
+/**
+ * Values available to a template
+ * @interface TemplateContext
+ */
+export interface TemplateContext {
+  target: string;
+  inputs: Record<string, string>;
+  /** Finished steps keyed by step id */
+  steps: Record<string, StepOutputs>;
+  /** Environment variables templates may read */
+  envAllowlist: string[];
+}
+
+/**
+ * A placeholder found in a template
+ * @interface TemplateReference
+ */
+export interface TemplateReference {
+  /** Placeholder as written, including braces */
+  raw: string;
+  root: "target" | "inputs" | "steps" | "env";
+  /** Dotted path after the root */
+  path: string[];
+}
+
+/** Fields of a step that templates can read */
+export const STEP_FIELDS = ["output", "changedFiles", "status"] as const;
+
+const PLACEHOLDER = /\{(target|inputs|steps|env)((?:\.[A-Za-z0-9_-]+)*)\}/g;
+
+/**
+ * Lists the placeholders in a template
+ *
+ * @param template - Prompt template
+ * @returns Every placeholder, in order
+ * @public
+ */
+export function templateReferences(template: string): TemplateReference[] {
+  const refs: TemplateReference[] = [];
+  const pattern = new RegExp(PLACEHOLDER.source, "g");
+  let match: RegExpExecArray | null;
+  while ((match = pattern.exec(template))) {
+    refs.push({
+      raw: match[0],
+      root: match[1] as TemplateReference["root"],
+      path: match[2] ? match[2].slice(1).split(".") : [],
+    });
+  }
+  return refs;
+}
+
+/**
+ * Describes what is wrong with a reference in a static context, or returns
+ * undefined when it is well-formed
+ *
+ * @param ref - Placeholder to check
+ * @param stepIds - Steps guaranteed to have finished before this one
+ * @param inputNames - Declared workflow inputs
+ * @returns Problem description
+ * @public
+ */
+export function checkReference(ref: TemplateReference, stepIds: string[], inputNames: string[]): string | undefined {
+  switch (ref.root) {
+    case "target":
+      return ref.path.length ? `${ref.raw}: target has no fields` : undefined;
+    case "inputs":
+      if (ref.path.length !== 1) return `${ref.raw}: use {inputs.<name>}`;
+      return inputNames.includes(ref.path[0]) ? undefined : `${ref.raw}: input "${ref.path[0]}" is not declared`;
+    case "env":
+      return ref.path.length === 1 ? undefined : `${ref.raw}: use {env.<NAME>}`;
+    case "steps": {
+      const [id, field] = ref.path;
+      if (ref.path.length !== 2 || !(STEP_FIELDS as readonly string[]).includes(field)) {
+        return `${ref.raw}: use {steps.<id>.${STEP_FIELDS.join("|")}}`;
+      }
+      return stepIds.includes(id) ? undefined : `${ref.raw}: step "${id}" does not run before this one`;
+    }
+  }
+}
+
+/**
+ * Looks up the value of a reference
+ *
+ * @param ref - Reference to resolve
+ * @param context - Available values
+ * @returns The value; changed files are a list
+ * @throws {TemplateError} If the reference cannot be resolved
+ * @public
+ */
+export function resolveReference(ref: TemplateReference, context: TemplateContext): string | string[] {
+  const problem = checkReference(ref, Object.keys(context.steps), Object.keys(context.inputs));
+  if (problem) throw new TemplateError(problem);
+
+  switch (ref.root) {
+    case "target":
+      return context.target;
+    case "inputs":
+      return context.inputs[ref.path[0]];
+    case "env": {
+      const name = ref.path[0];
+      if (!context.envAllowlist.includes(name)) {
+        throw new TemplateError(`${ref.raw}: ${name} is not listed in templateEnv`);
+      }
+      return process.env[name] ?? "";
+    }
+    case "steps": {
+      const step = context.steps[ref.path[0]];
+      const field = ref.path[1] as typeof STEP_FIELDS[number];
+      return step[field];
+    }
+  }
+}
+
+/**
+ * Renders a template
+ *
+ * @param template - Prompt template
+ * @param context - Available values
+ * @returns Rendered prompt
+ * @throws {TemplateError} If a placeholder cannot be resolved
+ * @public
+ */
+export function renderTemplate(template: string, context: TemplateContext): string {
+  return template.replace(PLACEHOLDER, (raw) => {
+    const value = resolveReference(templateReferences(raw)[0], context);
+    return Array.isArray(value) ? value.join("\n") : value;
+  });
+}
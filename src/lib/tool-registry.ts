@@ -0,0 +1,79 @@
+/**
+ * Registry of tools that can be invoked programmatically (background jobs)
+ *
+ * Modules are loaded lazily so that tools which use the registry can
+ * themselves be listed without an import cycle.
+ *
+ * @module tool-registry
+ */
+
+import { z } from "zod";
+
+/**
+ * Shape of an xmcp tool module
+ * @interface ToolModule
+ */
+export interface ToolModule {
+  schema: z.ZodRawShape;
+  default: (args: any, extra?: any) => unknown;
+}
+
+const LOADERS: Record<string, () => Promise<ToolModule>> = {
+  code_task: () => import("../tools/code-task"),
+  review_code: () => import("../tools/review-code"),
+  security_audit: () => import("../tools/security-audit"),
+  generate_tests: () => import("../tools/generate-tests"),
+  generate_docs: () => import("../tools/generate-docs"),
+  fix_issues: () => import("../tools/fix-issues"),
+  refactor: () => import("../tools/refactor"),
+  explain_code: () => import("../tools/explain-code"),
+  git_assist: () => import("../tools/git-assist"),
+  scaffold_project: () => import("../tools/scaffold-project"),
+  agent_workflow: () => import("../tools/agent-workflow"),
+  resume_workflow: () => import("../tools/resume-workflow"),
+  kestra_code_intel: () => import("../tools/kestra-code-intel"),
+  vercel_deploy: () => import("../tools/vercel-deploy"),
+};
+
+/** Names of tools that can run as background jobs */
+export const INVOCABLE_TOOLS = Object.keys(LOADERS) as [string, ...string[]];
+
+/**
+ * Validates arguments against a tool's schema and returns a bound invocation
+ *
+ * @param name - Tool name (as registered with MCP)
+ * @param args - Raw arguments
+ * @returns Function that runs the tool (passing on progress and cancellation) and resolves to its text output
+ * @throws {Error} If the tool is unknown or the arguments are invalid
+ * @public
+ */
+export async function prepareToolCall(
+  name: string,
+  args: Record<string, unknown>
+): Promise<(extra?: unknown) => Promise<string>> {
+  const load = LOADERS[name];
+  if (!load) {
+    throw new Error(`Unknown tool: ${name}`);
+  }
+  const tool = await load();
+  const parsed = z.object(tool.schema).safeParse(args);
+  if (!parsed.success) {
+    throw new Error(`Invalid arguments for ${name}: ${parsed.error.message}`);
+  }
+  return async (extra) => resultText(await tool.default(parsed.data, extra));
+}
+
+/**
+ * Flattens a tool result (string or MCP content) to text
+ * @internal
+ */
+function resultText(result: unknown): string {
+  if (typeof result === "string") return result;
+  if (result && typeof result === "object" && Array.isArray((result as { content?: unknown }).content)) {
+    return (result as { content: { type: string; text?: string }[] }).content
+      .filter((c) => c.type === "text")
+      .map((c) => c.text ?? "")
+      .join("\n");
+  }
+  return JSON.stringify(result, null, 2);
+}
@@ -0,0 +1,177 @@
+/**
+ * Verification Gates for AgentMesh Workflows
+ * Runs a workflow step's shell command (`pnpm test`, `tsc --noEmit`,
+ * `cargo test`...) and, when it fails, lets the agent fix the code before
+ * running it again
+ *
+ * Commands come from workflow files, which are trusted like any project
+ * script. They run through the system shell in the step's working directory
+ * and are never templated, so neither tool arguments nor agent output can
+ * reach the shell.
+ *
+ * @module verify
+ */
+
+import { execa } from "execa";
+import { runAgentTask } from "./agent";
+import { getRunContext } from "./run-context";
+import { resolveWorkspacePath } from "./workspace";
+
+const DEFAULT_COMMAND_TIMEOUT = 600000; // 10 minutes
+// Output kept per command run; failures are usually reported at the end
+const MAX_OUTPUT_LENGTH = 10000;
+// Output shown in the failure message of a step
+const MAX_FAILURE_OUTPUT = 2000;
+
+/** Fix request used when the step does not define its own */
+export const DEFAULT_FIX_PROMPT =
+  "The verification command below failed. Fix the code so that it passes. Do not skip, weaken or delete the checks.";
+
+/**
+ * Outcome of one command run
+ * @interface CommandResult
+ */
+export interface CommandResult {
+  command: string;
+  success: boolean;
+  exitCode?: number;
+  timedOut: boolean;
+  /** Combined stdout and stderr (the last 10000 characters) */
+  output: string;
+}
+
+/**
+ * Settings for a verification gate
+ * @interface GateOptions
+ */
+export interface GateOptions {
+  /** Working directory (default: the server's cwd) */
+  cwd?: string;
+  /** Timeout per command run in ms (default: 10 minutes) */
+  timeout?: number;
+  /** Fix-and-rerun rounds after the first failure (default: 0) */
+  fixAttempts?: number;
+  /** Request sent to the agent; the failing output is appended */
+  fixPrompt?: string;
+  /** Agent backend for fixes */
+  backend?: string;
+  /** Report the files each fix changed */
+  trackChanges?: boolean;
+  /** Called before each fix round */
+  onFix?: (attempt: number) => void;
+}
+
+/**
+ * Outcome of a verification gate
+ * @interface GateResult
+ */
+export interface GateResult {
+  success: boolean;
+  /** Status line followed by the output of the last command run */
+  output: string;
+  /** Why the gate failed */
+  error?: string;
+  /** Files changed by fixes */
+  changedFiles: string[];
+  /** Unified diff of the fixes (with `trackChanges`) */
+  diff?: string;
+  /** Fix rounds run */
+  fixes: number;
+  /** Last command run */
+  last: CommandResult;
+}
+
+function lastChars(text: string, max: number): string {
+  return text.length > max ? `...(truncated)\n${text.substring(text.length - max)}` : text;
+}
+
+/**
+ * Describes how a command ended
+ *
+ * @param result - Command result
+ * @returns e.g. "`pnpm test` exited with code 1"
+ * @public
+ */
+export function describeCommand(result: CommandResult): string {
+  if (result.success) return `\`${result.command}\` passed`;
+  if (result.timedOut) return `\`${result.command}\` timed out`;
+  return `\`${result.command}\` exited with code ${result.exitCode ?? "unknown"}`;
+}
+
+/**
+ * Runs a shell command and captures its output
+ *
+ * @param command - Shell command
+ * @param cwd - Directory to run in
+ * @param timeout - Timeout in ms
+ * @returns Exit status and output; never throws for a failing command
+ * @public
+ */
+export async function runCommand(command: string, cwd: string, timeout = DEFAULT_COMMAND_TIMEOUT): Promise<CommandResult> {
+  const result = await execa(command, {
+    cwd,
+    shell: true,
+    all: true,
+    reject: false,
+    timeout,
+    stdin: "ignore",
+    cancelSignal: getRunContext().signal,
+  });
+  return {
+    command,
+    success: result.exitCode === 0 && !result.timedOut,
+    exitCode: result.exitCode,
+    timedOut: Boolean(result.timedOut),
+    output: lastChars(result.all ?? "", MAX_OUTPUT_LENGTH),
+  };
+}
+
+/**
+ * Runs a command until it passes or the fix rounds are used up
+ *
+ * @param command - Shell command that must exit with 0
+ * @param options - Gate settings
+ * @returns Final status, with the files fixes changed
+ * @throws {WorkspaceError} If the working directory is outside the workspace roots
+ * @public
+ */
+export async function runGate(command: string, options: GateOptions = {}): Promise<GateResult> {
+  const cwd = resolveWorkspacePath(options.cwd || process.cwd());
+  const fixAttempts = options.fixAttempts ?? 0;
+  const changed = new Set<string>();
+  const diffs: string[] = [];
+  let fixError: string | undefined;
+  let fixes = 0;
+
+  let last = await runCommand(command, cwd, options.timeout);
+  while (!last.success && fixes < fixAttempts) {
+    fixes++;
+    options.onFix?.(fixes);
+    const fix = await runAgentTask(
+      `${options.fixPrompt ?? DEFAULT_FIX_PROMPT}\n\nCommand: ${command}\nResult: ${describeCommand(last)}\n\nOutput:\n${last.output}`,
+      { cwd, backend: options.backend, trackChanges: options.trackChanges }
+    );
+    const changes = fix.changes;
+    for (const file of changes ? [...changes.added, ...changes.modified, ...changes.deleted] : []) changed.add(file);
+    if (changes?.diff) diffs.push(changes.diff);
+    if (!fix.success) {
+      fixError = `fix attempt ${fixes} failed: ${fix.error}`;
+      break;
+    }
+    last = await runCommand(command, cwd, options.timeout);
+  }
+
+  const rounds = fixes ? ` after ${fixes} fix attempt${fixes > 1 ? "s" : ""}` : "";
+  const status = `${describeCommand(last)}${rounds}`;
+  return {
+    success: last.success,
+    output: `${status}\n\n${last.output}`,
+    error: last.success
+      ? undefined
+      : `${status}${fixError ? ` (${fixError})` : ""}\n\n\`\`\`\n${lastChars(last.output, MAX_FAILURE_OUTPUT)}\n\`\`\``,
+    changedFiles: [...changed].sort(),
+    diff: diffs.length ? diffs.join("\n") : undefined,
+    fixes,
+    last,
+  };
+}
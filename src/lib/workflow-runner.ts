@@ -0,0 +1,310 @@
+/**
+ * Workflow Execution for AgentMesh
+ * Runs the steps of a workflow as a dependency graph
+ *
+ * A step becomes ready once every step it depends on has finished; ready
+ * steps start in file order, at most `concurrency` at a time. When a step is
+ * ready:
+ * - it is skipped if a dependency was skipped or its `when` condition is false
+ * - otherwise its prompt is rendered and it runs (prompt steps go to the
+ *   agent, `run` steps are handed over as they are), with up to `retries`
+ *   extra attempts after a failure
+ * - a step that still fails stops the workflow: no further steps start and
+ *   running ones finish. With `continueOnError` the failure is recorded
+ *   (`steps.<id>.status == "failed"`) and the workflow carries on.
+ * - after a step with `approval` succeeds, its output goes to a reviewer.
+ *   Rejecting with feedback re-runs the step with the feedback (up to 3
+ *   times); rejecting without feedback stops the workflow, whatever
+ *   `continueOnError` says.
+ * - steps listed in `reuse` (the succeeded steps of a run being resumed) are
+ *   not run again; their stored outcome is used instead.
+ *
+ * @module workflow-runner
+ */
+
+import { evaluateCondition, parseCondition } from "./condition";
+import { withRunContext, type ApprovalDecision } from "./run-context";
+import { renderTemplate, TemplateError, type StepOutputs, type StepStatus, type TemplateContext } from "./template";
+import { stepDependencies, stepId, type WorkflowDefinition, type WorkflowStep } from "./workflows";
+
+// Times a rejected step is re-run with the reviewer's feedback
+const MAX_REVISIONS = 3;
+
+/**
+ * Outcome of one attempt at a step
+ * @interface StepAttempt
+ */
+export interface StepAttempt {
+  success: boolean;
+  output: string;
+  error?: string;
+  changedFiles: string[];
+  /** Unified diff of the attempt's changes */
+  diff?: string;
+}
+
+/**
+ * How a step ended
+ * @interface StepReport
+ */
+export interface StepReport {
+  step: WorkflowStep;
+  /** 1-based position in the workflow file */
+  index: number;
+  status: StepStatus;
+  /** Rendered prompt, or the command of a `run` step */
+  input?: string;
+  output: string;
+  error?: string;
+  changedFiles: string[];
+  /** Unified diff of the last attempt's changes */
+  diff?: string;
+  /** Attempts made (0 when skipped or reused) */
+  attempts: number;
+  /** Why the step was skipped */
+  reason?: string;
+  /** Review of a step with `approval` */
+  review?: { approved: boolean; revisions: number };
+  /** Taken from `reuse` instead of being run */
+  reused?: boolean;
+  /** When the first attempt started (unset when the step did not run) */
+  startedAt?: Date;
+  finishedAt: Date;
+}
+
+/**
+ * Callbacks and settings for a workflow run
+ * @interface WorkflowRunOptions
+ */
+export interface WorkflowRunOptions {
+  /** Template values; finished steps are added to `context.steps` */
+  context: TemplateContext;
+  /** Most steps running at once (default: the workflow's `concurrency`, else 1) */
+  concurrency?: number;
+  /** Outcomes of steps that already succeeded, by step id; these steps are not run */
+  reuse?: Record<string, StepOutputs>;
+  /**
+   * Runs one attempt of a step with its rendered prompt (undefined for `run`
+   * steps). A TemplateError thrown here fails the step instead of the run.
+   */
+  runStep(step: WorkflowStep, prompt: string | undefined, attempt: number): Promise<StepAttempt>;
+  /**
+   * Asks a reviewer about the output of a step with `approval`; calls never
+   * overlap. Without it such steps are not approved.
+   */
+  approve?(step: WorkflowStep, output: string): Promise<ApprovalDecision>;
+  /** Called when a step starts its first attempt */
+  onStepStart?(step: WorkflowStep, index: number): void;
+  /**
+   * Called when a step finishes or is skipped, before its dependents start.
+   * Calls never overlap, so it may commit or write shared state.
+   */
+  onStepEnd?(report: StepReport): Promise<void> | void;
+}
+
+/**
+ * Result of a workflow run
+ * @interface WorkflowRun
+ */
+export interface WorkflowRun {
+  /** False when a step failed without `continueOnError` */
+  completed: boolean;
+  /** Steps that finished or were skipped, in file order */
+  reports: StepReport[];
+  /** First step that stopped the workflow */
+  stoppedAt?: StepReport;
+}
+
+function revisionPrompt(prompt: string, previous: string, feedback: string): string {
+  return `${prompt}
+
+A reviewer rejected your previous answer:
+
+${previous}
+
+Reviewer feedback:
+${feedback}
+
+Produce a new answer that addresses the feedback.`;
+}
+
+/**
+ * Runs one step through skipping, rendering, retries and approval
+ * @internal
+ */
+async function runOne(
+  steps: WorkflowStep[],
+  index: number,
+  finished: Map<string, StepReport>,
+  options: WorkflowRunOptions,
+  oneAtATime: <T>(fn: () => Promise<T>) => Promise<T>
+): Promise<StepReport> {
+  const step = steps[index];
+  // Agent events of this step are labelled with it, even while siblings run
+  const runStep = (input: string | undefined, attempt: number) => withRunContext(
+    { step: { index: index + 1, total: steps.length, name: step.name } },
+    () => options.runStep(step, input, attempt)
+  );
+  let prompt: string | undefined;
+  let startedAt: Date | undefined;
+  // Every outcome is visible to later templates and conditions
+  const done = (
+    report: Omit<StepReport, "step" | "index" | "changedFiles" | "finishedAt">,
+    attempt?: StepAttempt
+  ): StepReport => {
+    const changedFiles = attempt?.changedFiles ?? [];
+    options.context.steps[stepId(step)] = { status: report.status, output: report.output, changedFiles };
+    return {
+      step,
+      index: index + 1,
+      input: prompt ?? step.run,
+      ...report,
+      changedFiles,
+      diff: attempt?.diff,
+      startedAt,
+      finishedAt: new Date(),
+    };
+  };
+
+  const reused = options.reuse?.[stepId(step)];
+  if (reused) {
+    return done(
+      { status: "succeeded", output: reused.output, attempts: 0, reused: true },
+      { success: true, output: reused.output, changedFiles: reused.changedFiles }
+    );
+  }
+
+  const skippedDep = stepDependencies(steps, index).find((dep) => finished.get(dep)?.status === "skipped");
+  if (skippedDep) {
+    return done({ status: "skipped", output: "", attempts: 0, reason: `${finished.get(skippedDep)?.step.name} was skipped` });
+  }
+
+  try {
+    if (step.when && !evaluateCondition(parseCondition(step.when), options.context)) {
+      return done({ status: "skipped", output: "", attempts: 0, reason: `\`${step.when}\` is false` });
+    }
+    prompt = step.prompt === undefined ? undefined : renderTemplate(step.prompt, options.context);
+  } catch (error) {
+    if (!(error instanceof TemplateError)) throw error;
+    return done({ status: "failed", output: "", attempts: 0, error: error.message });
+  }
+
+  options.onStepStart?.(step, index + 1);
+  startedAt = new Date();
+  const maxAttempts = (step.retries ?? 0) + 1;
+  let attempt = 0;
+  let result: StepAttempt;
+  try {
+    do {
+      attempt++;
+      result = await runStep(prompt, attempt);
+    } while (!result.success && attempt < maxAttempts);
+  } catch (error) {
+    if (!(error instanceof TemplateError)) throw error;
+    return done({ status: "failed", output: "", attempts: attempt, error: error.message });
+  }
+
+  if (step.approval && result.success) {
+    for (let revisions = 0; ; revisions++) {
+      const output = result.output;
+      const decision: ApprovalDecision = options.approve
+        ? await oneAtATime(() => options.approve!(step, output))
+        : { approved: false, reason: "no reviewer is available" };
+      if (decision.approved) {
+        return done({ status: "succeeded", output, attempts: attempt, review: { approved: true, revisions } }, result);
+      }
+      if (!decision.feedback || prompt === undefined || revisions >= MAX_REVISIONS) {
+        const why = decision.reason ?? (decision.feedback ? `rejected: ${decision.feedback}` : "rejected by the reviewer");
+        return done({
+          status: "failed",
+          output,
+          error: `Not approved (${why})`,
+          attempts: attempt,
+          review: { approved: false, revisions },
+        }, result);
+      }
+      attempt++;
+      result = await runStep(revisionPrompt(prompt, output, decision.feedback), attempt);
+      if (!result.success) break;
+    }
+  }
+
+  return done({
+    status: result.success ? "succeeded" : "failed",
+    output: result.output,
+    error: result.error,
+    attempts: attempt,
+  }, result);
+}
+
+/**
+ * Runs a workflow
+ *
+ * @param workflow - Workflow to run
+ * @param options - Step runner, callbacks and settings
+ * @returns Reports of every step that was reached
+ * @throws {Error} If `runStep` or `onStepEnd` throws; steps already running are left to finish
+ * @public
+ */
+export function runWorkflow(workflow: WorkflowDefinition, options: WorkflowRunOptions): Promise<WorkflowRun> {
+  const steps = workflow.steps;
+  const limit = Math.max(1, options.concurrency ?? workflow.concurrency ?? 1);
+  const started = new Set<number>();
+  const finished = new Map<string, StepReport>();
+  let stoppedAt: StepReport | undefined;
+  let running = 0;
+  let failure: Error | undefined;
+  // Serialises onStepEnd calls
+  let ending: Promise<void> = Promise.resolve();
+  // Serialises approval requests so a reviewer sees one at a time
+  let approving: Promise<unknown> = Promise.resolve();
+  const oneAtATime = <T>(fn: () => Promise<T>): Promise<T> => {
+    const next = approving.then(fn);
+    approving = next.catch(() => undefined);
+    return next;
+  };
+
+  return new Promise((resolve, reject) => {
+    const schedule = () => {
+      if (!stoppedAt && !failure) {
+        for (let i = 0; i < steps.length && running < limit; i++) {
+          if (started.has(i)) continue;
+          if (!stepDependencies(steps, i).every((dep) => finished.has(dep))) continue;
+          started.add(i);
+          running++;
+          void finish(i);
+        }
+      }
+      if (running > 0) return;
+      if (failure) {
+        reject(failure);
+        return;
+      }
+      resolve({
+        completed: !stoppedAt,
+        reports: [...finished.values()].sort((a, b) => a.index - b.index),
+        stoppedAt,
+      });
+    };
+
+    const finish = async (index: number) => {
+      try {
+        const report = await runOne(steps, index, finished, options, oneAtATime);
+        const ended = ending.then(() => options.onStepEnd?.(report));
+        ending = ended.catch(() => undefined);
+        await ended;
+        finished.set(stepId(report.step), report);
+        const fatal = !report.step.continueOnError || report.review?.approved === false;
+        if (report.status === "failed" && fatal && !stoppedAt) {
+          stoppedAt = report;
+        }
+      } catch (error) {
+        if (!failure) failure = error as Error;
+      }
+      running--;
+      schedule();
+    };
+
+    schedule();
+  });
+}
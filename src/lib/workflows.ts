@@ -0,0 +1,323 @@
+/**
+ * Workflow Definitions for AgentMesh
+ * Loads the multi-step workflows run by `agent_workflow` from YAML files
+ *
+ * Workflows come from two directories; a user file replaces a built-in one
+ * with the same name:
+ * - `workflows/` in the AgentMesh package (built-ins)
+ * - `.agentmesh/workflows/` next to the config file (user-defined)
+ *
+ * The file name (without `.yaml`/`.yml`) is the workflow name:
+ *
+ * ```yaml
+ * description: Analyze → Fix → Test
+ * inputs:
+ *   severity: { description: How urgent the bug is, default: normal }
+ * steps:
+ *   - name: Analysis
+ *     prompt: "Analyze and identify the root cause of: {target}"
+ *     mode: plan
+ *     timeout: 600   # seconds
+ *   - name: Fix
+ *     prompt: "Fix {target} ({inputs.severity}). Root cause: {steps.Analysis.output}"
+ * ```
+ *
+ * Prompts are templates (see the template module); references to inputs and
+ * earlier steps are checked when the file is loaded.
+ *
+ * Steps form a dependency graph. A step without `dependsOn` depends on the
+ * step before it, so a plain list runs in order; listing dependencies lets
+ * independent steps run side by side (up to `concurrency` at once):
+ *
+ * ```yaml
+ * concurrency: 2
+ * steps:
+ *   - name: Implementation
+ *     prompt: "Implement {target}"
+ *   - name: Docs
+ *     prompt: "Document {steps.Implementation.changedFiles}"
+ *     dependsOn: [Implementation]
+ *   - name: Tests
+ *     prompt: "Test {steps.Implementation.changedFiles}"
+ *     dependsOn: [Implementation]
+ *     retries: 1
+ *   - name: Review
+ *     prompt: Review the changes
+ *     dependsOn: [Docs, Tests]
+ *     when: steps.Tests.status == "succeeded"
+ * ```
+ *
+ * See the workflow-runner module for how `when`, `retries` and
+ * `continueOnError` affect a run.
+ *
+ * A step with `run` instead of `prompt` is a verification gate: it runs a
+ * shell command and fails when the command does (see the verify module).
+ * With `fix`, the agent gets the failing output and tries to fix the code,
+ * up to `attempts` times:
+ *
+ * ```yaml
+ *   - name: Tests
+ *     run: pnpm test
+ *     fix: { attempts: 2 }
+ * ```
+ *
+ * `approval: true` pauses the workflow after the step until a reviewer
+ * approves its output (see the approval module).
+ *
+ * @module workflows
+ */
+
+import * as fs from "fs";
+import * as path from "path";
+import { parse as parseYaml } from "yaml";
+import { z } from "zod";
+import { getConfigPath } from "./config";
+import { checkReference, templateReferences } from "./template";
+import { conditionReferences, parseCondition } from "./condition";
+
+const STEP_ID = /^[A-Za-z0-9_-]+$/;
+
+const stepSchema = z.object({
+  /** Identifier used in `{steps.<id>...}` (default: the name) */
+  id: z.string().regex(STEP_ID, "use letters, digits, \"-\" and \"_\"").optional(),
+  /** Step name shown in output and progress notifications */
+  name: z.string().min(1),
+  /** Prompt template (see the template module) */
+  prompt: z.string().min(1).optional(),
+  /** Shell command to run instead of a prompt; the step fails unless it exits with 0 */
+  run: z.string().min(1).optional(),
+  /** Let the agent fix failures of `run` */
+  fix: z.object({
+    /** Fix request template; the failing output is appended (default: a generic request) */
+    prompt: z.string().min(1).optional(),
+    /** Fix-and-rerun rounds (default: 1) */
+    attempts: z.number().int().min(1).max(10).optional(),
+  }).strict().optional(),
+  /** Agent mode (default: act) */
+  mode: z.enum(["act", "plan"]).optional(),
+  /** Step timeout in seconds (default: the backend timeout) */
+  timeout: z.number().positive().optional(),
+  /** Ids of earlier steps that must finish first (default: the previous step) */
+  dependsOn: z.array(z.string().regex(STEP_ID)).optional(),
+  /** Condition on inputs and earlier steps; the step is skipped when false */
+  when: z.string().min(1).optional(),
+  /** Extra attempts after a failure (default: 0) */
+  retries: z.number().int().min(0).max(10).optional(),
+  /** Keep running the workflow if this step fails (default: false) */
+  continueOnError: z.boolean().optional(),
+  /** Wait for a human to approve the step's output before going on (default: false) */
+  approval: z.boolean().optional(),
+}).strict();
+
+const inputSchema = z.object({
+  description: z.string().optional(),
+  /** Value used when the caller does not provide one; inputs without a default are required */
+  default: z.string().optional(),
+}).strict();
+
+const workflowSchema = z.object({
+  /** One-line summary listed in the tool description */
+  description: z.string().optional(),
+  /** Named values callers pass in, referenced as `{inputs.<name>}` */
+  inputs: z.record(inputSchema).optional(),
+  /** Most steps running at the same time (default: 1) */
+  concurrency: z.number().int().positive().max(16).optional(),
+  steps: z.array(stepSchema).min(1),
+}).strict().superRefine((workflow, ctx) => {
+  const inputNames = Object.keys(workflow.inputs ?? {});
+  const issue = (index: number, field: string, message: string) =>
+    ctx.addIssue({ code: z.ZodIssueCode.custom, path: ["steps", index, field], message });
+  // Steps each one transitively depends on, keyed by id
+  const upstream = new Map<string, string[]>();
+
+  workflow.steps.forEach((step, index) => {
+    const id = stepId(step);
+    if (upstream.has(id)) issue(index, "id", `Duplicate step id "${id}"`);
+
+    const ancestors: string[] = [];
+    for (const dep of stepDependencies(workflow.steps, index)) {
+      const above = upstream.get(dep);
+      if (!above) {
+        issue(index, "dependsOn", `"${dep}" is not an earlier step`);
+        continue;
+      }
+      for (const name of [dep, ...above]) {
+        if (!ancestors.includes(name)) ancestors.push(name);
+      }
+    }
+
+    if ((step.prompt === undefined) === (step.run === undefined)) {
+      issue(index, "prompt", 'set either "prompt" or "run"');
+    }
+    if (step.run !== undefined && step.mode) issue(index, "mode", "mode only applies to prompt steps");
+    if (step.prompt !== undefined && step.fix) issue(index, "fix", "fix only applies to run steps");
+
+    for (const [field, template] of [["prompt", step.prompt], ["fix", step.fix?.prompt]] as const) {
+      for (const ref of templateReferences(template ?? "")) {
+        const problem = checkReference(ref, ancestors, inputNames);
+        if (problem) issue(index, field, problem);
+      }
+    }
+    if (step.when) {
+      try {
+        for (const ref of conditionReferences(parseCondition(step.when))) {
+          const problem = checkReference(ref, ancestors, inputNames);
+          if (problem) issue(index, "when", problem);
+        }
+      } catch (error) {
+        issue(index, "when", (error as Error).message);
+      }
+    }
+    upstream.set(id, ancestors);
+  });
+});
+
+export type WorkflowStep = z.infer<typeof stepSchema>;
+export type WorkflowInput = z.infer<typeof inputSchema>;
+
+/**
+ * Identifier of a step in templates
+ *
+ * @param step - Workflow step
+ * @returns The step's `id`, or its name
+ * @public
+ */
+export function stepId(step: WorkflowStep): string {
+  return step.id ?? step.name;
+}
+
+/**
+ * Ids of the steps a step waits for
+ *
+ * @param steps - All steps of the workflow
+ * @param index - Position of the step
+ * @returns Its `dependsOn`, or the previous step's id
+ * @public
+ */
+export function stepDependencies(steps: WorkflowStep[], index: number): string[] {
+  return steps[index].dependsOn ?? (index > 0 ? [stepId(steps[index - 1])] : []);
+}
+
+/**
+ * A loaded workflow
+ * @interface WorkflowDefinition
+ */
+export interface WorkflowDefinition extends z.infer<typeof workflowSchema> {
+  /** Workflow name (file name without extension) */
+  name: string;
+  /** File the workflow was loaded from */
+  file: string;
+}
+
+// Workflow names double as tool enum values, so keep them simple
+export const WORKFLOW_NAME_PATTERN = /^[a-z0-9][a-z0-9_-]*$/i;
+
+/**
+ * Finds the `workflows/` directory that ships with AgentMesh, from either
+ * the source tree or the built bundle (falling back to the server's cwd)
+ * @internal
+ */
+function findBuiltinDir(): string | undefined {
+  for (const start of [__dirname, process.cwd()]) {
+    let dir = start;
+    for (;;) {
+      const candidate = path.join(dir, "workflows");
+      if (fs.existsSync(path.join(dir, "package.json")) && fs.existsSync(candidate)) return candidate;
+      const parent = path.dirname(dir);
+      if (parent === dir) break;
+      dir = parent;
+    }
+  }
+  return undefined;
+}
+
+/**
+ * Directory user-defined workflows are loaded from
+ * @public
+ */
+export function getUserWorkflowDir(): string {
+  return path.join(path.dirname(getConfigPath()), "workflows");
+}
+
+/**
+ * Parses and validates one workflow file
+ *
+ * @param file - Path to a `.yaml` or `.yml` file
+ * @returns The validated workflow
+ * @throws {Error} If the file is not valid YAML or does not match the schema
+ * @public
+ */
+export function loadWorkflowFile(file: string): WorkflowDefinition {
+  const name = path.basename(file).replace(/\.ya?ml$/, "");
+  if (!WORKFLOW_NAME_PATTERN.test(name)) {
+    throw new Error(`[AgentMesh] Invalid workflow name "${name}" (${file}): use letters, digits, "-" and "_"`);
+  }
+
+  let raw: unknown;
+  try {
+    raw = parseYaml(fs.readFileSync(file, "utf8"));
+  } catch (error) {
+    throw new Error(`[AgentMesh] Invalid workflow ${file}: ${(error as Error).message}`);
+  }
+  const parsed = workflowSchema.safeParse(raw);
+  if (!parsed.success) {
+    const issues = parsed.error.issues.map((i) => `${i.path.join(".") || "(root)"}: ${i.message}`).join("; ");
+    throw new Error(`[AgentMesh] Invalid workflow ${file}: ${issues}`);
+  }
+  return { ...parsed.data, name, file };
+}
+
+/**
+ * Loads each workflow file on its own, user-defined ones overriding built-ins
+ *
+ * A file that fails to load is kept as its error under its name, so it only
+ * affects that workflow (and still hides a built-in it was meant to replace).
+ * @internal
+ */
+function loadEach(): Map<string, WorkflowDefinition | Error> {
+  const workflows = new Map<string, WorkflowDefinition | Error>();
+  for (const dir of [findBuiltinDir(), getUserWorkflowDir()]) {
+    if (!dir || !fs.existsSync(dir)) continue;
+    for (const entry of fs.readdirSync(dir).filter((e) => /\.ya?ml$/.test(e)).sort()) {
+      const name = entry.replace(/\.ya?ml$/, "");
+      try {
+        workflows.set(name, loadWorkflowFile(path.join(dir, entry)));
+      } catch (error) {
+        workflows.set(name, error as Error);
+      }
+    }
+  }
+  return workflows;
+}
+
+/**
+ * Loads every workflow, user-defined ones overriding built-ins
+ *
+ * Files that fail to load are skipped with a warning; `getWorkflow` reports
+ * the error when that workflow is requested.
+ *
+ * @returns Workflows keyed by name
+ * @public
+ */
+export function loadWorkflows(): Record<string, WorkflowDefinition> {
+  const workflows: Record<string, WorkflowDefinition> = {};
+  loadEach().forEach((workflow, name) => {
+    if (workflow instanceof Error) console.warn(workflow.message);
+    else workflows[name] = workflow;
+  });
+  return workflows;
+}
+
+/**
+ * Looks up a workflow by name
+ *
+ * @param name - Workflow name
+ * @returns The workflow, or undefined if none has that name
+ * @throws {Error} If the workflow's file is invalid
+ * @public
+ */
+export function getWorkflow(name: string): WorkflowDefinition | undefined {
+  const workflow = loadEach().get(name);
+  if (workflow instanceof Error) throw workflow;
+  return workflow;
+}
@@ -0,0 +1,104 @@
+/**
+ * Workspace Path Jail for AgentMesh
+ * Confines working directories and file targets to the configured workspace roots
+ *
+ * Paths are resolved through symlinks before they are compared, so a link
+ * inside a root that points outside of it is rejected. Paths that do not exist
+ * yet are resolved through their longest existing ancestor.
+ *
+ * @module workspace
+ */
+
+import * as fs from "fs";
+import * as path from "path";
+import { loadConfig } from "./config";
+
+/**
+ * Raised when a path falls outside every workspace root
+ */
+export class WorkspaceError extends Error {
+  constructor(message: string) {
+    super(message);
+    this.name = "WorkspaceError";
+  }
+}
+
+/**
+ * Resolves a path through symlinks, including paths that do not exist yet
+ *
+ * @param target - Absolute path
+ * @returns Canonical path
+ * @internal
+ */
+function realpathLenient(target: string): string {
+  const missing: string[] = [];
+  let current = target;
+  for (;;) {
+    try {
+      return path.join(fs.realpathSync.native(current), ...missing.reverse());
+    } catch {
+      const parent = path.dirname(current);
+      if (parent === current) return target;
+      missing.push(path.basename(current));
+      current = parent;
+    }
+  }
+}
+
+function isWithin(root: string, candidate: string): boolean {
+  const relative = path.relative(root, candidate);
+  return relative === "" || (!relative.startsWith("..") && !path.isAbsolute(relative));
+}
+
+/**
+ * Canonical workspace roots from the configuration
+ * @public
+ */
+export function getWorkspaceRoots(): string[] {
+  return loadConfig().workspaceRoots.map((root) => realpathLenient(path.resolve(root)));
+}
+
+/**
+ * Resolves a path and checks that it lies inside a workspace root
+ *
+ * @param target - Path to check; relative paths resolve against `base`
+ * @param base - Directory relative paths are resolved from (defaults to cwd)
+ * @returns Canonical absolute path
+ * @throws {WorkspaceError} If the path is outside every workspace root
+ * @public
+ */
+export function resolveWorkspacePath(target: string, base: string = process.cwd()): string {
+  const resolved = realpathLenient(path.resolve(base, target));
+  const roots = getWorkspaceRoots();
+  if (!roots.some((root) => isWithin(root, resolved))) {
+    console.error(`[AgentMesh] Rejected path outside workspace roots: ${target}`);
+    throw new WorkspaceError(
+      `Path "${target}" is outside the allowed workspace roots (${roots.join(", ")})`
+    );
+  }
+  return resolved;
+}
+
+/**
+ * Checks a tool `target` that may be a path or free text
+ *
+ * Targets are treated as paths when they are absolute, start with `~`,
+ * contain a `..` segment, or name an existing file. Code snippets, git refs
+ * and feature descriptions pass through unchanged.
+ *
+ * @param target - Tool target argument
+ * @param workingDirectory - Directory the tool runs in
+ * @throws {WorkspaceError} If the target is a path outside every workspace root
+ * @public
+ */
+export function checkWorkspaceTarget(target: string | undefined, workingDirectory?: string): void {
+  if (!target || target.includes("\n")) return;
+  const base = resolveWorkspacePath(workingDirectory || process.cwd());
+  const expanded = target.startsWith("~") ? path.join(process.env.HOME || "/", target.slice(1)) : target;
+  const looksLikePath =
+    path.isAbsolute(expanded) ||
+    expanded !== target ||
+    expanded.split(/[\\/]/).includes("..") ||
+    fs.existsSync(path.resolve(base, expanded));
+  if (looksLikePath) resolveWorkspacePath(expanded, base);
+}
@@ -0,0 +1,328 @@
+/**
+ * Git Worktree Isolation for AgentMesh
+ * Runs destructive agent tasks in a throwaway worktree on a new branch
+ *
+ * The worktree is created from HEAD under `.git/agentmesh/worktrees`, so
+ * uncommitted work in the main checkout is never touched. After the run the
+ * changes are committed on the branch and the diff is returned; the user then
+ * merges or discards the branch with `manage_worktree`.
+ *
+ * @module worktree
+ */
+
+import * as fs from "fs";
+import * as path from "path";
+import { randomUUID } from "crypto";
+import { commitAll, currentBranch, findRepoRoot, git, identityArgs } from "./git";
+import { resolveWorkspacePath, WorkspaceError } from "./workspace";
+import { truncateDiff } from "./changes";
+
+/** Branch prefix for every isolation branch */
+export const WORKTREE_BRANCH_PREFIX = "agentmesh/";
+
+/**
+ * Isolation mode accepted by destructive tools
+ */
+export type IsolationMode = "none" | "worktree";
+
+/**
+ * A worktree created for one tool call
+ * @interface Worktree
+ */
+export interface Worktree {
+  /** Top level of the main checkout */
+  repoRoot: string;
+  /** Worktree directory */
+  path: string;
+  /** Directory the agent runs in (the requested subdirectory, mapped into the worktree) */
+  cwd: string;
+  /** Isolation branch */
+  branch: string;
+  /** Commit the branch was created from */
+  base: string;
+  /** Branch checked out in the main checkout when the worktree was created */
+  baseBranch: string;
+}
+
+/**
+ * What an isolated run produced
+ * @interface WorktreeOutcome
+ */
+export interface WorktreeOutcome {
+  /** Isolation branch (removed when nothing changed) */
+  branch: string;
+  /** Worktree directory (removed when nothing changed) */
+  path: string;
+  /** Whether the agent changed any files */
+  changed: boolean;
+  /** Commit holding the agent's changes */
+  commit?: string;
+  /** `git diff --stat` against the base commit */
+  diffStat: string;
+  /** Full diff against the base commit */
+  diff: string;
+}
+
+/**
+ * An isolation worktree listed by `listWorktrees`
+ * @interface WorktreeInfo
+ */
+export interface WorktreeInfo {
+  branch: string;
+  path: string;
+  head: string;
+}
+
+/**
+ * Finds the top level of the repository containing a directory
+ *
+ * @param dir - Any directory inside the repository
+ * @returns Repository root
+ * @throws {Error} If the directory is not inside a git repository
+ * @public
+ */
+export async function getRepoRoot(dir: string): Promise<string> {
+  const root = await findRepoRoot(dir);
+  if (!root) {
+    throw new Error(`Worktree isolation needs a git repository, but ${dir} is not inside one`);
+  }
+  return root;
+}
+
+/**
+ * Creates a worktree on a new branch from HEAD
+ *
+ * @param workingDirectory - Canonical directory the tool was asked to work in
+ * @param label - Short name used in the branch (usually the tool name)
+ * @returns The new worktree
+ * @throws {Error} If the directory is not in a git repository or has no commits
+ * @public
+ */
+export async function createWorktree(workingDirectory: string, label: string): Promise<Worktree> {
+  const repoRoot = await getRepoRoot(workingDirectory);
+  const base = await git(["rev-parse", "HEAD"], repoRoot).catch(() => {
+    throw new Error(`Worktree isolation needs at least one commit in ${repoRoot}`);
+  });
+  const commonDir = path.resolve(repoRoot, await git(["rev-parse", "--git-common-dir"], repoRoot));
+
+  const id = `${label.replace(/[^a-z0-9-]+/gi, "-").toLowerCase()}-${randomUUID().slice(0, 8)}`;
+  const branch = `${WORKTREE_BRANCH_PREFIX}${id}`;
+  const worktreePath = path.join(commonDir, "agentmesh", "worktrees", id);
+
+  await git(["worktree", "add", "-b", branch, worktreePath, base], repoRoot);
+  console.log(`[AgentMesh] Created worktree ${worktreePath} on ${branch}`);
+
+  const subdir = path.relative(repoRoot, workingDirectory);
+  return {
+    repoRoot,
+    path: worktreePath,
+    cwd: path.join(worktreePath, subdir),
+    branch,
+    base,
+    baseBranch: await currentBranch(repoRoot),
+  };
+}
+
+/**
+ * Commits the agent's changes on the isolation branch and collects the diff
+ *
+ * Changes already committed on the branch (for example by a workflow with
+ * `autoCommit`) are included. Worktrees whose branch never moved and has no
+ * pending changes are removed together with their branch.
+ *
+ * @param worktree - Worktree returned by `createWorktree`
+ * @param message - Commit message
+ * @returns Branch, commit and diff
+ * @public
+ */
+export async function finalizeWorktree(worktree: Worktree, message: string): Promise<WorktreeOutcome> {
+  await commitAll(worktree.path, message);
+  const commit = await git(["rev-parse", "HEAD"], worktree.path);
+
+  if (commit === worktree.base) {
+    await removeWorktree(worktree.repoRoot, worktree.path, worktree.branch);
+    return { branch: worktree.branch, path: worktree.path, changed: false, diffStat: "", diff: "" };
+  }
+
+  const range = `${worktree.base}..${commit}`;
+
+  return {
+    branch: worktree.branch,
+    path: worktree.path,
+    changed: true,
+    commit,
+    diffStat: await git(["diff", "--stat", range], worktree.path),
+    diff: await git(["diff", range], worktree.path),
+  };
+}
+
+async function removeWorktree(repoRoot: string, worktreePath: string, branch: string): Promise<void> {
+  await git(["worktree", "remove", "--force", worktreePath], repoRoot);
+  await git(["branch", "-D", branch], repoRoot);
+  console.log(`[AgentMesh] Removed worktree ${worktreePath} and ${branch}`);
+}
+
+/**
+ * Lists the isolation worktrees of a repository
+ *
+ * @param dir - Any directory inside the repository
+ * @returns Worktrees on `agentmesh/` branches
+ * @public
+ */
+export async function listWorktrees(dir: string): Promise<WorktreeInfo[]> {
+  const repoRoot = await getRepoRoot(dir);
+  const porcelain = await git(["worktree", "list", "--porcelain"], repoRoot);
+
+  const worktrees: WorktreeInfo[] = [];
+  for (const block of porcelain.split("\n\n")) {
+    const fields: Record<string, string> = {};
+    for (const line of block.split("\n")) {
+      const space = line.indexOf(" ");
+      if (space === -1) fields[line] = "";
+      else fields[line.slice(0, space)] = line.slice(space + 1);
+    }
+    const branch = (fields.branch ?? "").replace(/^refs\/heads\//, "");
+    if (branch.startsWith(WORKTREE_BRANCH_PREFIX)) {
+      worktrees.push({ branch, path: fields.worktree, head: fields.HEAD });
+    }
+  }
+  return worktrees;
+}
+
+async function findWorktree(dir: string, branch: string): Promise<{ repoRoot: string; info: WorktreeInfo }> {
+  if (!branch.startsWith(WORKTREE_BRANCH_PREFIX)) {
+    throw new Error(`"${branch}" is not an AgentMesh isolation branch`);
+  }
+  const repoRoot = await getRepoRoot(dir);
+  const info = (await listWorktrees(repoRoot)).find((w) => w.branch === branch);
+  if (!info) throw new Error(`No isolation worktree for branch "${branch}"`);
+  return { repoRoot, info };
+}
+
+/**
+ * Merges an isolation branch into the current branch of the main checkout,
+ * then removes its worktree and branch
+ *
+ * @param dir - Any directory inside the repository
+ * @param branch - Isolation branch
+ * @returns Merge output
+ * @throws {Error} If the merge fails; the main checkout is left unchanged
+ * @public
+ */
+export async function mergeWorktree(dir: string, branch: string): Promise<string> {
+  const { repoRoot, info } = await findWorktree(dir, branch);
+  let output: string;
+  try {
+    output = await git([...(await identityArgs(repoRoot)), "merge", "--no-ff", "--no-edit", branch], repoRoot);
+  } catch (error) {
+    await git(["merge", "--abort"], repoRoot).catch(() => undefined);
+    throw new Error(`Merge of ${branch} failed: ${(error as Error).message}`);
+  }
+  await removeWorktree(repoRoot, info.path, branch);
+  return output;
+}
+
+/**
+ * Deletes an isolation worktree and its branch without merging
+ *
+ * @param dir - Any directory inside the repository
+ * @param branch - Isolation branch
+ * @public
+ */
+export async function discardWorktree(dir: string, branch: string): Promise<void> {
+  const { repoRoot, info } = await findWorktree(dir, branch);
+  await removeWorktree(repoRoot, info.path, branch);
+}
+
+/**
+ * Maps a path-like target onto a worktree
+ *
+ * Relative targets already resolve against `worktree.cwd`. Absolute targets
+ * inside the repository are rebased onto the worktree, so the agent is not
+ * pointed back at the main checkout.
+ *
+ * @param target - Target from the tool call
+ * @param worktree - Worktree of an isolated run; the target is returned as is without one
+ * @returns Target to put in the prompt
+ * @throws {WorkspaceError} If an absolute target is outside the repository of the worktree
+ * @public
+ */
+export function isolatedTarget(target: string, worktree?: Worktree): string {
+  if (!worktree || target.includes("\n")) return target;
+  const expanded = target.startsWith("~") ? path.join(process.env.HOME || "/", target.slice(1)) : target;
+  if (!path.isAbsolute(expanded)) return target;
+
+  const relative = path.relative(worktree.repoRoot, resolveWorkspacePath(expanded));
+  if (relative.startsWith("..") || path.isAbsolute(relative)) {
+    throw new WorkspaceError(`${target} is outside ${worktree.repoRoot}, so worktree isolation cannot cover it`);
+  }
+  return path.join(worktree.path, relative);
+}
+
+/**
+ * Runs a task in a fresh worktree when isolation is requested
+ *
+ * When the task throws, its partial changes are still committed on the
+ * isolation branch (or the worktree is removed when there are none) before
+ * the error is rethrown.
+ *
+ * @param isolation - Isolation mode from the tool call
+ * @param workingDirectory - Directory the tool was asked to work in
+ * @param label - Short name used in the branch
+ * @param run - Task to run; receives the directory to work in and, when isolated, the worktree
+ * @param existing - Worktree of an earlier run to continue in, used while its directory still exists
+ * @returns The task result and, when isolated, the worktree outcome
+ * @throws {WorkspaceError} If the working directory is outside the workspace roots
+ * @public
+ */
+export async function withIsolation<T>(
+  isolation: IsolationMode | undefined,
+  workingDirectory: string | undefined,
+  label: string,
+  run: (cwd: string | undefined, worktree?: Worktree) => Promise<T>,
+  existing?: Worktree
+): Promise<{ result: T; worktree?: WorktreeOutcome }> {
+  if (isolation !== "worktree") {
+    return { result: await run(workingDirectory) };
+  }
+
+  const worktree = existing && fs.existsSync(existing.path)
+    ? existing
+    : await createWorktree(resolveWorkspacePath(workingDirectory || process.cwd()), label);
+  let result: T;
+  try {
+    result = await run(worktree.cwd, worktree);
+  } catch (error) {
+    const outcome = await finalizeWorktree(worktree, `AgentMesh ${label} (failed)`).catch(() => undefined);
+    if (outcome?.changed) {
+      console.log(`[AgentMesh] Partial changes of the failed ${label} run are on ${outcome.branch}`);
+    }
+    throw error;
+  }
+  return { result, worktree: await finalizeWorktree(worktree, `AgentMesh ${label}`) };
+}
+
+/**
+ * Formats a worktree outcome for tool output
+ *
+ * @param outcome - Outcome returned by `withIsolation`
+ * @returns Markdown section
+ * @public
+ */
+export function formatWorktree(outcome: WorktreeOutcome): string {
+  if (!outcome.changed) {
+    return "\n\n### Isolated Worktree\n\nNo files changed; the worktree was removed.";
+  }
+  return `\n\n### Isolated Worktree
+
+Branch: \`${outcome.branch}\`
+Path: ${outcome.path}
+
+${outcome.diffStat}
+
+\`\`\`diff
+${truncateDiff(outcome.diff)}
+\`\`\`
+
+💡 Use \`manage_worktree\` to merge or discard \`${outcome.branch}\`.`;
+}
@@ -0,0 +1,18 @@
+import { z } from "zod";
+import { type ResourceMetadata, type InferSchema } from "xmcp";
+import { loadRun } from "../../../lib/runs";
+
+export const schema = {
+  runId: z.string().describe("Run ID printed by agent_workflow"),
+};
+
+export const metadata: ResourceMetadata = {
+  name: "workflow-run",
+  title: "Workflow Run",
+  description: "One agent_workflow run with each step's input, output, diff, timing and status",
+};
+
+export default function handler({ runId }: InferSchema<typeof schema>) {
+  const run = loadRun(runId);
+  return run ? JSON.stringify(run, null, 2) : `Unknown workflow run: ${runId}`;
+}
@@ -0,0 +1,25 @@
+import { type ResourceMetadata } from "xmcp";
+import { listRuns, runStatus } from "../../lib/runs";
+
+export const metadata: ResourceMetadata = {
+  name: "workflow-history",
+  title: "Workflow Run History",
+  description: "Recent agent_workflow runs, newest first. Read runs://{runId} for the steps of one run.",
+};
+
+export default function handler() {
+  const runs = listRuns().map((run) => ({
+    id: run.id,
+    workflow: run.workflow,
+    target: run.target,
+    status: runStatus(run),
+    error: run.error,
+    resumedFrom: run.resumedFrom,
+    branch: run.branch,
+    pullRequest: run.pullRequest,
+    createdAt: run.createdAt,
+    finishedAt: run.finishedAt,
+    steps: run.steps.map((step) => ({ id: step.id, name: step.name, status: step.status, durationMs: step.durationMs })),
+  }));
+  return JSON.stringify(runs, null, 2);
+}
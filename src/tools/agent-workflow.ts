@@ -1,21 +1,47 @@
 import { z } from "zod";
 import { type ToolMetadata, type InferSchema } from "xmcp";
-import { runClineTask } from "../lib/cline";
+import { runAgentTask } from "../lib/agent";
+import { withProgress, type ToolExtra } from "../lib/progress";
+import { checkWorkspaceTarget } from "../lib/workspace";
+import { reportStep } from "../lib/run-context";
+import { formatWorktree, isolatedTarget, withIsolation, WORKTREE_BRANCH_PREFIX } from "../lib/worktree";
+import { commitAll, currentBranch, findRepoRoot, git } from "../lib/git";
+import { getForge } from "../lib/forge";
+import { getWorkflow, loadWorkflows, stepId, WORKFLOW_NAME_PATTERN, type WorkflowDefinition, type WorkflowStep } from "../lib/workflows";
+import { runWorkflow, type StepAttempt, type StepReport } from "../lib/workflow-runner";
+import { renderTemplate, type StepOutputs, type TemplateContext } from "../lib/template";
+import { runGate } from "../lib/verify";
+import { requestApproval } from "../lib/approval";
+import { loadConfig } from "../lib/config";
+import { createRun, finishRun, recordStep, type WorkflowRunRecord } from "../lib/runs";
+import { createCheckpoint, dropCheckpoints, formatRollback, restoreCheckpoint, type Checkpoint } from "../lib/checkpoint";
+
+// Workflows found at startup build the schema enum and description; broken
+// files are skipped. Each call loads the requested file again, so edits take
+// effect immediately and workflows added later can be run by name.
+const WORKFLOWS = loadWorkflows();
+const WORKFLOW_NAMES = Object.keys(WORKFLOWS) as [string, ...string[]];
 
 export const schema = {
-  workflow: z.enum([
-    "full-feature",      // Plan → Code → Test → Review → Deploy
-    "bug-fix",           // Analyze → Fix → Test → PR
-    "security-audit",    // Scan → Fix → Verify → Report
-    "refactor-deploy",   // Refactor → Test → Review → Deploy
-  ]).describe("Pre-defined workflow to execute"),
+  workflow: z.union([
+    z.enum(WORKFLOW_NAMES),
+    z.string().regex(WORKFLOW_NAME_PATTERN, "Workflow names use letters, digits, \"-\" and \"_\""),
+  ]).describe("Workflow to execute (built-in or from .agentmesh/workflows; files added after startup are accepted by name)"),
   target: z.string().describe("Target file, directory, or feature description"),
   options: z.object({
-    autoCommit: z.boolean().optional().default(true),
-    createPR: z.boolean().optional().default(true),
+    autoCommit: z.boolean().optional().default(true)
+      .describe("Commit each step's changes on a dedicated agentmesh/ branch"),
+    createPR: z.boolean().optional().default(true)
+      .describe("Open a pull request for the branch through the configured forge (implies autoCommit)"),
     deploy: z.boolean().optional().default(false),
+    rollbackOnFailure: z.enum(["none", "lastGood", "original"]).optional().default("none")
+      .describe("When a step stops the workflow, restore the checkpoint taken before that step ('lastGood') or before the workflow ('original'). Needs git."),
   }).optional().describe("Workflow options"),
+  inputs: z.record(z.string()).optional().describe("Values for the workflow's declared inputs, referenced in prompts as {inputs.<name>}"),
   workingDirectory: z.string().optional().describe("Working directory"),
+  backend: z.string().optional().describe("Agent backend to run on (see cline_status). Defaults to the configured backend."),
+  isolation: z.enum(["none", "worktree"]).optional().default("none")
+    .describe("'worktree' runs every step in one temporary git worktree on a new branch and returns the diff instead of editing your checkout."),
 };
 
 export const metadata: ToolMetadata = {
@@ -23,12 +49,20 @@ export const metadata: ToolMetadata = {
   description: `Execute multi-step AI agent workflows that chain multiple tools together.
 
 Available workflows:
-- **full-feature**: Plan → Code → Test → Review → Deploy
-- **bug-fix**: Analyze → Fix → Test → Create PR
-- **security-audit**: Scan → Fix vulnerabilities → Verify → Generate report
-- **refactor-deploy**: Refactor code → Run tests → Review → Deploy
+${Object.values(WORKFLOWS).map((w) => `- **${w.name}**: ${w.description ?? w.steps.map((s) => s.name).join(" → ")}`).join("\n")}
+
+Each workflow orchestrates multiple agent tasks. Steps run in order unless they
+declare dependsOn, in which case independent steps can run in parallel; steps
+can also be conditional (when), retried (retries) or allowed to fail
+(continueOnError). Steps with run execute a shell command (e.g. pnpm test) as a
+gate and can hand failures back to the agent to fix. Steps with approval pause
+for a human to approve their output (MCP elicitation, or approve_job when run
+through start_job). Add your own as YAML files in .agentmesh/workflows.
 
-Each workflow orchestrates multiple Cline tasks in sequence.`,
+Every run is recorded under .agentmesh/runs (browse them through the
+workflow-history resource); resume_workflow continues a failed run from the
+step that failed. With options.rollbackOnFailure the workspace is checkpointed
+before each step and restored when a step stops the workflow.`,
   annotations: {
     title: "Agent Workflow Orchestration",
     readOnlyHint: false,
@@ -37,86 +71,352 @@ Each workflow orchestrates multiple Cline tasks in sequence.`,
   },
 };
 
-interface WorkflowStep {
-  name: string;
-  prompt: string;
-  mode?: "act" | "plan";
+interface StepCommit {
+  step: string;
+  sha: string;
 }
 
-const WORKFLOWS: Record<string, WorkflowStep[]> = {
-  "full-feature": [
-    { name: "Planning", prompt: "Create a detailed implementation plan for: {target}", mode: "plan" },
-    { name: "Implementation", prompt: "Implement the feature according to the plan: {target}" },
-    { name: "Testing", prompt: "Write comprehensive tests for the new feature: {target}" },
-    { name: "Review", prompt: "Review the implementation for bugs, security issues, and code quality" },
-  ],
-  "bug-fix": [
-    { name: "Analysis", prompt: "Analyze and identify the root cause of: {target}", mode: "plan" },
-    { name: "Fix", prompt: "Fix the bug: {target}. Ensure minimal changes and no regressions." },
-    { name: "Testing", prompt: "Add regression tests to prevent this bug from recurring" },
-  ],
-  "security-audit": [
-    { name: "Scanning", prompt: "Perform a security audit on {target}. Look for vulnerabilities, exposed secrets, and security anti-patterns.", mode: "plan" },
-    { name: "Fixing", prompt: "Fix all identified security vulnerabilities in {target}" },
-    { name: "Verification", prompt: "Verify all security fixes are properly implemented and no new issues introduced" },
-  ],
-  "refactor-deploy": [
-    { name: "Refactoring", prompt: "Refactor {target} to improve code quality, readability, and maintainability" },
-    { name: "Testing", prompt: "Run all tests and fix any failures caused by refactoring" },
-    { name: "Review", prompt: "Final review of refactored code for quality and correctness" },
-  ],
-};
+// Keeps generated commit subjects and PR titles to a conventional length
+function subjectLine(text: string): string {
+  const line = text.replace(/\s+/g, " ").trim();
+  return line.length > 72 ? `${line.substring(0, 69)}...` : line;
+}
+
+function stepCommitMessage(workflow: string, report: StepReport, target: string): string {
+  const name = report.status === "failed" ? `${report.step.name} (failed)` : report.step.name;
+  const summary = report.output.trim().substring(0, 1000);
+  return `${subjectLine(`${workflow}: ${name} - ${target}`)}\n\n${summary}\n\nCommitted by AgentMesh agent_workflow`;
+}
 
-export default async function agentWorkflow({ 
-  workflow, 
-  target, 
+function pullRequestBody(workflow: string, target: string, commits: StepCommit[]): string {
+  return `Automated "${workflow}" workflow for: ${target}
+
+| Step | Commit |
+|------|--------|
+${commits.map((c) => `| ${c.step} | ${c.sha.substring(0, 12)} |`).join("\n")}
+
+Opened by AgentMesh agent_workflow.`;
+}
+
+function formatStep(report: StepReport, total: number): string {
+  const { step, output } = report;
+  const heading = `\n### Step ${report.index}/${total}: ${step.name}\n`;
+  if (report.reused) {
+    return `${heading}♻️ ${step.name} reused from the previous run\n`;
+  }
+  if (report.status === "skipped") {
+    return `${heading}⏭️ ${step.name} skipped: ${report.reason}\n`;
+  }
+  if (report.review?.approved === false) {
+    return `${heading}🛑 ${step.name} stopped: ${report.error}\n`;
+  }
+  if (report.status === "failed") {
+    const tolerated = step.continueOnError ? "↪️ Continuing (continueOnError)\n" : "";
+    return `${heading}❌ ${step.name} failed: ${report.error}\n${tolerated}`;
+  }
+  // Revisions after a rejection are counted separately from retries
+  const revisions = report.review?.revisions ?? 0;
+  const tries = report.attempts - revisions;
+  const retried = tries > 1 ? ` after ${tries} attempts` : "";
+  const revised = revisions ? ` after ${revisions} revision(s)` : "";
+  const approved = report.review ? `\n👍 Approved${revised}` : "";
+  return `${heading}✅ ${step.name} completed${retried}${approved}\n` +
+    output.substring(0, 500) + (output.length > 500 ? '...' : '') + "\n";
+}
+
+/**
+ * Applies declared defaults and rejects missing or unknown inputs
+ */
+function resolveInputs(definition: WorkflowDefinition, given: Record<string, string> = {}): Record<string, string> | string {
+  const declared = definition.inputs ?? {};
+  const unknown = Object.keys(given).filter((name) => !(name in declared));
+  if (unknown.length) {
+    return `❌ Unknown input(s) for "${definition.name}": ${unknown.join(", ")}`;
+  }
+  const inputs: Record<string, string> = {};
+  const missing: string[] = [];
+  for (const [name, input] of Object.entries(declared)) {
+    const value = given[name] ?? input.default;
+    if (value === undefined) missing.push(name);
+    else inputs[name] = value;
+  }
+  return missing.length ? `❌ Missing input(s) for "${definition.name}": ${missing.join(", ")}` : inputs;
+}
+
+/**
+ * Runs a workflow and records it in the run history
+ *
+ * @param args - Tool arguments
+ * @param extra - Request extra of the tool call
+ * @param resume - Earlier run to continue; its succeeded steps are not run again
+ * @returns Markdown report
+ */
+export async function runAgentWorkflow({
+  workflow,
+  target,
   options,
-  workingDirectory 
-}: InferSchema<typeof schema>) {
-  const steps = WORKFLOWS[workflow];
-  if (!steps) {
+  inputs,
+  workingDirectory,
+  backend,
+  isolation,
+}: InferSchema<typeof schema>, extra?: ToolExtra, resume?: WorkflowRunRecord): Promise<string> {
+  checkWorkspaceTarget(target, workingDirectory);
+
+  let definition: WorkflowDefinition | undefined;
+  try {
+    definition = getWorkflow(workflow);
+  } catch (error) {
+    return `❌ Could not load workflow "${workflow}"\n\nError: ${(error as Error).message}`;
+  }
+  if (!definition) {
     return `❌ Unknown workflow: ${workflow}`;
   }
+  const steps = definition.steps;
+  const total = steps.length;
+  const resolvedInputs = resolveInputs(definition, inputs);
+  if (typeof resolvedInputs === "string") {
+    return resolvedInputs;
+  }
+  const context: TemplateContext = {
+    target,
+    inputs: resolvedInputs,
+    steps: {},
+    envAllowlist: loadConfig().templateEnv,
+  };
+  const reuse: Record<string, StepOutputs> = {};
+  const commits: StepCommit[] = [];
+  // Steps rolled back to the original state have to run again
+  const reusable = resume?.rolledBack === "original" ? [] : resume?.steps ?? [];
+  for (const step of reusable) {
+    if (step.status !== "succeeded") continue;
+    reuse[step.id] = { status: step.status, output: step.output, changedFiles: step.changedFiles };
+    if (step.commit) commits.push({ step: step.name, sha: step.commit });
+  }
+
+  const record = createRun({
+    workflow,
+    target,
+    inputs: resolvedInputs,
+    options: options ?? {},
+    workingDirectory,
+    backend,
+    isolation: isolation ?? "none",
+    resumedFrom: resume?.id,
+  });
 
   const results: string[] = [];
+  results.push(`🗂️ Run: \`${record.id}\`${resume ? ` (resuming \`${resume.id}\`)` : ""}\n`);
   results.push(`🔄 Starting "${workflow}" workflow for: ${target}\n`);
   results.push(`📋 Steps: ${steps.map(s => s.name).join(" → ")}\n`);
   results.push("─".repeat(50) + "\n");
+  // Parallel steps finish in any order; sections are printed in file order
+  const sections: string[] = [];
+
+  const commitSteps = Boolean(options?.autoCommit || options?.createPR);
+  const rollback = options?.rollbackOnFailure ?? "none";
+  // Commits and checkpoints cover the whole working tree, so they can only be
+  // attributed to a step when no other step runs next to it
+  const oneByOne = commitSteps || rollback !== "none";
+  if (oneByOne && (definition.concurrency ?? 1) > 1) {
+    results.push(`ℹ️ Steps run one at a time because ${commitSteps ? "autoCommit" : "rollbackOnFailure"} is set\n`);
+  }
+
+  const { result: run, worktree } = await withIsolation(isolation, workingDirectory, `workflow-${workflow}`, (cwd, isolated) => withProgress(extra, async () => {
+    const dir = cwd || process.cwd();
+    let branch: string | undefined;
+    let baseBranch: string | undefined;
+    // Set when the main checkout was moved onto the workflow branch
+    let switched = false;
+    record.worktree = isolated;
+    // Prompts point at the worktree copy of an absolute target
+    context.target = isolatedTarget(target, isolated);
+
+    if (commitSteps && isolated) {
+      branch = isolated.branch;
+      baseBranch = isolated.baseBranch;
+    } else if (commitSteps) {
+      if (!(await findRepoRoot(dir))) {
+        throw new Error(`autoCommit needs a git repository, but ${dir} is not inside one`);
+      }
+      // Fixes made on the branch of a resumed run are committed with the next step
+      const onBranch = resume?.branch !== undefined && (await currentBranch(dir)) === resume.branch;
+      if (!onBranch && await git(["status", "--porcelain"], dir)) {
+        throw new Error(`autoCommit needs a clean working tree in ${dir}. Commit or stash your changes, or use isolation: "worktree".`);
+      }
+      if (resume?.branch) {
+        // Carry on committing where the earlier run left off
+        branch = resume.branch;
+        baseBranch = resume.baseBranch;
+        if (!onBranch) await git(["checkout", "-q", branch], dir);
+        switched = !onBranch;
+      } else {
+        baseBranch = await currentBranch(dir);
+        branch = `${WORKTREE_BRANCH_PREFIX}workflow-${workflow}-${Date.now().toString(36)}`;
+        await git(["checkout", "-q", "-b", branch], dir);
+        switched = true;
+      }
+    }
+    record.branch = branch;
+    record.baseBranch = baseBranch;
+
+    let original: Checkpoint | undefined;
+    // Checkpoints taken before each step, by step id
+    const checkpoints = new Map<string, Checkpoint>();
+    if (rollback !== "none") {
+      if (!(await findRepoRoot(dir))) {
+        throw new Error(`rollbackOnFailure needs a git repository, but ${dir} is not inside one`);
+      }
+      original = await createCheckpoint(dir, `${record.id}/start`, "the workflow");
+    }
 
-  for (let i = 0; i < steps.length; i++) {
-    const step = steps[i];
-    const stepNum = i + 1;
-    const prompt = step.prompt.replace("{target}", target);
-    
-    results.push(`\n### Step ${stepNum}/${steps.length}: ${step.name}\n`);
-    
-    const result = await runClineTask(prompt, {
-      cwd: workingDirectory,
-      mode: step.mode,
+    const runAttempt = async (step: WorkflowStep, prompt: string | undefined): Promise<StepAttempt> => {
+      if (prompt === undefined) {
+        return runGate(step.run ?? "", {
+          cwd,
+          timeout: step.timeout ? step.timeout * 1000 : undefined,
+          fixAttempts: step.fix ? step.fix.attempts ?? 1 : 0,
+          fixPrompt: step.fix?.prompt === undefined ? undefined : renderTemplate(step.fix.prompt, context),
+          backend,
+          // Changed files and diffs are kept in the run history
+          trackChanges: true,
+        });
+      }
+      const result = await runAgentTask(prompt, {
+        cwd,
+        mode: step.mode,
+        timeout: step.timeout ? step.timeout * 1000 : undefined,
+        trackChanges: true,
+        backend,
+      });
+      const changes = result.changes;
+      return {
+        success: result.success,
+        output: result.output,
+        error: result.error,
+        changedFiles: changes ? [...changes.added, ...changes.modified, ...changes.deleted].sort() : [],
+        diff: changes?.diff,
+      };
+    };
+    // Change tracking covers the whole working tree, so the changes of an
+    // attempt that overlapped another step would include that step's edits
+    const running = new Set<{ overlapped: boolean }>();
+    const untracked: string[] = [];
+
+    const { completed, stoppedAt } = await runWorkflow(definition, {
+      context,
+      concurrency: oneByOne ? 1 : undefined,
+      reuse,
+      runStep: async (step, prompt, attempt) => {
+        if (original && attempt === 1) {
+          const index = steps.indexOf(step) + 1;
+          checkpoints.set(stepId(step), await createCheckpoint(dir, `${record.id}/${index}`, `step ${index} (${step.name})`));
+        }
+        const slot = { overlapped: running.size > 0 };
+        running.forEach((other) => { other.overlapped = true; });
+        running.add(slot);
+        let outcome: StepAttempt;
+        try {
+          outcome = await runAttempt(step, prompt);
+        } finally {
+          running.delete(slot);
+        }
+        if (!slot.overlapped) return outcome;
+        if (untracked.indexOf(step.name) === -1) untracked.push(step.name);
+        return { ...outcome, changedFiles: [], diff: undefined };
+      },
+      approve: (step, output) => requestApproval({ workflow, step: step.name, summary: output }, extra),
+      onStepStart: (step, index) => reportStep({ index, total, name: step.name }),
+      onStepEnd: async (report) => {
+        sections[report.index - 1] = formatStep(report, total);
+        let sha: string | undefined;
+        if (report.reused) {
+          sha = resume?.steps.find((s) => s.id === stepId(report.step))?.commit;
+        } else if (branch && report.status !== "skipped") {
+          // Partial changes of a failed step are kept on the branch too
+          sha = await commitAll(dir, stepCommitMessage(workflow, report, target));
+          if (sha) commits.push({ step: report.step.name, sha });
+        }
+        recordStep(record, report, sha);
+      },
     });
 
-    if (result.success) {
-      results.push(`✅ ${step.name} completed\n`);
-      results.push(result.output.substring(0, 500) + (result.output.length > 500 ? '...' : '') + "\n");
-    } else {
-      results.push(`❌ ${step.name} failed: ${result.error}\n`);
-      results.push(`\n⚠️ Workflow stopped at step ${stepNum}. Fix the issue and retry.`);
-      break;
+    results.push(sections.filter(Boolean).join(""));
+    if (untracked.length) {
+      results.push(`\nℹ️ Changed files of ${untracked.join(", ")} are not tracked because they ran next to other steps in the same working tree.\n`);
     }
-  }
+    if (stoppedAt) {
+      results.push(`\n⚠️ Workflow stopped at step ${stoppedAt.index}. Fix the issue and resume with \`resume_workflow\` (runId: "${record.id}").`);
+      // A step that never ran changed nothing, so there is nothing to roll back
+      const checkpoint = rollback === "original" ? original : checkpoints.get(stepId(stoppedAt.step));
+      if (checkpoint && rollback !== "none") {
+        const rolledBack = await restoreCheckpoint(checkpoint, `${record.id}/discarded`);
+        results.push("\n" + formatRollback(rolledBack));
+        const undone = rolledBack.commits.map((c) => c.split(" ")[0]);
+        commits.splice(0, commits.length, ...commits.filter((c) => !undone.some((sha) => c.sha.startsWith(sha))));
+        record.rolledBack = rollback;
+      }
+      finishRun(record, "failed", stoppedAt.error);
+    }
+    await dropCheckpoints([...(original ? [original] : []), ...checkpoints.values()]);
+    return { completed, stoppedAt, branch, baseBranch, switched };
+  }), resume?.worktree).catch((error: Error) => {
+    finishRun(record, "failed", error.message);
+    throw error;
+  });
 
-  // Optional: Auto-commit and create PR
-  if (options?.autoCommit) {
+  if (run.branch) {
     results.push("\n### Git Operations\n");
-    results.push("💡 Auto-commit enabled. Changes will be committed.\n");
+    results.push(`🌿 Branch: \`${run.branch}\`\n`);
+    if (run.switched) {
+      results.push(`↪️ Your checkout was switched to \`${run.branch}\`; run \`git checkout ${run.baseBranch}\` to return to \`${run.baseBranch}\`.\n`);
+    }
+    results.push(commits.length
+      ? commits.map((c) => `- ${c.sha.substring(0, 12)} ${c.step}`).join("\n") + "\n"
+      : "No changes to commit.\n");
+
+    if (options?.createPR) {
+      if (!run.completed) {
+        results.push("⚠️ Pull request skipped: the workflow did not complete.\n");
+      } else if (!commits.length) {
+        results.push("⚠️ Pull request skipped: there is nothing to review.\n");
+      } else {
+        const forge = getForge();
+        try {
+          const pr = await forge.createPullRequest({
+            dir: workingDirectory || process.cwd(),
+            head: run.branch,
+            base: run.baseBranch ?? "main",
+            title: subjectLine(`${workflow}: ${target}`),
+            body: pullRequestBody(workflow, target, commits),
+          });
+          results.push(`🔀 Pull request #${pr.number} opened on ${forge.name}: ${pr.url}\n`);
+          record.pullRequest = pr.url;
+        } catch (error) {
+          results.push(`❌ Pull request failed: ${(error as Error).message}\n`);
+        }
+      }
+    }
   }
 
-  if (options?.createPR) {
-    results.push("💡 PR creation enabled. A PR will be created for review.\n");
+  if (worktree) {
+    results.push(formatWorktree(worktree) + "\n");
+  }
+  if (run.completed) {
+    finishRun(record, "completed");
   }
 
   results.push("\n" + "─".repeat(50));
-  results.push(`\n🎉 Workflow "${workflow}" completed!`);
+  if (run.completed) {
+    results.push(`\n🎉 Workflow "${workflow}" completed!`);
+  } else if (record.rolledBack) {
+    results.push(`\n↩️ Workflow "${workflow}" stopped at step ${run.stoppedAt?.index} and was rolled back.`);
+  } else {
+    results.push(`\n❌ Workflow "${workflow}" stopped at step ${run.stoppedAt?.index}.`);
+  }
 
   return results.join("");
 }
+
+export default async function agentWorkflow(args: InferSchema<typeof schema>, extra?: ToolExtra) {
+  return runAgentWorkflow(args, extra);
+}
@@ -0,0 +1,43 @@
+import { z } from "zod";
+import { type ToolMetadata, type InferSchema } from "xmcp";
+import { decideApproval, formatJobLine, getJob } from "../lib/jobs";
+
+export const schema = {
+  jobId: z.string().describe("Job ID of a workflow awaiting approval"),
+  decision: z.enum(["approve", "reject"]).describe("Approve to continue the workflow, reject to revise or stop it"),
+  feedback: z.string().optional()
+    .describe("What to change. Rejecting with feedback re-runs the step with it; rejecting without feedback stops the workflow."),
+};
+
+export const metadata: ToolMetadata = {
+  name: "approve_job",
+  description: `Answer the approval a background workflow is waiting for (job state
+"awaiting_approval"; job_status shows the step output to review).`,
+  annotations: {
+    title: "Approve Workflow Step",
+    readOnlyHint: false,
+    destructiveHint: false,
+    idempotentHint: false,
+  },
+};
+
+export default async function approveJob({ jobId, decision, feedback }: InferSchema<typeof schema>) {
+  const job = getJob(jobId);
+  if (!job) {
+    return `❌ Unknown job: ${jobId}`;
+  }
+  const pending = job.approval;
+  if (job.state !== "awaiting_approval" || !pending) {
+    return `ℹ️ Job is not awaiting approval\n\n${formatJobLine(job)}`;
+  }
+
+  const comments = feedback?.trim() || undefined;
+  decideApproval(jobId, { approved: decision === "approve", feedback: comments });
+
+  if (decision === "approve") {
+    return `✅ Approved "${pending.step}"; the workflow continues\n\n${formatJobLine(job)}`;
+  }
+  return comments
+    ? `↩️ Rejected "${pending.step}"; it will run again with your feedback\n\n${formatJobLine(job)}`
+    : `🛑 Rejected "${pending.step}"; the workflow will stop\n\n${formatJobLine(job)}`;
+}
@@ -0,0 +1,30 @@
+import { z } from "zod";
+import { type ToolMetadata, type InferSchema } from "xmcp";
+import { cancelJob, formatJobLine } from "../lib/jobs";
+
+export const schema = {
+  jobId: z.string().describe("Job ID returned by start_job"),
+};
+
+export const metadata: ToolMetadata = {
+  name: "cancel_job",
+  description: `Cancel a running background job. The agent process is killed;
+changes it already made to files are left in place.`,
+  annotations: {
+    title: "Cancel Background Job",
+    readOnlyHint: false,
+    destructiveHint: false,
+    idempotentHint: true,
+  },
+};
+
+export default async function cancelJobTool({ jobId }: InferSchema<typeof schema>) {
+  const job = cancelJob(jobId);
+  if (!job) {
+    return `❌ Unknown job: ${jobId}`;
+  }
+  if (job.state !== "cancelled") {
+    return `ℹ️ Job already finished\n\n${formatJobLine(job)}`;
+  }
+  return `🛑 Job cancelled\n\n${formatJobLine(job)}`;
+}
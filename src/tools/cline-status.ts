@@ -1,28 +1,49 @@
 import { z } from "zod";
 import { type ToolMetadata, type InferSchema } from "xmcp";
-import { isClineInstalled, getClineVersion } from "../lib/cline";
+import { listBackends } from "../lib/agent";
+import { loadConfig } from "../lib/config";
 
-export const schema = {};
+export const schema = {
+  backend: z.string().optional().describe("Only report this backend. Defaults to every configured backend."),
+};
 
 export const metadata: ToolMetadata = {
   name: "cline_status",
-  description: `Check if Cline CLI is installed and get version information. 
-Use this to verify AgentMesh can execute coding tasks.`,
+  description: `Check which coding agent backends (Cline, Aider, Codex CLI, custom commands)
+are installed and get version information. Use this to verify AgentMesh can execute coding tasks
+and to find backend names for the \`backend\` argument of other tools.`,
   annotations: {
-    title: "Check Cline Status",
+    title: "Check Agent Backend Status",
     readOnlyHint: true,
     destructiveHint: false,
     idempotentHint: true,
   },
 };
 
-export default async function clineStatus(_args: InferSchema<typeof schema>) {
-  const installed = await isClineInstalled();
-  const version = installed ? await getClineVersion() : null;
+const INSTALL_HINTS: Record<string, string> = {
+  cline: "npm install -g cline && cline auth",
+  aider: "python -m pip install aider-install && aider-install",
+  codex: "npm install -g @openai/codex",
+  command: "check the \"path\" of this backend in .agentmesh/config.json",
+};
+
+export default async function clineStatus({ backend }: InferSchema<typeof schema>) {
+  const { defaultBackend } = loadConfig();
+  const backends = listBackends().filter((b) => !backend || b.name === backend);
 
-  if (installed) {
-    return `✅ Cline CLI v${version} is installed and ready!\n\nAgentMesh can now execute coding tasks.`;
-  } else {
-    return `❌ Cline CLI is not installed.\n\nTo install, run:\n  npm install -g cline\n\nThen authenticate:\n  cline auth`;
+  if (backends.length === 0) {
+    return `❌ Unknown backend: ${backend}`;
   }
+
+  const lines = await Promise.all(backends.map(async (b) => {
+    const installed = await b.isInstalled();
+    const label = `**${b.name}** (${b.type}${b.name === defaultBackend ? ", default" : ""})`;
+    if (!installed) {
+      return `❌ ${label} is not installed.\n   To install: ${INSTALL_HINTS[b.type]}`;
+    }
+    const version = await b.getVersion();
+    return `✅ ${label} v${version} is installed and ready!`;
+  }));
+
+  return `🤖 Agent Backends\n\n${lines.join("\n")}\n\nDefault backend: ${defaultBackend}`;
 }
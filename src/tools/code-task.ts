@@ -1,11 +1,17 @@
 import { z } from "zod";
 import { type ToolMetadata, type InferSchema } from "xmcp";
-import { runClineTask } from "../lib/cline";
+import { runAgentTask } from "../lib/agent";
+import { withProgress, type ToolExtra } from "../lib/progress";
+import { formatWorktree, withIsolation } from "../lib/worktree";
+import { formatChanges } from "../lib/changes";
 
 export const schema = {
   prompt: z.string().describe("The coding task to perform. Be specific about what you want Cline to do."),
   workingDirectory: z.string().optional().describe("The directory to work in. Defaults to current directory."),
   mode: z.enum(["act", "plan"]).optional().describe("Mode: 'act' executes immediately, 'plan' creates a plan first."),
+  backend: z.string().optional().describe("Agent backend to run on (see cline_status). Defaults to the configured backend."),
+  isolation: z.enum(["none", "worktree"]).optional().default("none")
+    .describe("'worktree' runs the agent in a temporary git worktree on a new branch and returns the diff instead of editing your checkout."),
 };
 
 export const metadata: ToolMetadata = {
@@ -25,15 +31,21 @@ execute commands, and more. Use this for any software development task like:
   },
 };
 
-export default async function codeTask({ prompt, workingDirectory, mode }: InferSchema<typeof schema>) {
-  const result = await runClineTask(prompt, {
-    cwd: workingDirectory,
-    mode: mode as "act" | "plan" | undefined,
-  });
+export default async function codeTask({ prompt, workingDirectory, mode, backend, isolation }: InferSchema<typeof schema>, extra?: ToolExtra) {
+  const { result, worktree } = await withIsolation(isolation, workingDirectory, "code-task", (cwd) =>
+    withProgress(extra, () => runAgentTask(prompt, {
+      cwd,
+      mode: mode as "act" | "plan" | undefined,
+      backend,
+      trackChanges: true,
+    }))
+  );
+  // The worktree diff already covers every change of an isolated run
+  const changes = worktree ? formatWorktree(worktree) : formatChanges(result.changes);
 
   if (result.success) {
-    return `✅ Task completed successfully!\n\n${result.output}`;
+    return `✅ Task completed successfully!\n\n${result.output}${changes}`;
   } else {
-    return `❌ Task failed\n\nError: ${result.error}\n\nPartial output:\n${result.output}`;
+    return `❌ Task failed\n\nError: ${result.error}\n\nPartial output:\n${result.output}${changes}`;
   }
 }
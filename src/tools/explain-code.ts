@@ -1,12 +1,15 @@
 import { z } from "zod";
 import { type ToolMetadata, type InferSchema } from "xmcp";
-import { runClineTask } from "../lib/cline";
+import { runAgentTask } from "../lib/agent";
+import { withProgress, type ToolExtra } from "../lib/progress";
+import { checkWorkspaceTarget } from "../lib/workspace";
 
 export const schema = {
   target: z.string().describe("File or code snippet to explain."),
   detail: z.enum(["brief", "detailed", "comprehensive"]).optional().default("detailed")
     .describe("Level of detail in the explanation."),
   workingDirectory: z.string().optional().describe("The directory to work in."),
+  backend: z.string().optional().describe("Agent backend to run on (see cline_status). Defaults to the configured backend."),
 };
 
 export const metadata: ToolMetadata = {
@@ -21,7 +24,9 @@ complex code, documenting functionality, or onboarding.`,
   },
 };
 
-export default async function explainCode({ target, detail, workingDirectory }: InferSchema<typeof schema>) {
+export default async function explainCode({ target, detail, workingDirectory, backend }: InferSchema<typeof schema>, extra?: ToolExtra) {
+  checkWorkspaceTarget(target, workingDirectory);
+
   const detailInstructions: Record<string, string> = {
     brief: "Provide a brief, high-level summary.",
     detailed: "Provide a detailed explanation with key concepts.",
@@ -39,7 +44,7 @@ Include:
 - Important algorithms or patterns used
 - Dependencies and integrations`;
 
-  const result = await runClineTask(prompt, { cwd: workingDirectory });
+  const result = await withProgress(extra, () => runAgentTask(prompt, { cwd: workingDirectory, backend }));
 
   if (result.success) {
     return `📖 Code Explanation (${detail})\n\n${result.output}`;
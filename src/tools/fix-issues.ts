@@ -1,6 +1,10 @@
 import { z } from "zod";
 import { type ToolMetadata, type InferSchema } from "xmcp";
-import { runClineTask } from "../lib/cline";
+import { runAgentTask } from "../lib/agent";
+import { withProgress, type ToolExtra } from "../lib/progress";
+import { formatWorktree, isolatedTarget, withIsolation } from "../lib/worktree";
+import { formatChanges } from "../lib/changes";
+import { checkWorkspaceTarget } from "../lib/workspace";
 
 export const schema = {
   target: z.string().optional().default(".").describe("File or directory to fix. Use '.' for current directory."),
@@ -8,6 +12,9 @@ export const schema = {
     .describe("Types of issues to fix: 'lint', 'types', 'security', 'deprecated'"),
   dryRun: z.boolean().optional().default(false).describe("If true, only report what would be fixed without making changes."),
   workingDirectory: z.string().optional().describe("The directory to work in."),
+  backend: z.string().optional().describe("Agent backend to run on (see cline_status). Defaults to the configured backend."),
+  isolation: z.enum(["none", "worktree"]).optional().default("none")
+    .describe("'worktree' runs the agent in a temporary git worktree on a new branch and returns the diff instead of editing your checkout."),
 };
 
 export const metadata: ToolMetadata = {
@@ -22,7 +29,9 @@ security vulnerabilities, or deprecated code.`,
   },
 };
 
-export default async function fixIssues({ target, issueTypes, dryRun, workingDirectory }: InferSchema<typeof schema>) {
+export default async function fixIssues({ target, issueTypes, dryRun, workingDirectory, backend, isolation }: InferSchema<typeof schema>, extra?: ToolExtra) {
+  checkWorkspaceTarget(target, workingDirectory);
+
   const types = issueTypes || ["lint", "types"];
   
   const issueDescriptions = types.map((type) => {
@@ -35,7 +44,7 @@ export default async function fixIssues({ target, issueTypes, dryRun, workingDir
     }
   });
 
-  const prompt = `${dryRun ? "Analyze" : "Fix"} the following issues in ${target || "."}:
+  const buildPrompt = (files: string) => `${dryRun ? "Analyze" : "Fix"} the following issues in ${files}:
 ${issueDescriptions.map((d) => `- ${d}`).join("\n")}
 
 ${dryRun ? "DO NOT make changes. Only report what would be fixed." : "Fix the issues directly in the files."}
@@ -45,14 +54,20 @@ For each issue:
 2. Explain the fix
 3. ${dryRun ? "Show what the fix would be" : "Apply the fix"}`;
 
-  const result = await runClineTask(prompt, { 
-    cwd: workingDirectory,
-    yolo: !dryRun,
-  });
+  const { result, worktree } = await withIsolation(isolation, workingDirectory, "fix-issues", (cwd, isolated) =>
+    withProgress(extra, () => runAgentTask(buildPrompt(target ? isolatedTarget(target, isolated) : "."), { 
+      cwd,
+      yolo: !dryRun,
+      backend,
+      trackChanges: true,
+    }))
+  );
+  // The worktree diff already covers every change of an isolated run
+  const changes = worktree ? formatWorktree(worktree) : formatChanges(result.changes);
 
   if (result.success) {
-    return `🔧 ${dryRun ? "Analysis" : "Fix"} Complete\n\n${result.output}`;
+    return `🔧 ${dryRun ? "Analysis" : "Fix"} Complete\n\n${result.output}${changes}`;
   } else {
-    return `❌ ${dryRun ? "Analysis" : "Fix"} failed\n\nError: ${result.error}`;
+    return `❌ ${dryRun ? "Analysis" : "Fix"} failed\n\nError: ${result.error}${changes}`;
   }
 }
@@ -1,12 +1,15 @@
 import { z } from "zod";
 import { type ToolMetadata, type InferSchema } from "xmcp";
-import { runClineTask } from "../lib/cline";
+import { runAgentTask } from "../lib/agent";
+import { withProgress, type ToolExtra } from "../lib/progress";
+import { checkWorkspaceTarget } from "../lib/workspace";
 
 export const schema = {
   target: z.string().describe("File or directory to document."),
   docType: z.enum(["inline", "readme", "api", "all"]).optional().default("inline")
     .describe("Type of documentation to generate."),
   workingDirectory: z.string().optional().describe("The directory to work in."),
+  backend: z.string().optional().describe("Agent backend to run on (see cline_status). Defaults to the configured backend."),
 };
 
 export const metadata: ToolMetadata = {
@@ -21,7 +24,9 @@ README files, and API documentation.`,
   },
 };
 
-export default async function generateDocs({ target, docType, workingDirectory }: InferSchema<typeof schema>) {
+export default async function generateDocs({ target, docType, workingDirectory, backend }: InferSchema<typeof schema>, extra?: ToolExtra) {
+  checkWorkspaceTarget(target, workingDirectory);
+
   const docInstructions: Record<string, string> = {
     inline: "Add JSDoc/docstrings to all functions, classes, and methods.",
     readme: "Create or update a comprehensive README.md file.",
@@ -39,7 +44,7 @@ Requirements:
 - Add usage examples where helpful
 - Follow documentation best practices for the language`;
 
-  const result = await runClineTask(prompt, { cwd: workingDirectory });
+  const result = await withProgress(extra, () => runAgentTask(prompt, { cwd: workingDirectory, backend }));
 
   if (result.success) {
     return `📚 Documentation Generated (${docType})\n\n${result.output}`;
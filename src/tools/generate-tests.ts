@@ -1,6 +1,8 @@
 import { z } from "zod";
 import { type ToolMetadata, type InferSchema } from "xmcp";
-import { runClineTask } from "../lib/cline";
+import { runAgentTask } from "../lib/agent";
+import { withProgress, type ToolExtra } from "../lib/progress";
+import { checkWorkspaceTarget } from "../lib/workspace";
 
 export const schema = {
   target: z.string().describe("File or directory to generate tests for."),
@@ -10,6 +12,7 @@ export const schema = {
     .describe("Type of tests to generate."),
   coverage: z.number().optional().default(80).describe("Target code coverage percentage."),
   workingDirectory: z.string().optional().describe("The directory to work in."),
+  backend: z.string().optional().describe("Agent backend to run on (see cline_status). Defaults to the configured backend."),
 };
 
 export const metadata: ToolMetadata = {
@@ -24,7 +27,9 @@ and can create unit, integration, or e2e tests.`,
   },
 };
 
-export default async function generateTests({ target, framework, testType, coverage, workingDirectory }: InferSchema<typeof schema>) {
+export default async function generateTests({ target, framework, testType, coverage, workingDirectory, backend }: InferSchema<typeof schema>, extra?: ToolExtra) {
+  checkWorkspaceTarget(target, workingDirectory);
+
   const prompt = `Generate comprehensive ${testType || "unit"} tests for ${target}.
 
 Requirements:
@@ -45,7 +50,7 @@ For each file:
    - Boundary conditions
 4. Create the test file(s) with proper imports and setup`;
 
-  const result = await runClineTask(prompt, { cwd: workingDirectory });
+  const result = await withProgress(extra, () => runAgentTask(prompt, { cwd: workingDirectory, backend }));
 
   if (result.success) {
     return `🧪 Tests Generated (${framework}, ${testType})\n\n${result.output}`;
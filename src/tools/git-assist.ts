@@ -1,12 +1,15 @@
 import { z } from "zod";
 import { type ToolMetadata, type InferSchema } from "xmcp";
-import { runClineTask } from "../lib/cline";
+import { runAgentTask } from "../lib/agent";
+import { withProgress, type ToolExtra } from "../lib/progress";
+import { checkWorkspaceTarget } from "../lib/workspace";
 
 export const schema = {
   operation: z.enum(["analyze-commits", "suggest-commit-message", "review-diff", "explain-history"])
     .describe("The git operation to perform."),
   target: z.string().optional().describe("Git ref, file, or range to operate on."),
   workingDirectory: z.string().optional().describe("The git repository directory."),
+  backend: z.string().optional().describe("Agent backend to run on (see cline_status). Defaults to the configured backend."),
 };
 
 export const metadata: ToolMetadata = {
@@ -21,7 +24,9 @@ create meaningful commit messages, or help with git workflows.`,
   },
 };
 
-export default async function gitAssist({ operation, target, workingDirectory }: InferSchema<typeof schema>) {
+export default async function gitAssist({ operation, target, workingDirectory, backend }: InferSchema<typeof schema>, extra?: ToolExtra) {
+  checkWorkspaceTarget(target, workingDirectory);
+
   const operationPrompts: Record<string, string> = {
     "analyze-commits": `Analyze the git commits ${target || "HEAD"}. Provide insights on:
 - What changes were made
@@ -41,7 +46,7 @@ conventional commit message following best practices.`,
 
   const prompt = operationPrompts[operation] || `Perform git operation: ${operation} on ${target}`;
 
-  const result = await runClineTask(prompt, { cwd: workingDirectory });
+  const result = await withProgress(extra, () => runAgentTask(prompt, { cwd: workingDirectory, backend }));
 
   if (result.success) {
     return `🔀 Git ${operation} Complete\n\n${result.output}`;
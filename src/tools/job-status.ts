@@ -0,0 +1,64 @@
+import { z } from "zod";
+import { type ToolMetadata, type InferSchema } from "xmcp";
+import { formatJobLine, latestJobText, waitForJob } from "../lib/jobs";
+import { approvalMessage } from "../lib/approval";
+
+export const schema = {
+  jobId: z.string().describe("Job ID returned by start_job"),
+  waitMs: z.number().int().min(0).max(30000).optional().default(0)
+    .describe("Wait up to this many milliseconds for the job to finish before answering"),
+  events: z.number().int().min(0).max(100).optional().default(10)
+    .describe("Number of recent agent events to include"),
+};
+
+export const metadata: ToolMetadata = {
+  name: "job_status",
+  description: `Get the state of a background job, its partial output while running,
+and its final output once finished.`,
+  annotations: {
+    title: "Background Job Status",
+    readOnlyHint: true,
+    destructiveHint: false,
+    idempotentHint: true,
+  },
+};
+
+export default async function jobStatus({ jobId, waitMs, events }: InferSchema<typeof schema>) {
+  const job = await waitForJob(jobId, waitMs ?? 0);
+  if (!job) {
+    return `❌ Unknown job: ${jobId}`;
+  }
+
+  let result = `${formatJobLine(job)}\n`;
+
+  if (job.step) {
+    result += `\nStep ${job.step.index}/${job.step.total}: ${job.step.name}\n`;
+  }
+
+  if (job.approval) {
+    result += `\n## Awaiting Approval\n${approvalMessage(job.approval)}\n\nAnswer with \`approve_job\` (jobId: "${job.id}").\n`;
+  }
+
+  if (job.state === "running") {
+    const latest = latestJobText(job);
+    result += `\n## Latest Output\n${latest ?? "(no output yet)"}\n`;
+  }
+
+  const recent = job.events.slice(-(events ?? 10));
+  if (recent.length > 0) {
+    result += `\n## Recent Events (${recent.length} of ${job.events.length})\n`;
+    result += recent.map((e) => {
+      const text = e.text.replace(/\s+/g, " ").trim();
+      return `- ${e.kind}${e.path ? ` ${e.path}` : ""}: ${text.substring(0, 200)}${text.length > 200 ? '...' : ''}`;
+    }).join("\n") + "\n";
+  }
+
+  if (job.output !== undefined) {
+    result += `\n## Output\n${job.output}\n`;
+  }
+  if (job.error) {
+    result += `\n## Error\n${job.error}\n`;
+  }
+
+  return result.replace(/\s+$/, "");
+}
@@ -0,0 +1,34 @@
+import { z } from "zod";
+import { type ToolMetadata, type InferSchema } from "xmcp";
+import { recordKestraCallback } from "../lib/kestra-results";
+
+export const schema = {
+  executionId: z.string().regex(/^[A-Za-z0-9_-]+$/, "Invalid Kestra execution id")
+    .describe("Id of the Kestra execution reporting back (`{{ execution.id }}` in a flow)"),
+  results: z.record(z.unknown()).describe("Structured results of the flow"),
+  taskId: z.string().optional().describe("Task sending the callback"),
+  state: z.string().optional().describe("State to report, e.g. SUCCESS or FAILED"),
+  requestId: z.string().optional()
+    .describe("AgentMesh request to attach the results to. Defaults to the request that started the execution."),
+};
+
+export const metadata: ToolMetadata = {
+  name: "kestra_callback",
+  description: `Called by Kestra flows to report results back to AgentMesh. The results
+are stored against the kestra_code_intel request that started the execution, and
+\`kestra_code_intel\` with action \`fetch-results\` returns them later.`,
+  annotations: {
+    title: "Kestra Callback",
+    readOnlyHint: false,
+    destructiveHint: false,
+    idempotentHint: false,
+  },
+};
+
+export default async function kestraCallback({ executionId, results, taskId, state, requestId }: InferSchema<typeof schema>) {
+  const record = recordKestraCallback({ executionId, taskId, state, results }, requestId);
+  if (!record) {
+    return `❌ Unknown Kestra request: ${requestId}`;
+  }
+  return `✅ Stored results of Kestra execution \`${executionId}\` under request \`${record.id}\``;
+}
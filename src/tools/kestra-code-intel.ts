@@ -1,182 +1,288 @@
 import { z } from "zod";
 import { type ToolMetadata, type InferSchema } from "xmcp";
-import { runClineTask } from "../lib/cline";
+import { type ToolExtra } from "../lib/progress";
+import { formatExecution, getKestraClient, KestraError } from "../lib/kestra";
+import { fetchRepository, FORGE_NAMES, parseRepositoryUrl, repositoryApiUrls, type RepositoryData, type RepositoryRef } from "../lib/repository";
+import { analyzeLocalRepository, type LocalRepositoryData } from "../lib/local-repository";
+import { resolveWorkspacePath } from "../lib/workspace";
+import {
+  evaluateHealthRules,
+  explainHealthRules,
+  formatMetrics,
+  loadHealthRules,
+  parseSummary,
+  type HealthEvaluation,
+  type HealthRules,
+  type Metrics,
+  type RuleResult,
+  type Severity,
+} from "../lib/health-rules";
+import { prepareToolCall } from "../lib/tool-registry";
+import {
+  CODE_INTEL_ACTIONS,
+  codeIntelResult,
+  codeIntelResultSchema,
+  type CodeIntelAction,
+  type CodeIntelResult,
+} from "../lib/code-intel-result";
+import { findKestraRequest, formatKestraResults, loadKestraRequest, trackKestraExecution, type KestraRequestRecord } from "../lib/kestra-results";
+
+// Kestra ids and namespaces: letters, digits, dots, dashes and underscores
+const FLOW_NAME = /^[a-zA-Z0-9][a-zA-Z0-9._-]*$/;
+const DEFAULT_FLOW_ID = "agentmesh-code-intel";
+const AUTH_HINT = "set `kestra.auth` in the config (or KESTRA_USERNAME / KESTRA_PASSWORD, or KESTRA_TOKEN)";
+const UNSUPPORTED_URL = "is not a GitHub, GitLab or Gitea repository URL; add self-hosted instances to `gitlab.hosts` or `gitea.hosts` in the config";
 
 export const schema = {
-  action: z.enum([
-    "analyze-repo",      // Summarize repo health using Kestra AI
-    "setup-workflow",    // Generate Kestra workflow YAML
-    "process-summary",   // Process AI summary and decide actions
-    "execute-decision",  // Execute Cline based on AI decision
-  ]).describe("Kestra Code Intelligence action"),
-  repoUrl: z.string().optional().describe("GitHub repository URL"),
+  action: z.enum(CODE_INTEL_ACTIONS).describe("Kestra Code Intelligence action"),
+  repoUrl: z.string().optional().describe("Repository URL on GitHub, GitLab or Gitea/Forgejo"),
   summary: z.string().optional().describe("AI-generated summary to process"),
-  kestraUrl: z.string().optional().default("http://localhost:8080")
-    .describe("Kestra server URL"),
-  workingDirectory: z.string().optional().describe("Local working directory"),
+  namespace: z.string().regex(FLOW_NAME, "Invalid Kestra namespace").optional().default("agentmesh")
+    .describe("Kestra namespace for deploy-workflow, list-flows and delete-flow"),
+  flowId: z.string().regex(FLOW_NAME, "Invalid Kestra flow id").optional()
+    .describe(`Flow id for deploy-workflow (default: ${DEFAULT_FLOW_ID}) and delete-flow (required)`),
+  requestId: z.string().optional().describe("Request ID printed by analyze-repo, for fetch-results"),
+  executionId: z.string().optional().describe("Kestra execution ID, for fetch-results"),
+  kestraUrl: z.string().optional()
+    .describe("Kestra server URL (default: kestra.url from the config, else http://localhost:8080)"),
+  waitTimeout: z.number().int().min(0).max(3600).optional().default(300)
+    .describe("Seconds to wait for the triggered Kestra execution to finish; 0 returns as soon as it starts"),
+  workingDirectory: z.string().optional().describe("Local working directory; the checkout analyze-local reads"),
+  rulesFile: z.string().optional()
+    .describe("Health rules YAML file (default: .agentmesh/health-rules.yaml next to the config, else the built-in rules)"),
+  dryRun: z.boolean().optional().default(false)
+    .describe("For execute-decision: explain which rules fired and which tool would run, without running it"),
+  backend: z.string().optional().describe("Agent backend to run on (see cline_status). Defaults to the configured backend."),
 };
 
+// Structured content returned by every action, next to the markdown report
+export const outputSchema = codeIntelResultSchema.shape;
+
 export const metadata: ToolMetadata = {
   name: "kestra_code_intel",
   description: `Kestra-powered Code Intelligence Pipeline.
 
 Uses Kestra's AI Agent to:
-1. **Summarize** data from GitHub (issues, PRs, commits, CI status)
+1. **Summarize** data from GitHub, GitLab or Gitea/Forgejo (issues, PRs, CI pipelines, contributors)
 2. **Analyze** code health and identify priorities
 3. **Decide** what actions to take
 4. **Execute** fixes via Cline CLI
 
 Actions:
 - **analyze-repo**: Fetch and summarize repository data
+- **analyze-local**: Summarize a local checkout from git history, branches, tags, TODO/FIXME markers, tests and lockfiles (no network needed)
 - **setup-workflow**: Generate Kestra workflow for continuous monitoring
-- **process-summary**: Analyze AI summary and determine actions
-- **execute-decision**: Trigger Cline to execute recommended fixes
+- **process-summary**: Score a summary against the health rules and list the tools they route to
+- **execute-decision**: Run the tool of the most severe rule that fired (dryRun explains the decision instead)
+- **deploy-workflow**: Validate the monitoring workflow and create or update it in Kestra
+- **list-flows**: List the flows of a Kestra namespace
+- **delete-flow**: Delete a flow from Kestra
+- **fetch-results**: Read the results a flow reported back through \`kestra_callback\` (by requestId, executionId, or the latest for repoUrl)
+
+Every action also returns structured content (repository, metrics, health score and rule outcomes, priorities, recommendations, Kestra execution ID and state) matching the tool's output schema.
 
-This creates an intelligent pipeline: GitHub → Kestra AI → Decision → Cline Action`,
+This creates an intelligent pipeline: Forge → Kestra AI → Decision → Cline Action`,
   annotations: {
     title: "Kestra Code Intelligence",
     readOnlyHint: false,
-    destructiveHint: false,
+    destructiveHint: true,
     idempotentHint: false,
   },
 };
 
-// Simulated data fetching (in production, Kestra would do this)
-async function fetchGitHubData(repoUrl: string): Promise<object> {
-  // Extract owner/repo from URL
-  const match = repoUrl.match(/github\.com\/([^\/]+)\/([^\/]+)/);
-  if (!match) {
-    throw new Error("Invalid GitHub URL");
-  }
-  const [, owner, repo] = match;
-  
+interface Analysis {
+  summary: string;
+  metrics: Metrics;
+  priorities: { level: Severity; item: string; action?: string }[];
+  recommendations: string[];
+  overallHealth: string;
+  evaluation: HealthEvaluation;
+}
+
+// Loads the health rules, or explains why they could not be loaded
+function readRules(rulesFile?: string): HealthRules | string {
+  // Paths outside the workspace roots throw like in every other tool
+  const file = rulesFile ? resolveWorkspacePath(rulesFile) : undefined;
   try {
-    // Fetch repo data from GitHub API
-    const [repoData, issuesData, prsData] = await Promise.all([
-      fetch(`https://api.github.com/repos/${owner}/${repo}`).then(r => r.json()),
-      fetch(`https://api.github.com/repos/${owner}/${repo}/issues?state=open&per_page=10`).then(r => r.json()),
-      fetch(`https://api.github.com/repos/${owner}/${repo}/pulls?state=open&per_page=10`).then(r => r.json()),
-    ]);
-
-    return {
-      repo: {
-        name: repoData.name,
-        description: repoData.description,
-        stars: repoData.stargazers_count,
-        openIssues: repoData.open_issues_count,
-        language: repoData.language,
-        updatedAt: repoData.updated_at,
-      },
-      issues: Array.isArray(issuesData) ? issuesData.map((i: { title: string; labels: { name: string }[]; created_at: string; user: { login: string } }) => ({
-        title: i.title,
-        labels: i.labels?.map((l: { name: string }) => l.name) || [],
-        createdAt: i.created_at,
-        author: i.user?.login,
-      })) : [],
-      pullRequests: Array.isArray(prsData) ? prsData.map((p: { title: string; draft: boolean; created_at: string; user: { login: string } }) => ({
-        title: p.title,
-        draft: p.draft,
-        createdAt: p.created_at,
-        author: p.user?.login,
-      })) : [],
-    };
+    return loadHealthRules(file);
   } catch (error) {
-    return {
-      error: "Failed to fetch GitHub data",
-      repo: { name: repo, owner },
-      issues: [],
-      pullRequests: [],
-    };
+    return `❌ ${(error as Error).message}`;
   }
 }
 
-// AI-powered summary generation (simulates Kestra AI Agent)
-function generateAISummary(data: {
-  repo: { name: string; openIssues: number; language: string };
-  issues: { title: string; labels: string[] }[];
-  pullRequests: { title: string; draft: boolean }[];
-}): {
-  summary: string;
-  priorities: { level: string; item: string; action: string }[];
-  recommendations: string[];
-  overallHealth: string;
-} {
-  const priorities: { level: string; item: string; action: string }[] = [];
+// Scores the metrics and turns the rules that fired into priorities
+function scoreRepository(header: string[], metrics: Metrics, rules: HealthRules): Analysis {
+  const evaluation = evaluateHealthRules(rules, { metrics });
+  const summary = [
+    ...header,
+    ...formatMetrics(metrics),
+    `Health Score: ${evaluation.score}/100`,
+    `Overall Health: ${evaluation.health}`,
+  ].join('\n');
+
+  return { summary, metrics, ...prioritise(evaluation), overallHealth: evaluation.health, evaluation };
+}
+
+// Priorities and recommendations from the rules that fired
+function prioritise(evaluation: HealthEvaluation): Pick<Analysis, "priorities" | "recommendations"> {
+  const priorities = evaluation.fired.map(r => ({ level: r.rule.severity, item: r.item, action: r.rule.tool }));
   const recommendations: string[] = [];
-  
-  // Analyze issues
-  const bugIssues = data.issues.filter(i => 
-    i.labels.some(l => l.toLowerCase().includes('bug'))
-  );
-  const securityIssues = data.issues.filter(i => 
-    i.labels.some(l => l.toLowerCase().includes('security'))
-  );
-  
-  if (securityIssues.length > 0) {
-    priorities.push({
-      level: "CRITICAL",
-      item: `${securityIssues.length} security issue(s)`,
-      action: "security_audit"
-    });
-    recommendations.push("Run security audit immediately");
-  }
-  
-  if (bugIssues.length > 0) {
-    priorities.push({
-      level: "HIGH",
-      item: `${bugIssues.length} bug(s) reported`,
-      action: "fix_issues"
-    });
-    recommendations.push("Address bug fixes before new features");
-  }
-  
-  // Analyze PRs
-  const pendingPRs = data.pullRequests.filter(p => !p.draft);
-  if (pendingPRs.length > 3) {
-    priorities.push({
-      level: "MEDIUM",
-      item: `${pendingPRs.length} PRs awaiting review`,
-      action: "review_code"
-    });
-    recommendations.push("Review and merge pending PRs to reduce backlog");
+  for (const r of evaluation.fired) {
+    if (r.rule.recommendation && !recommendations.includes(r.rule.recommendation)) {
+      recommendations.push(r.rule.recommendation);
+    }
   }
-  
-  // Overall health assessment
-  let health = "HEALTHY";
-  if (securityIssues.length > 0) health = "CRITICAL";
-  else if (bugIssues.length > 2) health = "NEEDS_ATTENTION";
-  else if (data.repo.openIssues > 20) health = "MODERATE";
-  
-  const summary = `
-Repository: ${data.repo.name}
-Language: ${data.repo.language || 'Unknown'}
-Open Issues: ${data.repo.openIssues}
-Security Issues: ${securityIssues.length}
-Bug Reports: ${bugIssues.length}
-Pending PRs: ${pendingPRs.length}
-Overall Health: ${health}
-  `.trim();
-  
   if (recommendations.length === 0) {
     recommendations.push("Repository is in good health. Continue regular maintenance.");
   }
-  
-  return { summary, priorities, recommendations, overallHealth: health };
+  return { priorities, recommendations };
+}
+
+// Score and rule outcomes as structured content
+function healthData(evaluation: HealthEvaluation): CodeIntelResult["health"] {
+  return {
+    score: evaluation.score,
+    level: evaluation.health,
+    source: evaluation.source,
+    rules: evaluation.results.map(r => ({
+      id: r.rule.id,
+      severity: r.rule.severity,
+      fired: r.fired,
+      contribution: r.contribution,
+      explanation: r.explanation,
+      missing: r.missing,
+      tool: r.rule.tool,
+    })),
+  };
+}
+
+// The tool call a fired rule routes to
+function toolCall(result: RuleResult, args: Record<string, unknown> = { ...result.rule.arguments }) {
+  return {
+    rule: result.rule.id,
+    severity: result.rule.severity,
+    description: result.rule.description,
+    tool: result.rule.tool as string,
+    arguments: args,
+  };
+}
+
+// Report data shared by analyze-repo and analyze-local
+function analysisData(analysis: Analysis): Pick<CodeIntelResult, "metrics" | "health" | "priorities" | "recommendations"> {
+  return {
+    metrics: analysis.metrics,
+    health: healthData(analysis.evaluation),
+    priorities: analysis.priorities,
+    recommendations: analysis.recommendations,
+  };
+}
+
+// A failed action: the message, and the same message as data
+function failed(action: CodeIntelAction, text: string, data: Partial<CodeIntelResult> = {}) {
+  return codeIntelResult(text, { ...data, action, ok: false, error: text.replace(/^(❌|🔐|ℹ️)\s*/, "") });
+}
+
+// AI-powered summary generation (simulates Kestra AI Agent)
+function generateAISummary(data: RepositoryData, rules: HealthRules): Analysis {
+  const labelled = (labels: string[]) => data.issues.filter(i =>
+    i.labels.some(l => labels.some(label => l.toLowerCase().includes(label.toLowerCase())))
+  ).length;
+
+  // CI runs and contributors are only measured when the forge shares them
+  const metrics: Metrics = {
+    openIssues: data.repo.openIssues,
+    securityIssues: labelled(rules.labels.securityIssues),
+    bugReports: labelled(rules.labels.bugReports),
+    pendingPRs: data.pullRequests.filter(p => !p.draft).length,
+  };
+  if (data.pipelines?.length) {
+    metrics.failedCiRuns = data.pipelines.filter(p => p.state === 'failed').length;
+    metrics.ciRuns = data.pipelines.length;
+  }
+  if (data.contributors) {
+    metrics.contributors = data.contributors.length;
+  }
+
+  return scoreRepository([
+    `Repository: ${data.repo.name}`,
+    `Forge: ${FORGE_NAMES[data.forge]}`,
+    `Language: ${data.repo.language || 'Unknown'}`,
+  ], metrics, rules);
+}
+
+// The same health report for a local checkout, from git and the working tree
+function generateLocalSummary(data: LocalRepositoryData, rules: HealthRules, now: number = Date.now()): Analysis {
+  const metrics: Metrics = {
+    commits: data.commits.total,
+    recentCommits: data.commits.recent,
+    daysSinceLastCommit: Math.floor((now - Date.parse(data.commits.lastCommitAt)) / (24 * 60 * 60 * 1000)),
+    contributors: data.contributors.length,
+    activeContributors: data.activeContributors,
+    branches: data.branches.total,
+    staleBranches: data.branches.stale.length,
+    tags: data.tags.total,
+    todoMarkers: data.markers.todo,
+    fixmeMarkers: data.markers.fixme,
+    sourceFiles: data.tests.sourceFiles,
+    testFiles: data.tests.testFiles,
+    testRatio: data.tests.ratio,
+  };
+  if (data.lockfiles.length) {
+    metrics.lockfileAgeDays = Math.max(...data.lockfiles.map(l => l.ageDays));
+  }
+
+  const latestTag = data.tags.latest ? `${data.tags.latest.name} (${data.tags.latest.date.slice(0, 10)})` : 'none';
+  return scoreRepository([
+    `Repository: ${data.name}`,
+    `Branch: ${data.branch}`,
+    `Language: ${data.language || 'Unknown'}`,
+    `Last Commit: ${data.commits.lastCommitAt.slice(0, 10)}`,
+    `Latest Tag: ${latestTag}`,
+    `Lockfiles: ${data.lockfiles.length ? data.lockfiles.map(l => `${l.file} (${l.ageDays} days old)`).join(', ') : 'none'}`,
+  ], metrics, rules);
+}
+
+// Renders the analysis part of a health report
+function formatAnalysis(analysis: Analysis, source: string): string {
+  let result = `## Repository Analysis (from ${source})\n\`\`\`\n${analysis.summary}\n\`\`\`\n\n`;
+
+  if (analysis.priorities.length > 0) {
+    result += `## Priorities\n`;
+    for (const p of analysis.priorities) {
+      const emoji = p.level === 'CRITICAL' ? '🚨' : p.level === 'HIGH' ? '⚠️' : '📋';
+      const action = p.action ? ` → Action: \`${p.action}\`` : '';
+      result += `${emoji} **${p.level}**: ${p.item}${action}\n`;
+    }
+    result += '\n';
+  }
+
+  const { evaluation } = analysis;
+  result += `## Health Score: ${evaluation.score}/100\n`;
+  for (const r of evaluation.fired) {
+    result += `- -${r.contribution} **${r.rule.id}**: ${r.explanation}\n`;
+  }
+  result += `Rules: ${evaluation.source}\n\n`;
+
+  result += `## AI Recommendations\n`;
+  for (const rec of analysis.recommendations) {
+    result += `- ${rec}\n`;
+  }
+  return result;
 }
 
 // Generate Kestra workflow YAML
-function generateKestraWorkflow(repoUrl: string, agentmeshUrl: string): string {
-  const match = repoUrl.match(/github\.com\/([^\/]+)\/([^\/]+)/);
-  const repoName = match ? match[2] : 'repo';
-  
+function generateKestraWorkflow(ref: RepositoryRef, agentmeshUrl: string, namespace = "agentmesh", flowId = DEFAULT_FLOW_ID): string {
+  const forge = FORGE_NAMES[ref.forge];
+  const api = repositoryApiUrls(ref);
+
   return `# Kestra Code Intelligence Workflow for AgentMesh
 # This workflow uses Kestra's AI Agent to monitor and improve code quality
 
-id: agentmesh-code-intel
-namespace: agentmesh
+id: ${flowId}
+namespace: ${namespace}
 description: |
   AI-powered code intelligence pipeline that:
-  1. Fetches data from GitHub
+  1. Fetches data from ${forge}
   2. Uses AI to summarize and analyze
   3. Makes decisions on what actions to take
   4. Triggers Cline via AgentMesh to execute fixes
@@ -184,7 +290,7 @@ description: |
 inputs:
   - id: repo_url
     type: STRING
-    defaults: "${repoUrl}"
+    defaults: "${ref.url}"
   - id: agentmesh_url
     type: STRING
     defaults: "${agentmeshUrl}"
@@ -195,20 +301,20 @@ triggers:
     cron: "0 9 * * *"  # Run daily at 9 AM
 
 tasks:
-  # Step 1: Fetch GitHub Data
+  # Step 1: Fetch ${forge} Data
   - id: fetch_issues
     type: io.kestra.plugin.core.http.Request
-    uri: "https://api.github.com/repos/${match ? `${match[1]}/${match[2]}` : 'owner/repo'}/issues?state=open"
+    uri: "${api.issues}"
     method: GET
     headers:
-      Accept: application/vnd.github.v3+json
+      Accept: ${api.accept}
 
   - id: fetch_prs
     type: io.kestra.plugin.core.http.Request
-    uri: "https://api.github.com/repos/${match ? `${match[1]}/${match[2]}` : 'owner/repo'}/pulls?state=open"
+    uri: "${api.pullRequests}"
     method: GET
     headers:
-      Accept: application/vnd.github.v3+json
+      Accept: ${api.accept}
 
   # Step 2: AI Agent Summarization
   - id: ai_summarize
@@ -262,8 +368,8 @@ tasks:
         type: io.kestra.plugin.core.log.Log
         message: "Repository health check complete: {{ outputs.ai_summarize.choices[0].message.content }}"
 
-  # Step 5: Generate Report
-  - id: create_report
+  # Step 5: Report the results back to AgentMesh
+  - id: report_results
     type: io.kestra.plugin.core.http.Request
     uri: "{{ inputs.agentmesh_url }}"
     method: POST
@@ -276,11 +382,14 @@ tasks:
         "id": 2,
         "method": "tools/call",
         "params": {
-          "name": "generate_docs",
+          "name": "kestra_callback",
           "arguments": {
-            "target": "HEALTH_REPORT.md",
-            "type": "report",
-            "content": "{{ outputs.ai_summarize.choices[0].message.content | replace('\\"', '\\\\\"') }}"
+            "executionId": "{{ execution.id }}",
+            "taskId": "report_results",
+            "results": {
+              "repoUrl": "{{ inputs.repo_url }}",
+              "summary": {{ outputs.ai_summarize.choices[0].message.content | toJson }}
+            }
           }
         }
       }
@@ -295,132 +404,128 @@ outputs:
 `;
 }
 
-// Check if Kestra server is running
-async function checkKestraServer(kestraUrl: string): Promise<boolean> {
-  try {
-    const res = await fetch(`${kestraUrl}/api/v1/configs`, {
-      method: 'GET',
-      headers: { 'Accept': 'application/json' },
-    });
-    // 401 means server is running but needs auth - that's still "online"
-    return res.ok || res.status === 401;
-  } catch {
-    return false;
-  }
-}
-
-// Trigger a Kestra flow execution via webhook (no auth required)
-async function triggerKestraFlow(
-  kestraUrl: string, 
-  namespace: string, 
-  flowId: string, 
-  inputs: Record<string, string>
-): Promise<{ success: boolean; executionId?: string; error?: string }> {
-  // Use webhook trigger - doesn't require authentication
-  const webhookKey = 'agentmesh-github-analysis';
-  const webhookUrl = `${kestraUrl}/api/v1/executions/webhook/${namespace}/${flowId}/${webhookKey}`;
-  
-  try {
-    const res = await fetch(webhookUrl, {
-      method: 'POST',
-      headers: { 
-        'Content-Type': 'application/json',
-        'Accept': 'application/json',
-      },
-      body: JSON.stringify(inputs),
-    });
-    
-    if (res.ok) {
-      const data = await res.json();
-      return { success: true, executionId: data.id };
-    } else {
-      const error = await res.text();
-      return { success: false, error: `${res.status}: ${error}` };
-    }
-  } catch (error) {
-    return { success: false, error: String(error) };
+// Explains a failed Kestra API call
+function kestraFailure(error: unknown, kestraUrl: string): string {
+  if (error instanceof KestraError && error.status === 401) {
+    return `🔐 **Kestra** rejected the request; ${AUTH_HINT}`;
   }
-}
-
-// Get Kestra execution status and logs
-async function getKestraExecution(
-  kestraUrl: string, 
-  executionId: string
-): Promise<{ status: string; logs: string[] }> {
-  try {
-    const [execRes, logsRes] = await Promise.all([
-      fetch(`${kestraUrl}/api/v1/executions/${executionId}`),
-      fetch(`${kestraUrl}/api/v1/logs/${executionId}`),
-    ]);
-    
-    const execData = await execRes.json();
-    const logsData = await logsRes.json();
-    
-    return {
-      status: execData.state?.current || 'UNKNOWN',
-      logs: Array.isArray(logsData) ? logsData.map((l: { message: string }) => l.message) : [],
-    };
-  } catch {
-    return { status: 'ERROR', logs: [] };
+  if (error instanceof TypeError || (error as Error).name === "TimeoutError") {
+    return `❌ Kestra is not reachable at ${kestraUrl}`;
   }
+  return `❌ ${(error as Error).message}`;
 }
 
 export default async function kestraCodeIntel({ 
   action, 
   repoUrl,
   summary,
-  kestraUrl = "http://localhost:8080",
-  workingDirectory 
-}: InferSchema<typeof schema>) {
-  
+  namespace,
+  flowId,
+  requestId,
+  executionId,
+  kestraUrl,
+  waitTimeout,
+  workingDirectory,
+  rulesFile,
+  dryRun,
+  backend,
+}: InferSchema<typeof schema>, extra?: ToolExtra) {
+  const kestra = getKestraClient(kestraUrl);
+  kestraUrl = kestra.url;
+
   switch (action) {
     case "analyze-repo": {
       if (!repoUrl) {
-        return "❌ repoUrl is required for analyze-repo action";
+        return failed(action, "❌ repoUrl is required for analyze-repo action");
       }
       
-      // Check if Kestra is running
-      const kestraRunning = await checkKestraServer(kestraUrl);
-      
-      // Fetch GitHub data (always do this - it's real data)
-      const data = await fetchGitHubData(repoUrl);
+      const ref = parseRepositoryUrl(repoUrl);
+      if (!ref) {
+        return failed(action, `❌ ${repoUrl} ${UNSUPPORTED_URL}`);
+      }
+      const forge = FORGE_NAMES[ref.forge];
+      const rules = readRules(rulesFile);
+      if (typeof rules === "string") {
+        return failed(action, rules);
+      }
+
+      // Fetch the forge data (always do this - it's real data)
+      let data: RepositoryData;
+      try {
+        data = await fetchRepository(ref);
+      } catch (error) {
+        return failed(action, `❌ **${forge}**: ${(error as Error).message}`);
+      }
+
+      // Check if Kestra is running; a 401 still means it is up
+      const kestraStatus = await kestra.ping();
+      let request: KestraRequestRecord | undefined;
+      const kestraRunning = kestraStatus !== "offline";
+      const kestraData: NonNullable<CodeIntelResult["kestra"]> = { url: kestraUrl, status: kestraStatus };
       
-      // Generate analysis from real GitHub data
-      const analysis = generateAISummary(data as {
-        repo: { name: string; openIssues: number; language: string };
-        issues: { title: string; labels: string[] }[];
-        pullRequests: { title: string; draft: boolean }[];
-      });
+      // Generate analysis from real forge data
+      const analysis = generateAISummary(data, rules);
       
       let result = `🔍 **Kestra AI Code Intelligence Report**\n\n`;
       
       // Show Kestra status
-      if (kestraRunning) {
+      if (kestraRunning && ref.forge !== "github") {
+        result += `✅ **Kestra Server**: Connected at ${kestraUrl}\n`;
+        // The imported analysis flow only reads GitHub
+        result += `ℹ️ The Kestra analysis flow reads GitHub only - using direct ${forge} analysis\n\n`;
+      } else if (kestraRunning) {
         result += `✅ **Kestra Server**: Connected at ${kestraUrl}\n\n`;
         
-        // Parse owner/repo from URL for Kestra flow inputs
-        const match = repoUrl.match(/github\.com\/([^\/]+)\/([^\/]+)/);
-        const owner = match ? match[1] : 'N-45div';
-        const repo = match ? match[2].replace('.git', '') : 'Agentmesh';
+        // Kestra flow inputs
+        const owner = data.repo.owner;
+        const repo = data.repo.name;
         
         // Try to trigger the analysis flow via webhook
-        const flowResult = await triggerKestraFlow(
-          kestraUrl, 
-          'agentmesh', 
-          'github_repo_analysis',
-          { owner, repo }
-        );
-        
-        if (flowResult.success) {
-          result += `🚀 **Kestra Flow Triggered**: Execution ID \`${flowResult.executionId}\`\n`;
-          result += `View at: ${kestraUrl}/ui/executions/${flowResult.executionId}\n\n`;
-        } else if (flowResult.error?.includes('401')) {
-          // Auth required - Kestra is running but needs login
-          result += `🔐 **Kestra**: Server running (auth required for API)\n`;
-          result += `View flows at: ${kestraUrl}/ui/flows/agentmesh/github_repo_analysis\n\n`;
-        } else {
-          result += `ℹ️ Kestra flow not imported yet - using direct GitHub analysis\n`;
-          result += `Import flow at: ${kestraUrl}/ui/flows\n\n`;
+        try {
+          const execution = await kestra.triggerWebhook('agentmesh', 'github_repo_analysis', 'agentmesh-github-analysis', { owner, repo });
+          request = trackKestraExecution({
+            action: "analyze-repo",
+            repoUrl,
+            executionId: execution.id,
+            namespace: execution.namespace,
+            flowId: execution.flowId,
+          });
+          Object.assign(kestraData, {
+            executionId: execution.id,
+            state: execution.state,
+            namespace: execution.namespace,
+            flowId: execution.flowId,
+            requestId: request.id,
+          });
+          result += `🚀 **Kestra Flow Triggered**: Execution ID \`${execution.id}\`\n`;
+          result += `🧾 Request ID: \`${request.id}\`\n`;
+          result += `View at: ${kestraUrl}/ui/executions/${execution.id}\n\n`;
+
+          if (waitTimeout) {
+            try {
+              const finished = await kestra.waitForExecution(execution.id, { timeout: waitTimeout * 1000, signal: extra?.signal });
+              result += formatExecution(finished) + '\n';
+              Object.assign(kestraData, { state: finished.execution.state, finished: finished.finished, outputs: finished.execution.outputs });
+              // The flow may have reported back while we waited
+              const reported = loadKestraRequest(request.id);
+              if (reported && reported.callbacks.length > 0) {
+                result += formatKestraResults(reported) + '\n';
+              }
+            } catch (error) {
+              result += error instanceof KestraError && error.status === 401
+                ? `🔐 **Kestra**: Reading the execution needs credentials; ${AUTH_HINT}\n\n`
+                : `⚠️ Could not follow the execution: ${(error as Error).message}\n\n`;
+            }
+          }
+        } catch (error) {
+          if (error instanceof KestraError && error.status === 401) {
+            // Auth required - Kestra is running but rejected the credentials
+            result += `🔐 **Kestra**: Server running (auth required for API)\n`;
+            result += `View flows at: ${kestraUrl}/ui/flows/agentmesh/github_repo_analysis\n\n`;
+          } else {
+            result += `ℹ️ Kestra flow not imported yet - using direct GitHub analysis\n`;
+            result += `Import flow at: ${kestraUrl}/ui/flows\n\n`;
+          }
         }
       } else {
         result += `⚠️ **Kestra Server**: Not running at ${kestraUrl}\n`;
@@ -428,43 +533,81 @@ export default async function kestraCodeIntel({
         result += `\`\`\`bash\ndocker run -p 8080:8080 kestra/kestra:latest server local\n\`\`\`\n\n`;
       }
       
-      result += `## Repository Analysis (from GitHub API)\n\`\`\`\n${analysis.summary}\n\`\`\`\n\n`;
-      
-      if (analysis.priorities.length > 0) {
-        result += `## Priorities\n`;
-        for (const p of analysis.priorities) {
-          const emoji = p.level === 'CRITICAL' ? '🚨' : p.level === 'HIGH' ? '⚠️' : '📋';
-          result += `${emoji} **${p.level}**: ${p.item} → Action: \`${p.action}\`\n`;
-        }
-        result += '\n';
-      }
-      
-      result += `## AI Recommendations\n`;
-      for (const rec of analysis.recommendations) {
-        result += `- ${rec}\n`;
-      }
+      result += formatAnalysis(analysis, `${forge} API`);
       
       result += `\n## Next Steps\n`;
       if (kestraRunning) {
         result += `- View Kestra dashboard: ${kestraUrl}/ui\n`;
         result += `- Use \`setup-workflow\` to create automated monitoring\n`;
       }
+      if (request) {
+        result += `- Use \`fetch-results\` with requestId \`${request.id}\` to read what the flow reports back\n`;
+      }
       result += `- Use \`execute-decision\` to automatically fix issues via Cline`;
       
-      return result;
+      return codeIntelResult(result, {
+        action,
+        ok: true,
+        repository: {
+          source: ref.forge,
+          name: data.repo.name,
+          fullName: data.repo.fullName,
+          url: data.repo.url,
+          description: data.repo.description,
+          language: data.repo.language,
+          defaultBranch: data.repo.defaultBranch,
+          stars: data.repo.stars,
+        },
+        ...analysisData(analysis),
+        kestra: kestraData,
+      });
     }
     
+    case "analyze-local": {
+      // Reads git and the working tree only, so it works without network access
+      const dir = resolveWorkspacePath(workingDirectory || process.cwd());
+      const rules = readRules(rulesFile);
+      if (typeof rules === "string") {
+        return failed(action, rules);
+      }
+      let data: LocalRepositoryData;
+      try {
+        data = await analyzeLocalRepository(dir);
+      } catch (error) {
+        return failed(action, `❌ **Local analysis**: ${(error as Error).message}`);
+      }
+
+      const analysis = generateLocalSummary(data, rules);
+      let result = `🔍 **Local Code Intelligence Report**: \`${data.root}\`\n\n`;
+      result += formatAnalysis(analysis, "local git history");
+      result += `\n## Next Steps\n`;
+      result += `- Use \`execute-decision\` with workingDirectory \`${data.root}\` to fix issues via Cline\n`;
+      result += `- Use \`analyze-repo\` with the repository URL to add forge issues, pull requests and CI status`;
+      return codeIntelResult(result, {
+        action,
+        ok: true,
+        repository: { source: "local", name: data.name, language: data.language, root: data.root, branch: data.branch },
+        ...analysisData(analysis),
+      });
+    }
+
     case "setup-workflow": {
       if (!repoUrl) {
-        return "❌ repoUrl is required for setup-workflow action";
+        return failed(action, "❌ repoUrl is required for setup-workflow action");
       }
       
-      const agentmeshUrl = kestraUrl?.replace(':8080', ':3001/mcp') || 'http://localhost:3001/mcp';
-      const workflow = generateKestraWorkflow(repoUrl, agentmeshUrl);
+      const ref = parseRepositoryUrl(repoUrl);
+      if (!ref) {
+        return failed(action, `❌ ${repoUrl} ${UNSUPPORTED_URL}`);
+      }
+
+      const agentmeshUrl = kestraUrl.replace(':8080', ':3001/mcp');
+      const id = flowId || DEFAULT_FLOW_ID;
+      const workflow = generateKestraWorkflow(ref, agentmeshUrl, namespace, id);
       
-      return `📋 **Kestra Workflow Generated**
+      return codeIntelResult(`📋 **Kestra Workflow Generated**
 
-Save this as \`agentmesh-code-intel.yml\` and import into Kestra:
+Save this as \`${id}.yml\` and import into Kestra (or use \`deploy-workflow\` to save it through the API):
 
 \`\`\`yaml
 ${workflow}
@@ -489,101 +632,226 @@ ${workflow}
    - Trigger manually or wait for scheduled run
 
 This workflow will:
-✅ Fetch GitHub issues and PRs daily
+✅ Fetch ${FORGE_NAMES[ref.forge]} issues and ${ref.forge === "gitlab" ? "merge requests" : "PRs"} daily
 ✅ Use AI to summarize repository health
 ✅ Automatically trigger AgentMesh/Cline for critical issues
-✅ Generate health reports`;
+✅ Generate health reports`, {
+        action,
+        ok: true,
+        repository: { source: ref.forge, name: ref.name, fullName: ref.path, url: ref.url },
+        workflow: { namespace, flowId: id, yaml: workflow },
+      });
     }
     
     case "process-summary": {
       if (!summary) {
-        return "❌ summary is required for process-summary action";
+        return failed(action, "❌ summary is required for process-summary action");
       }
-      
-      // Parse the summary and determine actions
-      const lowerSummary = summary.toLowerCase();
-      const actions: string[] = [];
-      
-      if (lowerSummary.includes('security') || lowerSummary.includes('critical')) {
-        actions.push('security_audit');
-      }
-      if (lowerSummary.includes('bug') || lowerSummary.includes('fix')) {
-        actions.push('fix_issues');
-      }
-      if (lowerSummary.includes('review') || lowerSummary.includes('pr')) {
-        actions.push('review_code');
-      }
-      if (lowerSummary.includes('test')) {
-        actions.push('generate_tests');
+      const rules = readRules(rulesFile);
+      if (typeof rules === "string") {
+        return failed(action, rules);
       }
-      
+
+      // Metric lines ("Bug Reports: 3") are scored, the rest of the text is matched by mentions
+      const input = parseSummary(summary);
+      const evaluation = evaluateHealthRules(rules, input);
+      const actions = evaluation.fired.filter((r, i, fired) =>
+        r.rule.tool && fired.findIndex(f => f.rule.tool === r.rule.tool) === i
+      );
+      const explanation = explainHealthRules(evaluation);
+      const data: CodeIntelResult = {
+        action,
+        ok: true,
+        metrics: input.metrics,
+        health: healthData(evaluation),
+        ...prioritise(evaluation),
+        actions: actions.map(r => toolCall(r)),
+      };
+
       if (actions.length === 0) {
-        return `✅ **No immediate actions required**\n\nThe AI summary indicates the repository is healthy.`;
+        return codeIntelResult(`✅ **No immediate actions required**\n\nThe AI summary indicates the repository is healthy.\n\n${explanation}`, data);
       }
       
-      return `🎯 **AI Decision: Actions Required**
+      return codeIntelResult(`🎯 **AI Decision: Actions Required**
 
 Based on the summary, the following actions are recommended:
 
-${actions.map((a, i) => `${i + 1}. \`${a}\``).join('\n')}
+${actions.map((r, i) => `${i + 1}. \`${r.rule.tool}\` — ${r.rule.description} (${r.rule.severity})`).join('\n')}
+
+Use \`execute-decision\` with the summary to automatically run these actions via Cline.
 
-Use \`execute-decision\` with the summary to automatically run these actions via Cline.`;
+${explanation}`, data);
     }
     
     case "execute-decision": {
       if (!summary) {
-        return "❌ summary is required for execute-decision action";
+        return failed(action, "❌ summary is required for execute-decision action");
       }
-      
-      // Determine what to execute based on summary
-      const lowerSummary = summary.toLowerCase();
-      let prompt = "";
-      let actionName = "";
-      
-      if (lowerSummary.includes('security') || lowerSummary.includes('critical')) {
-        prompt = "Perform a security audit and fix any critical vulnerabilities found";
-        actionName = "Security Audit & Fix";
-      } else if (lowerSummary.includes('bug')) {
-        prompt = "Review open bug reports and fix the most critical ones";
-        actionName = "Bug Fix";
-      } else if (lowerSummary.includes('review') || lowerSummary.includes('pr')) {
-        prompt = "Review pending pull requests and provide feedback";
-        actionName = "Code Review";
-      } else {
-        prompt = "Analyze the codebase and suggest improvements";
-        actionName = "Code Analysis";
+      const rules = readRules(rulesFile);
+      if (typeof rules === "string") {
+        return failed(action, rules);
       }
       
-      const result = await runClineTask(prompt, {
-        cwd: workingDirectory,
-        mode: "act",
-      });
+      // Run the tool of the most severe rule that fired
+      const input = parseSummary(summary);
+      const evaluation = evaluateHealthRules(rules, input);
+      const scored: CodeIntelResult = { action, ok: true, metrics: input.metrics, health: healthData(evaluation), ...prioritise(evaluation) };
+      const decision = evaluation.fired.find(r => r.rule.tool);
+      if (!decision) {
+        return codeIntelResult(`ℹ️ **No action to execute**: no rule with a tool fired for the summary\n\n${explainHealthRules(evaluation)}`, scored);
+      }
+
+      const tool = decision.rule.tool as string;
+      const args: Record<string, unknown> = { ...decision.rule.arguments };
+      if (workingDirectory) args.workingDirectory = workingDirectory;
+      if (backend) args.backend = backend;
+      const actionName = `${decision.rule.description} (\`${tool}\`)`;
+      const call = toolCall(decision, args);
+
+      if (dryRun) {
+        return codeIntelResult(`🧪 **Dry Run**: execute-decision would run \`${tool}\` for rule \`${decision.rule.id}\`
+
+Action: ${actionName}
+Reason: ${decision.explanation}
+Arguments:
+\`\`\`json
+${JSON.stringify(args, null, 2)}
+\`\`\`
+
+${explainHealthRules(evaluation)}`, { ...scored, decision: { ...call, status: "dry-run" } });
+      }
+
+      let output: string;
+      try {
+        const run = await prepareToolCall(tool, args);
+        output = await run(extra);
+      } catch (error) {
+        const message = (error as Error).message;
+        return codeIntelResult(`❌ **Execution Failed**
+
+Action: ${actionName}
+Error: ${message}`, { ...scored, ok: false, error: message, decision: { ...call, status: "failed" } });
+      }
       
-      if (result.success) {
-        return `✅ **Kestra AI Decision Executed**
+      if (!output.startsWith("❌")) {
+        return codeIntelResult(`✅ **Kestra AI Decision Executed**
 
 Action: ${actionName}
 Status: Completed
 
 **Cline Output:**
-${result.output}
+${output}
 
 The AI-driven pipeline has completed:
-1. ✅ Data fetched from GitHub
+1. ✅ Data fetched from the forge
 2. ✅ AI summarized repository health
 3. ✅ Decision made based on priorities
-4. ✅ Cline executed the fix`;
+4. ✅ Cline executed the fix`, { ...scored, decision: { ...call, status: "completed", output } });
       } else {
-        return `❌ **Execution Failed**
+        return codeIntelResult(`❌ **Execution Failed**
 
 Action: ${actionName}
-Error: ${result.error}
 
-${result.output}`;
+${output}`, { ...scored, ok: false, error: `${tool} failed`, decision: { ...call, status: "failed", output } });
+      }
+    }
+    
+    case "deploy-workflow": {
+      if (!repoUrl) {
+        return failed(action, "❌ repoUrl is required for deploy-workflow action");
+      }
+
+      const ref = parseRepositoryUrl(repoUrl);
+      if (!ref) {
+        return failed(action, `❌ ${repoUrl} ${UNSUPPORTED_URL}`);
       }
+
+      const agentmeshUrl = kestraUrl.replace(':8080', ':3001/mcp');
+      const id = flowId || DEFAULT_FLOW_ID;
+      const workflow = generateKestraWorkflow(ref, agentmeshUrl, namespace, id);
+      const generated = { workflow: { namespace, flowId: id, yaml: workflow }, kestra: { url: kestraUrl } };
+
+      try {
+        const violations = await kestra.validateFlow(workflow);
+        if (violations.length > 0) {
+          return failed(action, `❌ **Kestra rejected the flow** \`${namespace}.${id}\`\n\n${violations.map((v) => `- ${v}`).join('\n')}`, generated);
+        }
+        const { flow, created } = await kestra.deployFlow(namespace, id, workflow);
+        return codeIntelResult(`🚀 **Kestra Flow ${created ? 'Created' : 'Updated'}**: \`${flow.namespace}.${flow.id}\` (revision ${flow.revision})
+
+View at: ${kestraUrl}/ui/flows/edit/${flow.namespace}/${flow.id}
+
+Add \`OPENAI_API_KEY\` in Kestra secrets before the first run.`, { action, ok: true, ...generated, flows: [{ ...flow, created }] });
+      } catch (error) {
+        return failed(action, kestraFailure(error, kestraUrl), generated);
+      }
+    }
+
+    case "list-flows": {
+      try {
+        const flows = await kestra.listFlows(namespace);
+        const data: CodeIntelResult = { action, ok: true, kestra: { url: kestraUrl }, flows };
+        if (flows.length === 0) {
+          return codeIntelResult(`ℹ️ No flows in namespace \`${namespace}\``, data);
+        }
+        const lines = flows.map((f) => {
+          const description = f.description ? `: ${f.description.trim().split('\n')[0]}` : '';
+          return `- \`${f.namespace}.${f.id}\` (revision ${f.revision}${f.disabled ? ', disabled' : ''})${description}`;
+        });
+        return codeIntelResult(`📋 **Kestra Flows** in \`${namespace}\` (${flows.length})\n\n${lines.join('\n')}`, data);
+      } catch (error) {
+        return failed(action, kestraFailure(error, kestraUrl));
+      }
+    }
+
+    case "delete-flow": {
+      if (!flowId) {
+        return failed(action, "❌ flowId is required for delete-flow action");
+      }
+
+      try {
+        await kestra.deleteFlow(namespace, flowId);
+        return codeIntelResult(`🗑️ **Kestra Flow Deleted**: \`${namespace}.${flowId}\``, {
+          action,
+          ok: true,
+          kestra: { url: kestraUrl },
+          flows: [{ namespace, id: flowId, deleted: true }],
+        });
+      } catch (error) {
+        if (error instanceof KestraError && error.status === 404) {
+          return failed(action, `❌ Flow \`${namespace}.${flowId}\` does not exist in Kestra`);
+        }
+        return failed(action, kestraFailure(error, kestraUrl));
+      }
+    }
+    
+    case "fetch-results": {
+      if (!requestId && !executionId && !repoUrl) {
+        return failed(action, "❌ requestId, executionId or repoUrl is required for fetch-results action");
+      }
+
+      const record = requestId ? loadKestraRequest(requestId) : findKestraRequest({ executionId, repoUrl });
+      if (!record) {
+        const key = requestId ? `request ${requestId}` : executionId ? `execution ${executionId}` : repoUrl;
+        return failed(action, `ℹ️ No Kestra results stored for ${key}`);
+      }
+      const last = record.callbacks[record.callbacks.length - 1];
+      return codeIntelResult(formatKestraResults(record), {
+        action,
+        ok: true,
+        kestra: {
+          url: kestraUrl,
+          executionId: record.executionId,
+          state: last?.state,
+          namespace: record.namespace,
+          flowId: record.flowId,
+          requestId: record.id,
+        },
+        results: { action: record.action, repoUrl: record.repoUrl, createdAt: record.createdAt, callbacks: record.callbacks },
+      });
     }
     
     default:
-      return `❌ Unknown action: ${action}`;
+      return failed(action, `❌ Unknown action: ${action}`);
   }
 }
@@ -0,0 +1,27 @@
+import { z } from "zod";
+import { type ToolMetadata, type InferSchema } from "xmcp";
+import { formatJobLine, listJobs } from "../lib/jobs";
+
+export const schema = {
+  state: z.enum(["running", "awaiting_approval", "completed", "failed", "cancelled"]).optional()
+    .describe("Only list jobs in this state"),
+};
+
+export const metadata: ToolMetadata = {
+  name: "list_jobs",
+  description: "List background jobs started with start_job, newest first.",
+  annotations: {
+    title: "List Background Jobs",
+    readOnlyHint: true,
+    destructiveHint: false,
+    idempotentHint: true,
+  },
+};
+
+export default async function listJobsTool({ state }: InferSchema<typeof schema>) {
+  const jobs = listJobs(state);
+  if (jobs.length === 0) {
+    return `📋 No ${state ? `${state} ` : ""}jobs.`;
+  }
+  return `📋 Jobs (${jobs.length})\n\n${jobs.map(formatJobLine).join("\n")}`;
+}
@@ -0,0 +1,51 @@
+import { z } from "zod";
+import { type ToolMetadata, type InferSchema } from "xmcp";
+import { discardWorktree, listWorktrees, mergeWorktree } from "../lib/worktree";
+import { resolveWorkspacePath } from "../lib/workspace";
+
+export const schema = {
+  action: z.enum(["list", "merge", "discard"]).describe("List isolation worktrees, merge one into the current branch, or discard one"),
+  branch: z.string().optional().describe("Isolation branch (agentmesh/...) for merge and discard"),
+  workingDirectory: z.string().optional().describe("Any directory inside the repository"),
+};
+
+export const metadata: ToolMetadata = {
+  name: "manage_worktree",
+  description: `Manage the git worktrees created by tools run with isolation: "worktree".
+- list: show pending isolation branches
+- merge: merge a branch into the current branch of your checkout, then remove it
+- discard: delete a branch and its worktree without merging`,
+  annotations: {
+    title: "Manage Isolation Worktrees",
+    readOnlyHint: false,
+    destructiveHint: true,
+    idempotentHint: false,
+  },
+};
+
+export default async function manageWorktree({ action, branch, workingDirectory }: InferSchema<typeof schema>) {
+  const dir = resolveWorkspacePath(workingDirectory || process.cwd());
+  try {
+    if (action === "list") {
+      const worktrees = await listWorktrees(dir);
+      if (!worktrees.length) {
+        return "📋 No isolation worktrees.";
+      }
+      return `📋 Isolation Worktrees:\n\n${worktrees.map((w) => `- \`${w.branch}\` at ${w.path} (${w.head.slice(0, 7)})`).join("\n")}`;
+    }
+
+    if (!branch) {
+      return `❌ The ${action} action needs a branch`;
+    }
+
+    if (action === "merge") {
+      const output = await mergeWorktree(dir, branch);
+      return `✅ Merged ${branch}\n\n${output}`;
+    }
+
+    await discardWorktree(dir, branch);
+    return `🗑️ Discarded ${branch}`;
+  } catch (error) {
+    return `❌ Worktree ${action} failed\n\nError: ${(error as Error).message}`;
+  }
+}
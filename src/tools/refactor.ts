@@ -1,12 +1,19 @@
 import { z } from "zod";
 import { type ToolMetadata, type InferSchema } from "xmcp";
-import { runClineTask } from "../lib/cline";
+import { runAgentTask } from "../lib/agent";
+import { withProgress, type ToolExtra } from "../lib/progress";
+import { formatWorktree, isolatedTarget, withIsolation } from "../lib/worktree";
+import { formatChanges } from "../lib/changes";
+import { checkWorkspaceTarget } from "../lib/workspace";
 
 export const schema = {
   target: z.string().describe("File or directory to refactor."),
   goals: z.array(z.string()).optional()
     .describe("Refactoring goals: 'readability', 'performance', 'modularity', 'dry'"),
   workingDirectory: z.string().optional().describe("The directory to work in."),
+  backend: z.string().optional().describe("Agent backend to run on (see cline_status). Defaults to the configured backend."),
+  isolation: z.enum(["none", "worktree"]).optional().default("none")
+    .describe("'worktree' runs the agent in a temporary git worktree on a new branch and returns the diff instead of editing your checkout."),
 };
 
 export const metadata: ToolMetadata = {
@@ -21,10 +28,12 @@ while maintaining functionality.`,
   },
 };
 
-export default async function refactor({ target, goals, workingDirectory }: InferSchema<typeof schema>) {
+export default async function refactor({ target, goals, workingDirectory, backend, isolation }: InferSchema<typeof schema>, extra?: ToolExtra) {
+  checkWorkspaceTarget(target, workingDirectory);
+
   const refactorGoals = goals || ["readability", "maintainability"];
 
-  const prompt = `Refactor the code in ${target}.
+  const buildPrompt = (files: string) => `Refactor the code in ${files}.
 
 Goals:
 ${refactorGoals.map((g) => `- Improve ${g}`).join("\n")}
@@ -36,11 +45,15 @@ Guidelines:
 - Consider SOLID principles where applicable
 - Ensure tests still pass after refactoring`;
 
-  const result = await runClineTask(prompt, { cwd: workingDirectory });
+  const { result, worktree } = await withIsolation(isolation, workingDirectory, "refactor", (cwd, isolated) =>
+    withProgress(extra, () => runAgentTask(buildPrompt(isolatedTarget(target, isolated)), { cwd, backend, trackChanges: true }))
+  );
+  // The worktree diff already covers every change of an isolated run
+  const changes = worktree ? formatWorktree(worktree) : formatChanges(result.changes);
 
   if (result.success) {
-    return `♻️ Refactoring Complete\n\nGoals: ${refactorGoals.join(", ")}\n\n${result.output}`;
+    return `♻️ Refactoring Complete\n\nGoals: ${refactorGoals.join(", ")}\n\n${result.output}${changes}`;
   } else {
-    return `❌ Refactoring failed\n\nError: ${result.error}`;
+    return `❌ Refactoring failed\n\nError: ${result.error}${changes}`;
   }
 }
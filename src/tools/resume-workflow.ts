@@ -0,0 +1,52 @@
+import { z } from "zod";
+import { type ToolMetadata, type InferSchema } from "xmcp";
+import { type ToolExtra } from "../lib/progress";
+import { formatRunLine, isRunActive, loadRun } from "../lib/runs";
+import { runAgentWorkflow } from "./agent-workflow";
+
+export const schema = {
+  runId: z.string().describe("Run ID printed by agent_workflow (see the workflow-history resource)"),
+  backend: z.string().optional().describe("Agent backend to run the remaining steps on. Defaults to the run's backend."),
+};
+
+export const metadata: ToolMetadata = {
+  name: "resume_workflow",
+  description: `Continue a failed or interrupted agent_workflow run. Steps that succeeded
+are reused (their outputs still feed later prompts) and the workflow carries on
+from the step that failed, in the same worktree or on the same branch. The
+resumed run is recorded as a new run.`,
+  annotations: {
+    title: "Resume Agent Workflow",
+    readOnlyHint: false,
+    destructiveHint: true,
+    idempotentHint: false,
+  },
+};
+
+export default async function resumeWorkflow({ runId, backend }: InferSchema<typeof schema>, extra?: ToolExtra) {
+  const run = loadRun(runId);
+  if (!run) {
+    return `❌ Unknown workflow run: ${runId}`;
+  }
+  if (isRunActive(run.id)) {
+    return `❌ Run ${run.id} is still in progress`;
+  }
+  if (run.status === "completed") {
+    return `ℹ️ Run \`${run.id}\` already completed; nothing to resume\n\n${formatRunLine(run)}`;
+  }
+
+  return runAgentWorkflow({
+    workflow: run.workflow,
+    target: run.target,
+    options: {
+      autoCommit: Boolean(run.options.autoCommit),
+      createPR: Boolean(run.options.createPR),
+      deploy: Boolean(run.options.deploy),
+      rollbackOnFailure: run.options.rollbackOnFailure ?? "none",
+    },
+    inputs: run.inputs,
+    workingDirectory: run.workingDirectory,
+    backend: backend ?? run.backend,
+    isolation: run.isolation,
+  }, extra, run);
+}
@@ -1,11 +1,14 @@
 import { z } from "zod";
 import { type ToolMetadata, type InferSchema } from "xmcp";
-import { runClineTask } from "../lib/cline";
+import { runAgentTask } from "../lib/agent";
+import { withProgress, type ToolExtra } from "../lib/progress";
+import { checkWorkspaceTarget } from "../lib/workspace";
 
 export const schema = {
   target: z.string().optional().default(".").describe("File, directory, or git ref to review. Use '.' for current directory."),
   focusAreas: z.array(z.string()).optional().describe("Areas to focus on: 'security', 'performance', 'bugs', 'style'"),
   workingDirectory: z.string().optional().describe("The directory to work in."),
+  backend: z.string().optional().describe("Agent backend to run on (see cline_status). Defaults to the configured backend."),
 };
 
 export const metadata: ToolMetadata = {
@@ -20,7 +23,9 @@ Returns detailed analysis with suggestions for improvement.`,
   },
 };
 
-export default async function reviewCode({ target, focusAreas, workingDirectory }: InferSchema<typeof schema>) {
+export default async function reviewCode({ target, focusAreas, workingDirectory, backend }: InferSchema<typeof schema>, extra?: ToolExtra) {
+  checkWorkspaceTarget(target, workingDirectory);
+
   let prompt = `Review the code in ${target || "."}. Analyze for:
 - Potential bugs and logic errors
 - Security vulnerabilities
@@ -37,7 +42,7 @@ export default async function reviewCode({ target, focusAreas, workingDirectory
 3. Recommendations
 4. Overall assessment`;
 
-  const result = await runClineTask(prompt, { cwd: workingDirectory });
+  const result = await withProgress(extra, () => runAgentTask(prompt, { cwd: workingDirectory, backend }));
 
   if (result.success) {
     return `📝 Code Review Complete\n\n${result.output}`;
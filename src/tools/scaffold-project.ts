@@ -1,6 +1,8 @@
 import { z } from "zod";
 import { type ToolMetadata, type InferSchema } from "xmcp";
-import { runClineTask } from "../lib/cline";
+import { runAgentTask } from "../lib/agent";
+import { withProgress, type ToolExtra } from "../lib/progress";
+import { WorkspaceError } from "../lib/workspace";
 
 export const schema = {
   projectType: z.enum([
@@ -11,10 +13,11 @@ export const schema = {
     "mcp-server",
     "fullstack",
   ]).describe("Type of project to scaffold"),
-  name: z.string().describe("Project name"),
+  name: z.string().describe("Project name; the project is created in a folder of this name inside workingDirectory"),
   features: z.array(z.string()).optional()
     .describe("Additional features to include (e.g., 'auth', 'database', 'testing')"),
   workingDirectory: z.string().optional().describe("Parent directory for the new project"),
+  backend: z.string().optional().describe("Agent backend to run on (see cline_status). Defaults to the configured backend."),
 };
 
 export const metadata: ToolMetadata = {
@@ -88,8 +91,14 @@ export default async function scaffoldProject({
   projectType, 
   name, 
   features,
-  workingDirectory 
-}: InferSchema<typeof schema>) {
+  workingDirectory,
+  backend,
+}: InferSchema<typeof schema>, extra?: ToolExtra) {
+  // The project folder must stay inside the working directory
+  if (!name || /[\\/]/.test(name) || name.includes("..")) {
+    throw new WorkspaceError(`Project name "${name}" must be a plain folder name, without path separators or ".."`);
+  }
+
   const template = PROJECT_TEMPLATES[projectType];
   if (!template) {
     return `❌ Unknown project type: ${projectType}`;
@@ -111,9 +120,10 @@ ${features.map(f => `- ${f}`).join('\n')}`;
 Create all necessary files and folders. Make sure the project is immediately runnable.
 Include a README.md with setup instructions.`;
 
-  const result = await runClineTask(prompt, {
+  const result = await withProgress(extra, () => runAgentTask(prompt, {
     cwd: workingDirectory,
-  });
+    backend,
+  }));
 
   if (result.success) {
     return `🎉 Project "${name}" scaffolded successfully!
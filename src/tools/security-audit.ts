@@ -1,12 +1,15 @@
 import { z } from "zod";
 import { type ToolMetadata, type InferSchema } from "xmcp";
-import { runClineTask } from "../lib/cline";
+import { runAgentTask } from "../lib/agent";
+import { withProgress, type ToolExtra } from "../lib/progress";
+import { checkWorkspaceTarget } from "../lib/workspace";
 
 export const schema = {
   target: z.string().optional().default(".").describe("Directory to audit. Use '.' for current directory."),
   scanTypes: z.array(z.enum(["code", "dependencies", "secrets", "licenses"])).optional()
     .describe("Types of scans: 'code', 'dependencies', 'secrets', 'licenses'"),
   workingDirectory: z.string().optional().describe("The directory to work in."),
+  backend: z.string().optional().describe("Agent backend to run on (see cline_status). Defaults to the configured backend."),
 };
 
 export const metadata: ToolMetadata = {
@@ -21,7 +24,9 @@ exposed secrets, dependency issues, and provides a risk assessment.`,
   },
 };
 
-export default async function securityAudit({ target, scanTypes, workingDirectory }: InferSchema<typeof schema>) {
+export default async function securityAudit({ target, scanTypes, workingDirectory, backend }: InferSchema<typeof schema>, extra?: ToolExtra) {
+  checkWorkspaceTarget(target, workingDirectory);
+
   const types = scanTypes || ["code", "dependencies", "secrets"];
   
   const scanDescriptions = types.map((type) => {
@@ -51,7 +56,7 @@ At the end, provide:
 - Risk score (1-10)
 - Priority action items`;
 
-  const result = await runClineTask(prompt, { cwd: workingDirectory });
+  const result = await withProgress(extra, () => runAgentTask(prompt, { cwd: workingDirectory, backend }));
 
   if (result.success) {
     return `🔒 Security Audit Complete\n\nScanned: ${types.join(", ")}\n\n${result.output}`;
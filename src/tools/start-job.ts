@@ -0,0 +1,40 @@
+import { z } from "zod";
+import { type ToolMetadata, type InferSchema } from "xmcp";
+import { startJob } from "../lib/jobs";
+import { INVOCABLE_TOOLS, prepareToolCall } from "../lib/tool-registry";
+
+export const schema = {
+  tool: z.enum(INVOCABLE_TOOLS).describe("Tool to run in the background"),
+  arguments: z.record(z.unknown()).optional().default({})
+    .describe("Arguments for the tool, exactly as for a direct call"),
+};
+
+export const metadata: ToolMetadata = {
+  name: "start_job",
+  description: `Run any AgentMesh tool in the background and return a job ID immediately.
+Use this for long tasks (code_task, agent_workflow, scaffold_project...) that would otherwise
+exceed the client's request timeout. Poll with job_status, stop with cancel_job.`,
+  annotations: {
+    title: "Start Background Job",
+    readOnlyHint: false,
+    destructiveHint: true,
+    idempotentHint: false,
+  },
+};
+
+export default async function startJobTool({ tool, arguments: args }: InferSchema<typeof schema>) {
+  let run: () => Promise<string>;
+  try {
+    run = await prepareToolCall(tool, args ?? {});
+  } catch (error) {
+    return `❌ Could not start job\n\nError: ${(error as Error).message}`;
+  }
+
+  const job = startJob(tool, args ?? {}, run);
+
+  return `🚀 Job started: \`${job.id}\`
+
+Tool: ${tool}
+
+Poll with \`job_status\` (jobId: "${job.id}") or stop with \`cancel_job\`.`;
+}
@@ -1,6 +1,7 @@
 import { z } from "zod";
 import { type ToolMetadata, type InferSchema } from "xmcp";
 import { execa } from "execa";
+import { resolveWorkspacePath } from "../lib/workspace";
 
 export const schema = {
   action: z.enum(["deploy", "status", "logs", "promote", "rollback"])
@@ -31,7 +32,7 @@ export default async function vercelDeploy({
   environment,
   projectPath 
 }: InferSchema<typeof schema>) {
-  const cwd = projectPath || process.cwd();
+  const cwd = resolveWorkspacePath(projectPath || process.cwd());
 
   try {
     switch (action) {
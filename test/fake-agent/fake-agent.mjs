@@ -0,0 +1,62 @@
+#!/usr/bin/env node
+/**
+ * Deterministic stand-in for an agent CLI (Cline, Aider, Codex...)
+ *
+ * Behaviour is driven by environment variables so tests can point any backend
+ * at this script:
+ * - FAKE_AGENT_FIXTURE: file whose contents are replayed on stdout
+ * - FAKE_AGENT_LINE_DELAY_MS: pause between replayed lines
+ * - FAKE_AGENT_SLEEP_MS: pause before exiting (to trigger timeouts)
+ * - FAKE_AGENT_EXIT_CODE: exit code (default 0)
+ * - FAKE_AGENT_STDERR: text written to stderr
+ * - FAKE_AGENT_CAPTURE: file that each invocation appends a JSON record to
+ *   (argv, cwd, stdin and the contents of any file passed as an argument)
+ * - FAKE_AGENT_VERSION: version printed for `version` / `--version`
+ * - FAKE_AGENT_WRITES: JSON object of files (relative to cwd) to write, like an
+ *   agent editing the workspace
+ */
+
+import fs from "node:fs";
+import path from "node:path";
+
+const args = process.argv.slice(2);
+const env = process.env;
+const sleep = (ms) => new Promise((resolve) => setTimeout(resolve, ms));
+
+if (args[0] === "version" || args[0] === "--version") {
+  process.stdout.write(`${env.FAKE_AGENT_VERSION ?? "0.0.0-fake"}\n`);
+  process.exit(0);
+}
+
+let stdin = "";
+for await (const chunk of process.stdin) stdin += chunk;
+
+if (env.FAKE_AGENT_CAPTURE) {
+  const files = Object.fromEntries(
+    args.filter((arg) => arg.startsWith("/") && fs.statSync(arg, { throwIfNoEntry: false })?.isFile())
+      .map((file) => [file, fs.readFileSync(file, "utf8")])
+  );
+  fs.appendFileSync(env.FAKE_AGENT_CAPTURE, JSON.stringify({ argv: args, cwd: process.cwd(), stdin, files }) + "\n");
+}
+
+for (const [file, content] of Object.entries(JSON.parse(env.FAKE_AGENT_WRITES ?? "{}"))) {
+  fs.mkdirSync(path.dirname(path.resolve(file)), { recursive: true });
+  fs.writeFileSync(file, content);
+}
+
+if (env.FAKE_AGENT_STDERR) {
+  process.stderr.write(env.FAKE_AGENT_STDERR);
+}
+
+const fixture = env.FAKE_AGENT_FIXTURE ? fs.readFileSync(env.FAKE_AGENT_FIXTURE, "utf8") : "";
+const lineDelay = Number(env.FAKE_AGENT_LINE_DELAY_MS ?? 0);
+for (const line of fixture.split(/(?<=\n)/)) {
+  process.stdout.write(line);
+  if (lineDelay > 0) await sleep(lineDelay);
+}
+
+const sleepMs = Number(env.FAKE_AGENT_SLEEP_MS ?? 0);
+if (sleepMs > 0) await sleep(sleepMs);
+
+// Let stdout drain instead of calling process.exit()
+process.exitCode = Number(env.FAKE_AGENT_EXIT_CODE ?? 0);
@@ -0,0 +1,160 @@
+/**
+ * Fixture-replay harness around the fake agent executable
+ *
+ * `useFakeAgent()` writes a throwaway AgentMesh config whose `cline` backend
+ * points at `test/fake-agent/fake-agent.mjs`, so tools run end to end (process
+ * spawn, event parsing, formatting) without network access or credentials.
+ */
+
+import * as fs from "fs";
+import * as os from "os";
+import * as path from "path";
+import { z } from "zod";
+import { resetConfig, type BackendConfig } from "../../src/lib/config";
+
+export const FAKE_AGENT = path.resolve(__dirname, "../fake-agent/fake-agent.mjs");
+export const FIXTURES = path.resolve(__dirname, "../fixtures");
+
+/**
+ * Resolves a fixture file under `test/fixtures`
+ */
+export function fixture(name: string): string {
+  return path.join(FIXTURES, name);
+}
+
+export interface FakeAgentOptions {
+  /** Fixture replayed on stdout, relative to `test/fixtures` */
+  fixture?: string;
+  /** Exit code of the fake process */
+  exitCode?: number;
+  /** Text written to stderr */
+  stderr?: string;
+  /** Delay before the process exits */
+  sleepMs?: number;
+  /** Delay between replayed lines */
+  lineDelayMs?: number;
+  /** Files (relative to the agent's cwd) the fake agent writes */
+  writes?: Record<string, string>;
+  /** Additional backends to configure, all pointing at the fake agent */
+  extraBackends?: Record<string, Omit<BackendConfig, "path">>;
+  /** Allowed workspace roots (defaults to the current directory) */
+  workspaceRoots?: string[];
+  /** Other config keys written to the AgentMesh config */
+  config?: Record<string, unknown>;
+}
+
+/** Options that can change between invocations */
+export type ReplayOptions = Omit<FakeAgentOptions, "extraBackends" | "workspaceRoots" | "config">;
+
+export interface FakeAgentCall {
+  argv: string[];
+  cwd: string;
+  stdin: string;
+  /** Contents of files passed by absolute path in argv */
+  files: Record<string, string>;
+}
+
+export interface FakeAgentHandle {
+  /** Scratch directory holding the config and capture files */
+  dir: string;
+  /** Every invocation of the fake agent so far */
+  calls(): FakeAgentCall[];
+  /** The most recent invocation */
+  lastCall(): FakeAgentCall;
+  /** Prompt of the most recent invocation (stdin, prompt file, or last argument) */
+  lastPrompt(): string;
+  /** Reconfigures the replay for subsequent invocations */
+  set(options: ReplayOptions): void;
+  /** Removes the environment overrides */
+  restore(): void;
+}
+
+const ENV_KEYS = [
+  "AGENTMESH_CONFIG",
+  "AGENTMESH_WORKSPACE_ROOTS",
+  "FAKE_AGENT_FIXTURE",
+  "FAKE_AGENT_EXIT_CODE",
+  "FAKE_AGENT_STDERR",
+  "FAKE_AGENT_SLEEP_MS",
+  "FAKE_AGENT_LINE_DELAY_MS",
+  "FAKE_AGENT_CAPTURE",
+  "FAKE_AGENT_WRITES",
+];
+
+function setEnv(key: string, value: string | number | undefined): void {
+  if (value === undefined) delete process.env[key];
+  else process.env[key] = String(value);
+}
+
+/**
+ * Points the AgentMesh config at the fake agent
+ *
+ * @param options - Replay behaviour
+ * @returns Handle for inspecting invocations and restoring the environment
+ */
+export function useFakeAgent(options: FakeAgentOptions = {}): FakeAgentHandle {
+  const saved = Object.fromEntries(ENV_KEYS.map((key) => [key, process.env[key]]));
+  const dir = fs.mkdtempSync(path.join(os.tmpdir(), "agentmesh-fake-"));
+  const configPath = path.join(dir, "config.json");
+  const capturePath = path.join(dir, "calls.jsonl");
+
+  const backends: Record<string, BackendConfig> = { cline: { type: "cline", path: FAKE_AGENT } };
+  for (const [name, backend] of Object.entries(options.extraBackends ?? {})) {
+    backends[name] = { ...backend, path: FAKE_AGENT };
+  }
+  const config = { ...options.config, defaultBackend: "cline", backends, workspaceRoots: options.workspaceRoots };
+  fs.writeFileSync(configPath, JSON.stringify(config, null, 2));
+
+  const set = (replay: ReplayOptions) => {
+    setEnv("FAKE_AGENT_FIXTURE", replay.fixture ? fixture(replay.fixture) : undefined);
+    setEnv("FAKE_AGENT_EXIT_CODE", replay.exitCode);
+    setEnv("FAKE_AGENT_STDERR", replay.stderr);
+    setEnv("FAKE_AGENT_SLEEP_MS", replay.sleepMs);
+    setEnv("FAKE_AGENT_LINE_DELAY_MS", replay.lineDelayMs);
+    setEnv("FAKE_AGENT_WRITES", replay.writes ? JSON.stringify(replay.writes) : undefined);
+  };
+
+  setEnv("AGENTMESH_CONFIG", configPath);
+  setEnv("AGENTMESH_WORKSPACE_ROOTS", undefined);
+  setEnv("FAKE_AGENT_CAPTURE", capturePath);
+  set(options);
+  resetConfig();
+
+  const calls = (): FakeAgentCall[] =>
+    fs.existsSync(capturePath)
+      ? fs.readFileSync(capturePath, "utf8").trim().split("\n").filter(Boolean).map((line) => JSON.parse(line))
+      : [];
+
+  const lastCall = (): FakeAgentCall => {
+    const all = calls();
+    if (all.length === 0) throw new Error("fake agent was never invoked");
+    return all[all.length - 1];
+  };
+
+  return {
+    dir,
+    calls,
+    lastCall,
+    lastPrompt: () => {
+      const { argv, stdin, files } = lastCall();
+      return stdin || Object.values(files)[0] || argv[argv.length - 1];
+    },
+    set,
+    restore() {
+      for (const key of ENV_KEYS) setEnv(key, saved[key]);
+      resetConfig();
+      fs.rmSync(dir, { recursive: true, force: true });
+    },
+  };
+}
+
+/**
+ * Invokes a tool module the way xmcp does: arguments are parsed through the
+ * tool's zod schema first so defaults apply
+ */
+export async function callTool<S extends z.ZodRawShape, R>(
+  tool: { schema: S; default: (args: z.infer<z.ZodObject<S>>) => Promise<R> },
+  args: z.input<z.ZodObject<S>>
+): Promise<R> {
+  return tool.default(z.object(tool.schema).parse(args));
+}
@@ -0,0 +1,35 @@
+/**
+ * Throwaway git repositories for tests that exercise git-backed features
+ */
+
+import * as fs from "fs";
+import * as os from "os";
+import * as path from "path";
+import { execFileSync } from "child_process";
+
+/**
+ * Runs git synchronously in a directory
+ */
+export function git(cwd: string, ...args: string[]): string {
+  return execFileSync("git", args, { cwd, encoding: "utf8" }).trim();
+}
+
+/**
+ * Creates a repository with one commit containing `files`
+ *
+ * @param files - Initial files keyed by relative path
+ * @returns Canonical repository path
+ */
+export function createGitRepo(files: Record<string, string> = { "README.md": "# demo\n" }): string {
+  const dir = fs.realpathSync(fs.mkdtempSync(path.join(os.tmpdir(), "agentmesh-repo-")));
+  git(dir, "init", "-q", "-b", "main");
+  git(dir, "config", "user.name", "Test");
+  git(dir, "config", "user.email", "test@example.com");
+  for (const [file, content] of Object.entries(files)) {
+    fs.mkdirSync(path.dirname(path.join(dir, file)), { recursive: true });
+    fs.writeFileSync(path.join(dir, file), content);
+  }
+  git(dir, "add", "-A");
+  git(dir, "commit", "-q", "-m", "initial");
+  return dir;
+}
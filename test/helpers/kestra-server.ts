@@ -0,0 +1,212 @@
+/**
+ * In-process stand-in for the Kestra REST API
+ *
+ * `startKestraServer()` listens on a free localhost port and implements the
+ * endpoints AgentMesh uses (executions, logs and the flows API), so the Kestra
+ * client and `kestra_code_intel` run against real HTTP without a Kestra install.
+ */
+
+import * as http from "http";
+import type { AddressInfo } from "net";
+import { parse as parseYaml } from "yaml";
+
+export interface MockExecution {
+  id: string;
+  namespace: string;
+  flowId: string;
+  state: { current: string; startDate: string; endDate?: string };
+  outputs?: Record<string, unknown>;
+  /** Polls answered before the execution finishes */
+  pollsLeft: number;
+  /** State reached when it finishes */
+  finalState: string;
+}
+
+export interface MockFlow {
+  id: string;
+  namespace: string;
+  revision: number;
+  description?: string;
+  disabled: boolean;
+  source: string;
+}
+
+export interface MockLog {
+  level: string;
+  taskId?: string;
+  message: string;
+}
+
+export interface KestraServerOptions {
+  /** Required Authorization header; other requests get 401 */
+  authorization?: string;
+  /** GET polls an execution answers with RUNNING before finishing (default 1) */
+  polls?: number;
+  /** State executions finish in (default SUCCESS) */
+  finalState?: string;
+  /** Flow outputs of finished executions */
+  outputs?: Record<string, unknown>;
+  /** Logs of every execution */
+  logs?: MockLog[];
+  /** Flows stored before the server starts */
+  flows?: Array<Pick<MockFlow, "id" | "namespace"> & Partial<MockFlow>>;
+}
+
+export interface RecordedRequest {
+  method: string;
+  path: string;
+  authorization?: string;
+  body: string;
+}
+
+export interface KestraServer {
+  url: string;
+  requests: RecordedRequest[];
+  executions: Map<string, MockExecution>;
+  /** Stored flows, keyed by "namespace/id" */
+  flows: Map<string, MockFlow>;
+  close(): Promise<void>;
+}
+
+type Handler = (match: RegExpMatchArray, body: string, query: URLSearchParams) => [number, unknown];
+
+/** Checks flow YAML the way Kestra's validate endpoint does, roughly */
+function flowConstraints(source: string): { flow?: { id?: string; namespace?: string; description?: string }; constraints?: string } {
+  let flow: { id?: string; namespace?: string; description?: string; tasks?: unknown };
+  try {
+    flow = parseYaml(source) ?? {};
+  } catch (error) {
+    return { constraints: `Invalid YAML: ${(error as Error).message}` };
+  }
+  const missing = ["id", "namespace", "tasks"].filter((key) => !flow[key as keyof typeof flow]);
+  return { flow, constraints: missing.length ? missing.map((key) => `${key}: must not be null`).join("\n") : undefined };
+}
+
+/**
+ * Starts a mock Kestra server
+ *
+ * @param options - Auth requirement and execution behaviour
+ * @returns Server handle; call `close()` when done
+ */
+export async function startKestraServer(options: KestraServerOptions = {}): Promise<KestraServer> {
+  const requests: RecordedRequest[] = [];
+  const executions = new Map<string, MockExecution>();
+  const flows = new Map<string, MockFlow>();
+  let next = 1;
+
+  for (const flow of options.flows ?? []) {
+    flows.set(`${flow.namespace}/${flow.id}`, { revision: 1, disabled: false, source: "", ...flow });
+  }
+
+  const view = (execution: MockExecution) => ({
+    id: execution.id,
+    namespace: execution.namespace,
+    flowId: execution.flowId,
+    state: execution.state,
+    outputs: execution.outputs,
+  });
+
+  const flowView = ({ source, ...flow }: MockFlow) => flow;
+
+  const saveFlow = (body: string, existing?: MockFlow): [number, unknown] => {
+    const { flow, constraints } = flowConstraints(body);
+    if (constraints || !flow) return [422, { message: `Invalid entity: ${constraints}` }];
+    const key = `${flow.namespace}/${flow.id}`;
+    if (!existing && flows.has(key)) return [422, { message: `Invalid entity: Flow id already exists` }];
+    const saved: MockFlow = {
+      id: flow.id as string,
+      namespace: flow.namespace as string,
+      revision: existing ? existing.revision + 1 : 1,
+      description: flow.description,
+      disabled: false,
+      source: body,
+    };
+    flows.set(key, saved);
+    return [200, flowView(saved)];
+  };
+
+  const routes: Array<[string, RegExp, Handler]> = [
+    ["GET", /^\/api\/v1\/(?:[^/]+\/)?configs$/, () => [200, { version: "0.0.0-mock" }]],
+    ["POST", /^\/api\/v1\/(?:[^/]+\/)?executions\/webhook\/([^/]+)\/([^/]+)\/([^/]+)$/, (m) => {
+      const execution: MockExecution = {
+        id: `exec${next++}`,
+        namespace: m[1],
+        flowId: m[2],
+        state: { current: "RUNNING", startDate: "2026-01-01T10:00:00.000Z" },
+        pollsLeft: options.polls ?? 1,
+        finalState: options.finalState ?? "SUCCESS",
+      };
+      executions.set(execution.id, execution);
+      return [200, view(execution)];
+    }],
+    ["GET", /^\/api\/v1\/(?:[^/]+\/)?executions\/([^/]+)$/, (m) => {
+      const execution = executions.get(m[1]);
+      if (!execution) return [404, { message: `Execution ${m[1]} not found` }];
+      if (execution.state.current === "RUNNING" && execution.pollsLeft-- <= 0) {
+        execution.state = { ...execution.state, current: execution.finalState, endDate: "2026-01-01T10:00:12.500Z" };
+        execution.outputs = options.outputs;
+      }
+      return [200, view(execution)];
+    }],
+    ["GET", /^\/api\/v1\/(?:[^/]+\/)?logs\/([^/]+)$/, (m) =>
+      executions.has(m[1]) ? [200, options.logs ?? []] : [404, { message: "not found" }]],
+    ["POST", /^\/api\/v1\/(?:[^/]+\/)?flows\/validate$/, (_m, body) => {
+      const { flow, constraints } = flowConstraints(body);
+      return [200, [{ index: 0, flow: flow?.id, namespace: flow?.namespace, constraints }]];
+    }],
+    ["GET", /^\/api\/v1\/(?:[^/]+\/)?flows\/search$/, (_m, _body, query) => {
+      const namespace = query.get("namespace") ?? "";
+      const results = [...flows.values()]
+        .filter((f) => !namespace || f.namespace === namespace || f.namespace.startsWith(`${namespace}.`))
+        .sort((a, b) => a.id.localeCompare(b.id))
+        .map(flowView);
+      return [200, { results, total: results.length }];
+    }],
+    ["POST", /^\/api\/v1\/(?:[^/]+\/)?flows$/, (_m, body) => saveFlow(body)],
+    ["GET", /^\/api\/v1\/(?:[^/]+\/)?flows\/([^/]+)\/([^/]+)$/, (m) => {
+      const flow = flows.get(`${m[1]}/${m[2]}`);
+      return flow ? [200, flowView(flow)] : [404, { message: `Flow ${m[1]}.${m[2]} not found` }];
+    }],
+    ["PUT", /^\/api\/v1\/(?:[^/]+\/)?flows\/([^/]+)\/([^/]+)$/, (m, body) => {
+      const flow = flows.get(`${m[1]}/${m[2]}`);
+      return flow ? saveFlow(body, flow) : [404, { message: `Flow ${m[1]}.${m[2]} not found` }];
+    }],
+    ["DELETE", /^\/api\/v1\/(?:[^/]+\/)?flows\/([^/]+)\/([^/]+)$/, (m) =>
+      flows.delete(`${m[1]}/${m[2]}`) ? [204, undefined] : [404, { message: `Flow ${m[1]}.${m[2]} not found` }]],
+  ];
+
+  const server = http.createServer((req, res) => {
+    let body = "";
+    req.on("data", (chunk) => (body += chunk));
+    req.on("end", () => {
+      const url = new URL(req.url ?? "/", "http://localhost");
+      requests.push({ method: req.method ?? "GET", path: url.pathname, authorization: req.headers.authorization, body });
+
+      let status = 404;
+      let payload: unknown = { message: `No route for ${req.method} ${url.pathname}` };
+      if (options.authorization && req.headers.authorization !== options.authorization) {
+        [status, payload] = [401, { message: "Unauthorized" }];
+      } else {
+        for (const [method, pattern, handler] of routes) {
+          const match = url.pathname.match(pattern);
+          if (method === req.method && match) {
+            [status, payload] = handler(match, body, url.searchParams);
+            break;
+          }
+        }
+      }
+      res.writeHead(status, { "Content-Type": "application/json" });
+      res.end(payload === undefined ? undefined : JSON.stringify(payload));
+    });
+  });
+
+  await new Promise<void>((resolve) => server.listen(0, "127.0.0.1", resolve));
+  const { port } = server.address() as AddressInfo;
+  return {
+    url: `http://127.0.0.1:${port}`,
+    requests,
+    executions,
+    flows,
+    close: () => new Promise((resolve) => server.close(() => resolve())),
+  };
+}
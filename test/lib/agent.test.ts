@@ -0,0 +1,118 @@
+import { afterEach, describe, expect, it } from "vitest";
+import { getBackend, listBackends, runAgentTask } from "../../src/lib/agent";
+import { runAgentProcess } from "../../src/lib/agent-process";
+import { useFakeAgent, type FakeAgentHandle } from "../helpers/fake-agent";
+
+let fake: FakeAgentHandle;
+
+afterEach(() => fake?.restore());
+
+describe("runAgentTask", () => {
+  it("runs the default backend and returns the parsed answer and events", async () => {
+    fake = useFakeAgent({ fixture: "cline/completion.jsonl" });
+
+    const result = await runAgentTask("Fix the parser", { mode: "act" });
+
+    expect(result.success).toBe(true);
+    expect(result.backend).toBe("cline");
+    expect(result.output).toContain("{braces}");
+    expect(result.events.filter((e) => e.kind === "tool_use")).toHaveLength(2);
+    expect(fake.lastCall().argv).toEqual(
+      expect.arrayContaining(["-y", "-m", "act", "--output-format", "json", "--oneshot"])
+    );
+  });
+
+  it("reports non-zero exits with stderr and partial output", async () => {
+    fake = useFakeAgent({ fixture: "cline/plan.jsonl", exitCode: 2, stderr: "rate limited" });
+
+    const result = await runAgentTask("Plan it");
+
+    expect(result.success).toBe(false);
+    expect(result.error).toBe("rate limited");
+    expect(result.output).toContain("## Plan");
+  });
+
+  it("fails on timeout", async () => {
+    fake = useFakeAgent({ fixture: "cline/completion.jsonl", sleepMs: 5000 });
+
+    const result = await runAgentTask("Slow task", { timeout: 300 });
+
+    expect(result.success).toBe(false);
+    expect(result.error).toMatch(/timed out/i);
+  });
+
+  it("falls back to raw output when no answer can be parsed", async () => {
+    fake = useFakeAgent({ fixture: "cline/empty.jsonl" });
+
+    const result = await runAgentTask("Nothing to say");
+
+    expect(result).toMatchObject({ success: true, output: "Task completed (no output)", events: [] });
+  });
+
+  it("rejects unknown backends", async () => {
+    fake = useFakeAgent();
+    await expect(runAgentTask("x", { backend: "nope" })).rejects.toThrow(/Unknown agent backend "nope"/);
+  });
+});
+
+describe("backends", () => {
+  it("maps modes to aider and codex flags", async () => {
+    fake = useFakeAgent({
+      fixture: "cline/empty.jsonl",
+      extraBackends: { aider: { type: "aider", model: "sonnet" }, codex: { type: "codex" } },
+    });
+
+    await runAgentTask("Explain", { backend: "aider", mode: "plan" });
+    expect(fake.lastCall().argv).toEqual(
+      expect.arrayContaining(["--model", "sonnet", "--chat-mode", "ask", "--message-file"])
+    );
+    expect(fake.lastPrompt()).toBe("Explain");
+
+    await runAgentTask("Explain", { backend: "codex", mode: "plan" });
+    expect(fake.lastCall().argv.slice(0, 3)).toEqual(["exec", "--sandbox", "read-only"]);
+    expect(fake.lastCall().argv).toContain("-");
+    expect(fake.lastCall().stdin).toBe("Explain");
+  });
+
+  it("substitutes command templates per argument", async () => {
+    fake = useFakeAgent({
+      extraBackends: { custom: { type: "command", template: ["run", "--mode={mode}", "{prompt}"] } },
+    });
+
+    const result = await runAgentTask("do it", { backend: "custom", mode: "plan" });
+
+    expect(result.backend).toBe("custom");
+    expect(fake.lastCall().argv).toEqual(["run", "--mode=plan", "do it"]);
+    expect(fake.lastCall().stdin).toBe("");
+  });
+
+  it("writes the prompt to stdin when the template has no prompt placeholder", async () => {
+    fake = useFakeAgent({ extraBackends: { piped: { type: "command", template: ["--mode", "{mode}"] } } });
+
+    await runAgentTask("from stdin", { backend: "piped" });
+
+    expect(fake.lastCall().argv).toEqual(["--mode", "act"]);
+    expect(fake.lastCall().stdin).toBe("from stdin");
+  });
+
+  it("lists every configured backend and probes versions", async () => {
+    fake = useFakeAgent({ extraBackends: { second: { type: "cline" } } });
+
+    expect(listBackends().map((b) => b.name)).toEqual(["cline", "aider", "codex", "second"]);
+    expect(await getBackend("second").getVersion()).toBe("0.0.0-fake");
+  });
+});
+
+describe("runAgentProcess", () => {
+  it("keeps multibyte characters split across stdout chunks intact", async () => {
+    // "é" is 0xC3 0xA9; the two bytes are written as separate chunks
+    const script = "process.stdout.write(Buffer.from([0x63, 0x61, 0x66, 0xc3])); setTimeout(() => process.stdout.write(Buffer.from([0xa9, 0x0a])), 50);";
+    const chunks: string[] = [];
+
+    const result = await runAgentProcess({ label: "node", command: process.execPath, args: ["-e", script], onStdout: (c) => chunks.push(c) });
+
+    expect(result.success).toBe(true);
+    expect(chunks.join("")).toBe("café\n");
+    expect(chunks.join("")).not.toContain("�");
+  });
+});
@@ -0,0 +1,59 @@
+import { describe, expect, it, vi } from "vitest";
+import { requestApproval } from "../../src/lib/approval";
+import { withRunContext } from "../../src/lib/run-context";
+import type { ToolExtra } from "../../src/lib/progress";
+
+const request = { workflow: "full-feature", step: "Planning", summary: "1. Add a cache" };
+
+function elicitingExtra(answer: unknown): ToolExtra & { sendRequest: ReturnType<typeof vi.fn> } {
+  const sendRequest = vi.fn(async (_request: unknown, schema: { parse(value: unknown): unknown }) => {
+    if (answer instanceof Error) throw answer;
+    return schema.parse(answer);
+  });
+  return { sendRequest } as never;
+}
+
+describe("requestApproval", () => {
+  it("asks the client through elicitation", async () => {
+    const extra = elicitingExtra({ action: "accept", content: { decision: "approve" } });
+
+    expect(await requestApproval(request, extra)).toEqual({ approved: true, feedback: undefined });
+    const [sent, , options] = extra.sendRequest.mock.calls[0];
+    expect(sent.method).toBe("elicitation/create");
+    expect(sent.params.message).toContain('Workflow "full-feature" finished step "Planning"');
+    expect(sent.params.message).toContain("1. Add a cache");
+    expect(sent.params.requestedSchema.required).toEqual(["decision"]);
+    expect(options.timeout).toBeGreaterThan(60000);
+  });
+
+  it("passes rejections and feedback through", async () => {
+    const extra = elicitingExtra({ action: "accept", content: { decision: "reject", feedback: " Use Redis " } });
+
+    expect(await requestApproval(request, extra)).toEqual({ approved: false, feedback: "Use Redis" });
+  });
+
+  it.each([
+    ["decline", "the reviewer declined"],
+    ["cancel", "the reviewer dismissed the approval request"],
+  ])("treats %s as not approved", async (action, reason) => {
+    expect(await requestApproval(request, elicitingExtra({ action }))).toEqual({ approved: false, reason });
+  });
+
+  it("does not approve when the client has no elicitation", async () => {
+    const unsupported = Object.assign(new Error("Method not found"), { code: -32601 });
+
+    expect(await requestApproval(request, elicitingExtra(unsupported))).toMatchObject({ approved: false, reason: expect.stringContaining("start_job") });
+    expect(await requestApproval(request)).toMatchObject({ approved: false, reason: expect.stringContaining("approve_job") });
+  });
+
+  it("prefers the handler of the surrounding run context", async () => {
+    const extra = elicitingExtra({ action: "decline" });
+    const onApproval = vi.fn(async () => ({ approved: true }));
+
+    const decision = await withRunContext({ onApproval }, () => requestApproval(request, extra));
+
+    expect(decision).toEqual({ approved: true });
+    expect(onApproval).toHaveBeenCalledWith(request);
+    expect(extra.sendRequest).not.toHaveBeenCalled();
+  });
+});
@@ -0,0 +1,78 @@
+import * as fs from "fs";
+import * as os from "os";
+import * as path from "path";
+import { afterEach, describe, expect, it } from "vitest";
+import { formatChanges, MAX_SNAPSHOT_FILES, trackChanges } from "../../src/lib/changes";
+import { createGitRepo, git } from "../helpers/git-repo";
+
+let dir: string;
+
+function edit(files: Record<string, string | null>) {
+  for (const [file, content] of Object.entries(files)) {
+    const full = path.join(dir, file);
+    if (content === null) fs.rmSync(full);
+    else {
+      fs.mkdirSync(path.dirname(full), { recursive: true });
+      fs.writeFileSync(full, content);
+    }
+  }
+}
+
+describe("trackChanges", () => {
+  afterEach(() => fs.rmSync(dir, { recursive: true, force: true }));
+
+  it("reports git changes without touching the real index", async () => {
+    dir = createGitRepo({ "a.txt": "a\n", "b.txt": "b\n", ".gitignore": "dist/\n" });
+    edit({ "staged.txt": "already staged\n" });
+    git(dir, "add", "staged.txt");
+
+    const { result, changes } = await trackChanges(dir, async () => {
+      edit({ "a.txt": "a2\n", "b.txt": null, "src/new.ts": "export {};\n", "dist/out.js": "ignored" });
+      return "done";
+    });
+
+    expect(result).toBe("done");
+    expect(changes).toMatchObject({ added: ["src/new.ts"], modified: ["a.txt"], deleted: ["b.txt"] });
+    expect(changes!.diff).toContain("-a\n+a2");
+    expect(changes!.diff).not.toContain("staged");
+    expect(git(dir, "status", "--porcelain")).toBe([" M a.txt", " D b.txt", "A  staged.txt", "?? src/"].join("\n"));
+  });
+
+  it("hashes files outside git and builds a unified diff", async () => {
+    dir = fs.realpathSync(fs.mkdtempSync(path.join(os.tmpdir(), "agentmesh-plain-")));
+    edit({ "keep.txt": "1\n2\n3\n", "gone.txt": "bye\n", "node_modules/x.js": "skip" });
+
+    const { changes } = await trackChanges(dir, async () => {
+      edit({ "keep.txt": "1\nTWO\n3\n", "gone.txt": null, "hi.txt": "hello\n", "node_modules/x.js": "changed" });
+    });
+
+    expect(changes).toMatchObject({ added: ["hi.txt"], modified: ["keep.txt"], deleted: ["gone.txt"] });
+    expect(changes!.diff).toContain("--- a/keep.txt\n+++ b/keep.txt\n@@ -1,3 +1,3 @@\n 1\n-2\n+TWO\n 3");
+    expect(changes!.diff).toContain("--- /dev/null\n+++ b/hi.txt\n@@ -0,0 +1,1 @@\n+hello");
+  });
+
+  it("skips tracking when the directory grows past the file limit during the run", async () => {
+    dir = fs.realpathSync(fs.mkdtempSync(path.join(os.tmpdir(), "agentmesh-plain-")));
+    fs.mkdirSync(path.join(dir, "many"));
+    for (let i = 0; i < MAX_SNAPSHOT_FILES; i++) fs.writeFileSync(path.join(dir, "many", `${i}.txt`), "");
+
+    const { result, changes } = await trackChanges(dir, async () => {
+      edit({ "one-too-many.txt": "x\n" });
+      return "done";
+    });
+
+    expect(result).toBe("done");
+    expect(changes).toBeUndefined();
+  }, 60000);
+});
+
+describe("formatChanges", () => {
+  it("lists files by kind and says when nothing changed", () => {
+    const text = formatChanges({ added: ["n.ts"], modified: ["m.ts"], deleted: ["d.ts"], diff: "+x" });
+
+    expect(text).toContain("### Changed Files (3)");
+    expect(text).toContain("- ➕ n.ts\n- ✏️ m.ts\n- 🗑️ d.ts");
+    expect(formatChanges({ added: [], modified: [], deleted: [], diff: "" })).toContain("No files changed.");
+    expect(formatChanges(undefined)).toBe("");
+  });
+});
@@ -0,0 +1,77 @@
+import * as fs from "fs";
+import * as path from "path";
+import { afterEach, describe, expect, it } from "vitest";
+import { createCheckpoint, dropCheckpoints, formatRollback, restoreCheckpoint } from "../../src/lib/checkpoint";
+import { createGitRepo, git } from "../helpers/git-repo";
+
+let repo: string;
+
+afterEach(() => fs.rmSync(repo, { recursive: true, force: true }));
+
+const refs = () => git(repo, "for-each-ref", "--format=%(refname)", "refs/agentmesh/").split("\n").filter(Boolean);
+
+describe("checkpoints", () => {
+  it("restores added, modified and deleted files without touching the index", async () => {
+    repo = createGitRepo({ "a.ts": "a\n", "b.ts": "b\n" });
+    fs.writeFileSync(path.join(repo, "wip.ts"), "mine\n");
+    git(repo, "add", "wip.ts");
+    const checkpoint = await createCheckpoint(repo, "run/1", "step 1 (Fix)");
+
+    fs.writeFileSync(path.join(repo, "a.ts"), "changed\n");
+    fs.rmSync(path.join(repo, "b.ts"));
+    fs.mkdirSync(path.join(repo, "src"));
+    fs.writeFileSync(path.join(repo, "src", "new.ts"), "new\n");
+    const report = await restoreCheckpoint(checkpoint, "run/discarded");
+
+    expect(report).toMatchObject({ removed: ["src/new.ts"], reverted: ["a.ts"], restored: ["b.ts"], commits: [] });
+    expect(fs.readFileSync(path.join(repo, "a.ts"), "utf8")).toBe("a\n");
+    expect(fs.readFileSync(path.join(repo, "b.ts"), "utf8")).toBe("b\n");
+    expect(fs.existsSync(path.join(repo, "src", "new.ts"))).toBe(false);
+    expect(git(repo, "status", "--porcelain")).toBe("A  wip.ts");
+    expect(git(repo, "show", `${report.discarded.commit}:a.ts`)).toBe("changed");
+    expect(formatRollback(report)).toContain("- 🗑️ src/new.ts (added, removed)\n- ↩️ a.ts (modified, reverted)\n- ♻️ b.ts (deleted, restored)\n");
+  });
+
+  it("moves HEAD back over commits made after the checkpoint", async () => {
+    repo = createGitRepo({ "a.ts": "a\n" });
+    const base = git(repo, "rev-parse", "HEAD");
+    const checkpoint = await createCheckpoint(repo, "run/start", "the workflow");
+
+    fs.writeFileSync(path.join(repo, "a.ts"), "step 1\n");
+    git(repo, "commit", "-q", "-am", "step 1");
+    const report = await restoreCheckpoint(checkpoint, "run/discarded");
+
+    expect(git(repo, "rev-parse", "HEAD")).toBe(base);
+    expect(git(repo, "status", "--porcelain")).toBe("");
+    expect(report.commits).toEqual([expect.stringMatching(/^[0-9a-f]+ step 1$/)]);
+    expect(formatRollback(report)).toContain(`💾 The discarded state is kept at \`refs/agentmesh/checkpoints/run/discarded\``);
+  });
+
+  it("undoes every commit when the checkpoint was taken before the first one", async () => {
+    repo = createGitRepo();
+    git(repo, "update-ref", "-d", "HEAD");
+    const checkpoint = await createCheckpoint(repo, "run/start", "the workflow");
+
+    fs.writeFileSync(path.join(repo, "a.ts"), "step 1\n");
+    git(repo, "add", "-A");
+    git(repo, "commit", "-q", "-m", "step 1");
+    const report = await restoreCheckpoint(checkpoint, "run/discarded");
+
+    expect(report).toMatchObject({ removed: ["a.ts"], commits: [expect.stringMatching(/^[0-9a-f]+ step 1$/)] });
+    expect(git(repo, "for-each-ref", "refs/heads/")).toBe("");
+    expect(git(repo, "status", "--porcelain")).toBe("?? README.md");
+    expect(formatRollback(report)).toContain("Commits undone:\n- ");
+  });
+
+  it("keeps no refs when there is nothing to roll back", async () => {
+    repo = createGitRepo();
+    const checkpoint = await createCheckpoint(repo, "run/1", "step 1 (Plan)");
+
+    const report = await restoreCheckpoint(checkpoint, "run/discarded");
+    expect(formatRollback(report)).toContain("No files needed reverting.");
+    expect(refs()).toEqual(["refs/agentmesh/checkpoints/run/1"]);
+
+    await dropCheckpoints([checkpoint]);
+    expect(refs()).toEqual([]);
+  });
+});
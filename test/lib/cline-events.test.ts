@@ -0,0 +1,66 @@
+import * as fs from "fs";
+import { describe, expect, it } from "vitest";
+import { ClineEventParser, extractAnswer, parseClineOutput } from "../../src/lib/cline-events";
+import { fixture } from "../helpers/fake-agent";
+
+const read = (name: string) => fs.readFileSync(fixture(name), "utf8");
+
+describe("ClineEventParser", () => {
+  it("types every event and unescapes text", () => {
+    const { events, malformed } = parseClineOutput(read("cline/completion.jsonl"));
+
+    expect(malformed).toEqual([]);
+    expect(events.map((e) => e.kind)).toEqual([
+      "other", "text", "tool_use", "tool_use", "command", "command_output", "completion",
+    ]);
+    expect(events[1].text).toBe('I\'ll start by reading the "main" entry point.');
+    expect(events[3]).toMatchObject({ tool: "editedExistingFile", path: "src/index.ts" });
+    expect(events[4].text).toBe("npm test");
+  });
+
+  it("keeps completion text containing braces, quotes and newlines intact", () => {
+    const { events } = parseClineOutput(read("cline/completion.jsonl"));
+    expect(extractAnswer(events)).toBe('Fixed `parse()` so that {braces} and "quotes" survive.\nAll tests pass.');
+  });
+
+  it("extracts the nested plan-mode response", () => {
+    const { events } = parseClineOutput(read("cline/plan.jsonl"));
+    expect(extractAnswer(events)).toBe('## Plan\n1. Add a "parser" module\n2. Wire it in');
+  });
+
+  it("collapses partial updates of one message", () => {
+    const { events } = parseClineOutput(read("cline/partial.jsonl"));
+    expect(events).toHaveLength(1);
+    expect(events[0].text).toBe("Work in progress is now finished.");
+  });
+
+  it("parses objects split across chunks and lines", () => {
+    const text = read("cline/pretty.json");
+    const seen: string[] = [];
+    const parser = new ClineEventParser((event) => seen.push(event.kind));
+    for (let i = 0; i < text.length; i += 7) parser.push(text.slice(i, i + 7));
+    const events = parser.end();
+
+    expect(seen).toEqual(["completion"]);
+    expect(events[0].text).toBe("Pretty-printed\nmulti-line answer with a } brace.");
+  });
+
+  it("skips noise and truncated objects, then resynchronises", () => {
+    const { events, malformed } = parseClineOutput(read("cline/malformed.jsonl"));
+    expect(malformed).toHaveLength(1);
+    expect(extractAnswer(events)).toBe("Recovered answer after noise.");
+  });
+
+  it("reports an unterminated trailing object as malformed", () => {
+    const parser = new ClineEventParser();
+    parser.push('{"type":"say","say":"text","text":"cut');
+    expect(parser.end()).toEqual([]);
+    expect(parser.malformed).toHaveLength(1);
+  });
+});
+
+describe("extractAnswer", () => {
+  it("returns undefined when there is nothing to report", () => {
+    expect(extractAnswer([])).toBeUndefined();
+  });
+});
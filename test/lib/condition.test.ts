@@ -0,0 +1,47 @@
+import { describe, expect, it } from "vitest";
+import { ConditionError, conditionReferences, evaluateCondition, parseCondition } from "../../src/lib/condition";
+import type { TemplateContext } from "../../src/lib/template";
+
+const context: TemplateContext = {
+  target: "src",
+  inputs: { deploy: "false", env: "staging" },
+  steps: {
+    scan: { status: "succeeded", output: "Found 2 CRITICAL issues", changedFiles: [] },
+    fix: { status: "failed", output: "", changedFiles: ["src/a.ts", "src/b.ts"] },
+  },
+  envAllowlist: [],
+};
+
+const check = (source: string) => evaluateCondition(parseCondition(source), context);
+
+describe("conditions", () => {
+  it.each([
+    ['steps.scan.status == "succeeded"', true],
+    ["steps.fix.status != 'failed'", false],
+    ['steps.scan.output contains "CRITICAL"', true],
+    ['steps.fix.changedFiles contains "src/a.ts"', true],
+    ['steps.fix.changedFiles contains "src/a"', false],
+    ["inputs.deploy", false],
+    ["!inputs.deploy && inputs.env", true],
+    ['inputs.env == "prod" || (steps.scan.status == "succeeded" && !steps.scan.changedFiles)', true],
+    ["true && !false", true],
+  ])("%s is %s", (source, expected) => {
+    expect(check(source)).toBe(expected);
+  });
+
+  it("lists references for load-time validation", () => {
+    const refs = conditionReferences(parseCondition('steps.scan.output contains inputs.env || target == "x"'));
+    expect(refs.map((r) => r.raw)).toEqual(["steps.scan.output", "inputs.env", "target"]);
+  });
+
+  it.each([
+    ["steps.scan.status ==", /Unexpected end/],
+    ["(inputs.env", /Missing "\)"/],
+    ["process.exit(1)", /Unknown name "process.exit"/],
+    ["inputs.env; rm -rf", /Unexpected ";"/],
+    ["inputs.env inputs.deploy", /Unexpected "inputs.deploy"/],
+  ])("rejects %s", (source, message) => {
+    expect(() => parseCondition(source)).toThrow(ConditionError);
+    expect(() => parseCondition(source)).toThrow(message);
+  });
+});
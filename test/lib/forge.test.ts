@@ -0,0 +1,78 @@
+import * as fs from "fs";
+import * as os from "os";
+import * as path from "path";
+import { afterEach, beforeEach, describe, expect, it, vi } from "vitest";
+import { GitHubForge, LocalForge, parseGitHubRemote } from "../../src/lib/forge";
+import { createGitRepo, git } from "../helpers/git-repo";
+
+describe("parseGitHubRemote", () => {
+  it("understands SSH and HTTPS remotes", () => {
+    expect(parseGitHubRemote("git@github.com:acme/widgets.git")).toEqual({ owner: "acme", repo: "widgets" });
+    expect(parseGitHubRemote("https://github.com/acme/widgets")).toEqual({ owner: "acme", repo: "widgets" });
+    expect(parseGitHubRemote("https://token@github.com/acme/widgets.git")).toEqual({ owner: "acme", repo: "widgets" });
+    expect(parseGitHubRemote("/srv/git/widgets")).toBeUndefined();
+  });
+});
+
+describe("forges", () => {
+  let repo: string;
+  let bare: string;
+  let savedToken: string | undefined;
+
+  beforeEach(() => {
+    savedToken = process.env.GITHUB_TOKEN;
+    repo = createGitRepo();
+    bare = fs.mkdtempSync(path.join(os.tmpdir(), "agentmesh-bare-"));
+    git(bare, "init", "-q", "--bare");
+    git(repo, "remote", "add", "origin", "https://github.com/acme/widgets.git");
+    git(repo, "remote", "set-url", "--push", "origin", bare);
+    git(repo, "checkout", "-q", "-b", "agentmesh/fix");
+  });
+
+  afterEach(() => {
+    vi.unstubAllGlobals();
+    if (savedToken === undefined) delete process.env.GITHUB_TOKEN;
+    else process.env.GITHUB_TOKEN = savedToken;
+    fs.rmSync(repo, { recursive: true, force: true });
+    fs.rmSync(bare, { recursive: true, force: true });
+  });
+
+  it("GitHub pushes the branch and opens the PR", async () => {
+    process.env.GITHUB_TOKEN = "t0ken";
+    const fetchMock = vi.fn(async () => new Response(
+      JSON.stringify({ number: 7, html_url: "https://github.com/acme/widgets/pull/7" }), { status: 201 }
+    ));
+    vi.stubGlobal("fetch", fetchMock);
+
+    const pr = await new GitHubForge().createPullRequest({ dir: repo, head: "agentmesh/fix", base: "main", title: "Fix", body: "Body" });
+
+    expect(pr).toEqual({ number: 7, url: "https://github.com/acme/widgets/pull/7" });
+    expect(git(bare, "rev-parse", "agentmesh/fix")).toBe(git(repo, "rev-parse", "HEAD"));
+    const [url, init] = fetchMock.mock.calls[0] as unknown as [string, RequestInit];
+    expect(url).toBe("https://api.github.com/repos/acme/widgets/pulls");
+    expect((init.headers as Record<string, string>).Authorization).toBe("Bearer t0ken");
+    expect(JSON.parse(init.body as string)).toEqual({ title: "Fix", body: "Body", head: "agentmesh/fix", base: "main" });
+  });
+
+  it("GitHub reports API errors and a missing token", async () => {
+    delete process.env.GITHUB_TOKEN;
+    delete process.env.GH_TOKEN;
+    const spec = { dir: repo, head: "agentmesh/fix", base: "main", title: "Fix", body: "" };
+    await expect(new GitHubForge().createPullRequest(spec)).rejects.toThrow(/GITHUB_TOKEN is not set/);
+
+    process.env.GITHUB_TOKEN = "t0ken";
+    vi.stubGlobal("fetch", vi.fn(async () => new Response(JSON.stringify({ message: "Validation Failed" }), { status: 422 })));
+    await expect(new GitHubForge().createPullRequest(spec)).rejects.toThrow("GitHub refused the pull request: Validation Failed");
+  });
+
+  it("local forge records numbered pull requests", async () => {
+    const forge = new LocalForge({ type: "local", path: path.join(repo, ".git", "prs.json") });
+
+    await forge.createPullRequest({ dir: repo, head: "agentmesh/fix", base: "main", title: "One", body: "" });
+    const second = await forge.createPullRequest({ dir: repo, head: "agentmesh/fix", base: "main", title: "Two", body: "" });
+
+    expect(second).toEqual({ number: 2, url: `local://${path.basename(repo)}/pull/2` });
+    expect(forge.list().map((pr) => pr.title)).toEqual(["One", "Two"]);
+    await expect(forge.createPullRequest({ dir: repo, head: "nope", base: "main", title: "", body: "" })).rejects.toThrow();
+  });
+});
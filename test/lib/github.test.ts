@@ -0,0 +1,128 @@
+import * as fs from "fs";
+import * as os from "os";
+import * as path from "path";
+import { afterEach, beforeEach, describe, expect, it, vi } from "vitest";
+import { ForgeApiError, nextPageUrl, RateLimitError } from "../../src/lib/forge-api";
+import { GitHubClient, parseGitHubUrl } from "../../src/lib/github";
+import { fixture } from "../helpers/fake-agent";
+
+const ENV_KEYS = ["GITHUB_TOKEN", "GH_TOKEN", "AGENTMESH_GITHUB_FIXTURES"];
+const savedEnv = ENV_KEYS.map((key) => process.env[key]);
+
+beforeEach(() => ENV_KEYS.forEach((key) => delete process.env[key]));
+
+afterEach(() => {
+  vi.unstubAllGlobals();
+  ENV_KEYS.forEach((key, i) => {
+    if (savedEnv[i] === undefined) delete process.env[key];
+    else process.env[key] = savedEnv[i];
+  });
+});
+
+const recorded = fixture("github/recorded");
+
+/** Answers fetch with the given responses in order */
+function stubFetch(...responses: Array<() => Response>) {
+  const fetchMock = vi.fn(async () => {
+    const next = responses.shift();
+    if (!next) throw new TypeError("fetch failed");
+    return next();
+  });
+  vi.stubGlobal("fetch", fetchMock);
+  return fetchMock;
+}
+
+const json = (body: unknown, status = 200, headers: Record<string, string> = {}) =>
+  () => new Response(JSON.stringify(body), { status, headers });
+
+describe("GitHub URLs", () => {
+  it("parses repository URLs and Link headers", () => {
+    expect(parseGitHubUrl("https://github.com/acme/widgets")).toEqual({ owner: "acme", repo: "widgets" });
+    expect(parseGitHubUrl("https://github.com/acme/widgets.git")).toEqual({ owner: "acme", repo: "widgets" });
+    expect(parseGitHubUrl("https://github.com/acme/widgets/issues?q=bug")).toEqual({ owner: "acme", repo: "widgets" });
+    expect(parseGitHubUrl("https://gitlab.com/acme/widgets")).toBeUndefined();
+
+    expect(nextPageUrl('<https://api.github.com/x?page=2>; rel="next", <https://api.github.com/x?page=5>; rel="last"'))
+      .toBe("https://api.github.com/x?page=2");
+    expect(nextPageUrl('<https://api.github.com/x?page=1>; rel="prev"')).toBeUndefined();
+  });
+});
+
+describe("GitHubClient", () => {
+  it("replays recorded fixtures, following pagination and dropping pull requests from issues", async () => {
+    stubFetch();
+    const client = new GitHubClient({ fixtures: recorded });
+
+    expect(await client.getRepo("acme", "widgets")).toMatchObject({ owner: "acme", fullName: "acme/widgets", stars: 42, language: "TypeScript" });
+    const issues = await client.listIssues("acme", "widgets");
+    expect(issues.map((i) => i.number)).toEqual([1, 2, 3]);
+    expect(issues[1]).toEqual({
+      number: 2,
+      title: "Token leaked in logs",
+      state: "open",
+      labels: ["security"],
+      author: "bob",
+      createdAt: "2026-09-02T00:00:00Z",
+      url: "https://github.com/acme/widgets/issues/2",
+    });
+    expect(await client.listPullRequests("acme", "widgets")).toMatchObject([{ number: 4, draft: false, author: "dave" }]);
+
+    await expect(client.getRepo("acme", "gone")).rejects.toThrow("GitHub GET /repos/acme/gone failed (404): Not Found");
+    await expect(client.getRepo("acme", "unknown")).rejects.toThrow("No GitHub fixture for GET /repos/acme/unknown");
+  });
+
+  it("records responses in fixture mode so they replay offline", async () => {
+    const dir = fs.mkdtempSync(path.join(os.tmpdir(), "agentmesh-github-"));
+    process.env.GITHUB_TOKEN = "t0ken";
+    const fetchMock = stubFetch(json([{ number: 9, title: "Draft", state: "open", draft: true, created_at: "2026-09-05T00:00:00Z", html_url: "u" }]));
+
+    const live = await new GitHubClient({ fixtures: dir, record: true }).listPullRequests("acme", "widgets", { maxItems: 10 });
+
+    const [url, init] = fetchMock.mock.calls[0] as unknown as [string, RequestInit];
+    expect(url).toBe("https://api.github.com/repos/acme/widgets/pulls?state=open&per_page=10");
+    expect((init.headers as Record<string, string>).Authorization).toBe("Bearer t0ken");
+    expect(fs.readdirSync(dir)).toEqual(["repos_acme_widgets_pulls_state_open_per_page_10.json"]);
+
+    process.env.AGENTMESH_GITHUB_FIXTURES = dir;
+    expect(await new GitHubClient().listPullRequests("acme", "widgets", { maxItems: 10 })).toEqual(live);
+    expect(fetchMock).toHaveBeenCalledTimes(1);
+  });
+
+  it("waits out a short rate limit and retries server errors", async () => {
+    const fetchMock = stubFetch(
+      json({ message: "secondary rate limit" }, 403, { "retry-after": "0" }),
+      json({ message: "Bad Gateway" }, 502),
+      json({ name: "widgets", full_name: "acme/widgets", updated_at: "2026-09-30T12:00:00Z", html_url: "u" }),
+    );
+
+    const repo = await new GitHubClient({}, { retryDelay: 1 }).getRepo("acme", "widgets");
+
+    expect(repo.fullName).toBe("acme/widgets");
+    expect(fetchMock).toHaveBeenCalledTimes(3);
+  });
+
+  it("sends a POST only once after a server or network error", async () => {
+    const fetchMock = stubFetch(json({ message: "Bad Gateway" }, 502), json({ number: 2, html_url: "u" }, 201));
+    const client = new GitHubClient({}, { retryDelay: 1 });
+    const spec = { title: "t", body: "b", head: "agentmesh/x", base: "main" };
+
+    await expect(client.createPullRequest("acme", "widgets", spec)).rejects.toMatchObject({ status: 502 });
+    expect(fetchMock).toHaveBeenCalledTimes(1);
+
+    stubFetch();
+    await expect(client.createPullRequest("acme", "widgets", spec)).rejects.toThrow("fetch failed");
+  });
+
+  it("fails clearly when the rate limit resets too late", async () => {
+    const reset = Math.floor(Date.now() / 1000) + 3600;
+    stubFetch(json({ message: "API rate limit exceeded" }, 403, { "x-ratelimit-remaining": "0", "x-ratelimit-reset": String(reset) }));
+
+    const error = await new GitHubClient().getRepo("acme", "widgets").catch((e) => e);
+
+    expect(error).toBeInstanceOf(RateLimitError);
+    expect(error).toBeInstanceOf(ForgeApiError);
+    expect(error.message).toBe(
+      `GitHub rate limit exceeded; it resets at ${new Date(reset * 1000).toISOString()}. Set GITHUB_TOKEN (or github.token in the config) for a higher limit`,
+    );
+  });
+});
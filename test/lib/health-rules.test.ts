@@ -0,0 +1,132 @@
+import * as fs from "fs";
+import * as path from "path";
+import { afterEach, beforeEach, describe, expect, it } from "vitest";
+import {
+  evaluateHealthRules,
+  explainHealthRules,
+  formatMetrics,
+  getHealthRulesPath,
+  loadHealthRules,
+  parseSummary,
+} from "../../src/lib/health-rules";
+import { useFakeAgent, type FakeAgentHandle } from "../helpers/fake-agent";
+
+let fake: FakeAgentHandle;
+
+beforeEach(() => {
+  fake = useFakeAgent();
+});
+
+afterEach(() => fake.restore());
+
+function writeRules(yaml: string) {
+  fs.writeFileSync(getHealthRulesPath(), yaml);
+}
+
+describe("evaluateHealthRules", () => {
+  it("takes the weight of every fired rule off the score", () => {
+    const evaluation = evaluateHealthRules(loadHealthRules(), {
+      metrics: { securityIssues: 1, bugReports: 2, pendingPRs: 5, openIssues: 30 },
+    });
+
+    expect(evaluation.fired.map((r) => r.rule.id)).toEqual(["security-issues", "bug-reports", "review-backlog", "issue-backlog"]);
+    expect(evaluation.fired.map((r) => r.contribution)).toEqual([40, 20, 10, 10]);
+    expect(evaluation.score).toBe(20);
+    expect(evaluation.health).toBe("CRITICAL");
+    expect(evaluation.fired[1].item).toBe("2 bug(s) reported");
+  });
+
+  it("rates health from the score", () => {
+    const rules = loadHealthRules();
+
+    expect(evaluateHealthRules(rules, { metrics: { bugReports: 0, pendingPRs: 1 } })).toMatchObject({ score: 100, health: "HEALTHY" });
+    expect(evaluateHealthRules(rules, { metrics: { bugReports: 1 } })).toMatchObject({ score: 80, health: "MODERATE" });
+    expect(evaluateHealthRules(rules, { metrics: { bugReports: 1, failedCiRuns: 3, ciRuns: 5 } }))
+      .toMatchObject({ score: 60, health: "NEEDS_ATTENTION" });
+  });
+
+  it("matches mentions as whole words only", () => {
+    const rules = loadHealthRules();
+    const tools = (text: string) => evaluateHealthRules(rules, { metrics: {}, text }).fired.map((r) => r.rule.tool);
+
+    expect(tools("Needs improvement; see the approval process")).toEqual([]);
+    expect(tools("Two open PRs need a review")).toEqual(["review_code"]);
+    expect(tools("No security vulnerabilities found, no bugs or crashes")).toEqual([]);
+  });
+
+  it("lets measured metrics decide over mentions", () => {
+    const evaluation = evaluateHealthRules(loadHealthRules(), parseSummary("Pending PRs: 1\nTwo open PRs need a review"));
+
+    expect(evaluation.fired).toEqual([]);
+    expect(evaluation.results.find((r) => r.rule.id === "review-backlog")?.explanation)
+      .toBe('(pendingPRs = 1 > 3) or (mentions "review", "pull request", "pull requests", "PRs" (metrics decide))');
+  });
+
+  it("leaves rules on unmeasured metrics undecided", () => {
+    const evaluation = evaluateHealthRules(loadHealthRules(), { metrics: { bugReports: 1 } });
+    const explanation = explainHealthRules(evaluation);
+
+    expect(explanation).toContain("## Rules (built-in)\nHealth Score: 80/100 (1 of 9 rules fired)");
+    expect(explanation).toContain("- ✅ **bug-reports** (HIGH, -20): bugReports = 1 > 0 → `fix_issues`");
+    expect(explanation).toContain("- ⏭️ **failing-ci**: not evaluated, failedCiRuns missing");
+    expect(explanation).toContain("- ⏭️ **security-issues**: not evaluated, securityIssues missing");
+  });
+});
+
+describe("parseSummary", () => {
+  it("reads back the metric lines formatMetrics writes", () => {
+    const metrics = { openIssues: 4, bugReports: 1, testRatio: 0.125, lockfileAgeDays: 200 };
+    const summary = ["Repository: widgets", ...formatMetrics(metrics), "Overall Health: MODERATE"].join("\n");
+
+    expect(formatMetrics(metrics)).toEqual(["Open Issues: 4", "Bug Reports: 1", "Test Ratio: 0.13", "Lockfile Age (days): 200"]);
+    expect(parseSummary(summary)).toEqual({
+      metrics: { openIssues: 4, bugReports: 1, testRatio: 0.13, lockfileAgeDays: 200 },
+      text: "Repository: widgets\nOverall Health: MODERATE",
+    });
+    expect(parseSummary("- pending prs: 7\nBug reports everywhere").metrics).toEqual({ pendingPRs: 7 });
+  });
+});
+
+describe("loadHealthRules", () => {
+  it("uses the built-in rules without a rules file", () => {
+    expect(getHealthRulesPath()).toBe(path.join(fake.dir, "health-rules.yaml"));
+    expect(loadHealthRules()).toMatchObject({ source: "built-in", labels: { bugReports: ["bug"] } });
+  });
+
+  it("replaces the built-in rules with the rules file", () => {
+    writeRules([
+      "labels:",
+      "  bugReports: [defect]",
+      "rules:",
+      "  - id: slow-reviews",
+      "    description: Review queue",
+      "    when: { metric: pendingPRs, atLeast: 1 }",
+      "    severity: HIGH",
+      "    weight: 35",
+      "    tool: review_code",
+      "    arguments: { focus: [performance] }",
+      "",
+    ].join("\n"));
+
+    const rules = loadHealthRules();
+    const evaluation = evaluateHealthRules(rules, { metrics: { pendingPRs: 1, bugReports: 9 } });
+
+    expect(rules.source).toBe(getHealthRulesPath());
+    expect(rules.labels).toEqual({ securityIssues: ["security"], bugReports: ["defect"] });
+    expect(evaluation).toMatchObject({ score: 65, health: "NEEDS_ATTENTION" });
+    expect(evaluation.fired[0].rule).toMatchObject({ id: "slow-reviews", tool: "review_code", arguments: { focus: ["performance"] } });
+  });
+
+  it.each([
+    ["an unknown metric", "rules:\n  - id: a\n    description: A\n    when: { metric: stars, above: 1 }\n    severity: LOW\n", /rules\.0\.when/],
+    ["a metric without bounds", "rules:\n  - id: a\n    description: A\n    when: { metric: openIssues }\n    severity: LOW\n", /rules\.0\.when/],
+    ["an unknown tool", "rules:\n  - id: a\n    description: A\n    when: { mentions: [x] }\n    severity: LOW\n    tool: rm_rf\n", /rules\.0\.tool/],
+    ["a bad severity", "rules:\n  - id: a\n    description: A\n    when: { mentions: [x] }\n    severity: URGENT\n", /rules\.0\.severity/],
+    ["duplicate ids", "rules:\n  - id: a\n    description: A\n    when: { mentions: [x] }\n    severity: LOW\n  - id: a\n    description: B\n    when: { mentions: [y] }\n    severity: LOW\n", /rules\.1\.id: Duplicate rule id "a"/],
+    ["broken YAML", "rules: [\n", /Invalid health rules .*health-rules\.yaml/],
+  ])("rejects %s", (_name, yaml, message) => {
+    writeRules(yaml);
+
+    expect(() => loadHealthRules()).toThrow(message);
+  });
+});
@@ -0,0 +1,91 @@
+import * as fs from "fs";
+import * as os from "os";
+import * as path from "path";
+import { afterEach, beforeEach, describe, expect, it, vi } from "vitest";
+import {
+  findKestraRequest,
+  formatKestraResults,
+  getKestraResultsDir,
+  listKestraRequests,
+  loadKestraRequest,
+  MAX_KESTRA_REQUESTS,
+  recordKestraCallback,
+  trackKestraExecution,
+} from "../../src/lib/kestra-results";
+
+const saved = process.env.AGENTMESH_CONFIG;
+let dir: string;
+
+beforeEach(() => {
+  dir = fs.mkdtempSync(path.join(os.tmpdir(), "agentmesh-kestra-"));
+  process.env.AGENTMESH_CONFIG = path.join(dir, "config.json");
+});
+
+afterEach(() => {
+  vi.useRealTimers();
+  if (saved === undefined) delete process.env.AGENTMESH_CONFIG;
+  else process.env.AGENTMESH_CONFIG = saved;
+});
+
+const repoUrl = "https://github.com/acme/widgets";
+
+describe("kestra results", () => {
+  it("files callbacks under the request that started the execution", () => {
+    const request = trackKestraExecution({ action: "analyze-repo", repoUrl, executionId: "exec1", namespace: "agentmesh", flowId: "github_repo_analysis" });
+
+    const updated = recordKestraCallback({ executionId: "exec1", taskId: "report", state: "SUCCESS", results: { health: 72 } });
+
+    expect(updated?.id).toBe(request.id);
+    expect(fs.existsSync(path.join(getKestraResultsDir(), `${request.id}.json`))).toBe(true);
+    expect(loadKestraRequest(request.id)?.callbacks).toMatchObject([{ executionId: "exec1", taskId: "report", results: { health: 72 } }]);
+    expect(findKestraRequest({ repoUrl })?.id).toBe(request.id);
+
+    const text = formatKestraResults(loadKestraRequest(request.id)!);
+    expect(text).toContain(`Request \`${request.id}\` (analyze-repo ${repoUrl}, started `);
+    expect(text).toContain("Execution: `exec1` (agentmesh.github_repo_analysis)");
+    expect(text).toContain("(report, SUCCESS)");
+    expect(text).toContain('"health": 72');
+  });
+
+  it("keeps a callback that arrives before the trigger response", () => {
+    const early = recordKestraCallback({ executionId: "exec2", results: { done: true } });
+    expect(early).toMatchObject({ action: "callback", executionId: "exec2" });
+
+    const request = trackKestraExecution({ action: "analyze-repo", repoUrl, executionId: "exec2" });
+
+    expect(request.id).toBe(early?.id);
+    expect(request.callbacks).toHaveLength(1);
+    expect(listKestraRequests()).toHaveLength(1);
+  });
+
+  it("finds scheduled runs by the repository URL they report", () => {
+    recordKestraCallback({ executionId: "cron1", results: { repoUrl: "https://github.com/acme/widgets.git", health: 80 } });
+
+    expect(findKestraRequest({ repoUrl })).toMatchObject({ action: "callback", repoUrl, executionId: "cron1" });
+    expect(findKestraRequest({ repoUrl: `${repoUrl}/` })?.executionId).toBe("cron1");
+    expect(findKestraRequest({ executionId: "cron1", repoUrl: `${repoUrl}.git` })?.executionId).toBe("cron1");
+  });
+
+  it("rejects unknown request ids and reports requests without results", () => {
+    expect(recordKestraCallback({ executionId: "exec3", results: {} }, "not-a-request")).toBeUndefined();
+    expect(loadKestraRequest("../config")).toBeUndefined();
+
+    const request = trackKestraExecution({ action: "analyze-repo", repoUrl, executionId: "exec3" });
+    expect(formatKestraResults(request)).toContain("⏳ The flow has not reported any results yet.");
+  });
+
+  it("keeps only the newest requests and their execution index entries", () => {
+    vi.useFakeTimers();
+    const first = Date.parse("2026-01-01T00:00:00.000Z");
+    for (let i = 0; i < MAX_KESTRA_REQUESTS + 5; i++) {
+      vi.setSystemTime(first + i * 3600_000);
+      recordKestraCallback({ executionId: `scheduled${i}`, results: { run: i } });
+    }
+
+    expect(listKestraRequests(Infinity)).toHaveLength(MAX_KESTRA_REQUESTS);
+    expect(fs.readdirSync(path.join(getKestraResultsDir(), "executions"))).toHaveLength(MAX_KESTRA_REQUESTS);
+    expect(findKestraRequest({ executionId: "scheduled4" })).toBeUndefined();
+    expect(findKestraRequest({ executionId: "scheduled5" })?.callbacks).toMatchObject([{ results: { run: 5 } }]);
+    expect(findKestraRequest({ executionId: `scheduled${MAX_KESTRA_REQUESTS + 4}` })).toBeDefined();
+  });
+});
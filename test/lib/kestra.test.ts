@@ -0,0 +1,122 @@
+import { afterEach, beforeEach, describe, expect, it } from "vitest";
+import { formatExecution, KestraClient, KestraError } from "../../src/lib/kestra";
+import { startKestraServer, type KestraServer } from "../helpers/kestra-server";
+
+let server: KestraServer | undefined;
+const ENV_KEYS = ["KESTRA_USERNAME", "KESTRA_PASSWORD", "KESTRA_TOKEN"];
+const savedEnv = ENV_KEYS.map((key) => process.env[key]);
+
+beforeEach(() => ENV_KEYS.forEach((key) => delete process.env[key]));
+
+afterEach(async () => {
+  await server?.close();
+  server = undefined;
+  ENV_KEYS.forEach((key, i) => {
+    if (savedEnv[i] === undefined) delete process.env[key];
+    else process.env[key] = savedEnv[i];
+  });
+});
+
+const basic = `Basic ${Buffer.from("admin:secret").toString("base64")}`;
+
+describe("KestraClient", () => {
+  it("authenticates with basic credentials from the config", async () => {
+    server = await startKestraServer({ authorization: basic });
+
+    const client = new KestraClient({ url: server.url, auth: { type: "basic", username: "admin", password: "secret" } });
+
+    expect(await client.ping()).toBe("online");
+    expect(server.requests[0].authorization).toBe(basic);
+  });
+
+  it("reads a bearer token from KESTRA_TOKEN", async () => {
+    server = await startKestraServer({ authorization: "Bearer t0ken" });
+    process.env.KESTRA_TOKEN = "t0ken";
+
+    expect(await new KestraClient({ url: server.url }).ping()).toBe("online");
+  });
+
+  it("reports rejected credentials and unreachable servers", async () => {
+    server = await startKestraServer({ authorization: basic });
+    const client = new KestraClient({ url: server.url });
+
+    expect(await client.ping()).toBe("unauthorized");
+    await expect(client.getExecution("exec1")).rejects.toMatchObject({ status: 401 });
+    await expect(client.getExecution("exec1")).rejects.toBeInstanceOf(KestraError);
+    expect(await new KestraClient({ url: "http://127.0.0.1:1" }).ping()).toBe("offline");
+  });
+
+  it("requires complete credentials for the configured auth type", async () => {
+    const client = new KestraClient({ url: "http://127.0.0.1:1", auth: { type: "bearer" } });
+
+    await expect(client.getExecution("exec1")).rejects.toThrow("Kestra bearer auth needs kestra.auth.token or KESTRA_TOKEN");
+  });
+
+  it("adds the tenant to API paths", () => {
+    expect(new KestraClient({ url: "http://kestra:8080/", tenant: "main" }).apiUrl("/flows"))
+      .toBe("http://kestra:8080/api/v1/main/flows");
+  });
+
+  it("waits for an execution to finish and collects its outputs and logs", async () => {
+    server = await startKestraServer({
+      polls: 2,
+      outputs: { status: "✅ Analysis completed" },
+      logs: [{ level: "INFO", taskId: "start_analysis", message: "Initiating analysis\n" }],
+    });
+    const client = new KestraClient({ url: server.url });
+    const started = await client.triggerWebhook("agentmesh", "github_repo_analysis", "hook", { owner: "acme" });
+
+    const result = await client.waitForExecution(started.id, { timeout: 5000, interval: 10 });
+
+    expect(result.finished).toBe(true);
+    expect(result.execution).toMatchObject({ state: "SUCCESS", outputs: { status: "✅ Analysis completed" } });
+    expect(server.requests.filter((r) => r.path === `/api/v1/executions/${started.id}`)).toHaveLength(3);
+    expect(JSON.parse(server.requests[0].body)).toEqual({ owner: "acme" });
+
+    const text = formatExecution(result);
+    expect(text).toContain("✅ `exec1` (agentmesh.github_repo_analysis): **SUCCESS**\nDuration: 12.5s\n");
+    expect(text).toContain('"status": "✅ Analysis completed"');
+    expect(text).toContain("INFO  [start_analysis] Initiating analysis");
+  });
+
+  it("stops waiting at the timeout", async () => {
+    server = await startKestraServer({ polls: 1000 });
+    const client = new KestraClient({ url: server.url });
+    const started = await client.triggerWebhook("agentmesh", "slow", "hook", {});
+
+    const result = await client.waitForExecution(started.id, { timeout: 50, interval: 10 });
+
+    expect(result.finished).toBe(false);
+    expect(formatExecution(result)).toContain("⏳ `exec1` (agentmesh.slow): **RUNNING**\nStill running when the wait timed out");
+  });
+
+  it("encodes the namespace, flow id and key of webhook paths", async () => {
+    server = await startKestraServer();
+    const client = new KestraClient({ url: server.url });
+
+    await client.triggerWebhook("agent mesh", "repo/scan", "k?ey", {});
+
+    expect(server.requests[0].path).toBe("/api/v1/executions/webhook/agent%20mesh/repo%2Fscan/k%3Fey");
+  });
+
+  it("validates, deploys, lists and deletes flows", async () => {
+    server = await startKestraServer();
+    const client = new KestraClient({ url: server.url });
+    const source = "id: nightly\nnamespace: agentmesh.ci\ndescription: Nightly build\ntasks:\n  - id: log\n    type: io.kestra.plugin.core.log.Log\n";
+
+    expect(await client.validateFlow("id: nightly\n")).toEqual(["namespace: must not be null", "tasks: must not be null"]);
+    expect(await client.validateFlow(source)).toEqual([]);
+
+    expect(await client.deployFlow("agentmesh.ci", "nightly", source)).toMatchObject({ created: true, flow: { id: "nightly", revision: 1 } });
+    expect(await client.deployFlow("agentmesh.ci", "nightly", source)).toMatchObject({ created: false, flow: { revision: 2 } });
+    expect(server.requests.find((r) => r.method === "PUT")?.path).toBe("/api/v1/flows/agentmesh.ci/nightly");
+
+    expect(await client.listFlows("agentmesh")).toEqual([
+      { id: "nightly", namespace: "agentmesh.ci", revision: 2, description: "Nightly build", disabled: false },
+    ]);
+
+    await client.deleteFlow("agentmesh.ci", "nightly");
+    expect(await client.getFlow("agentmesh.ci", "nightly")).toBeUndefined();
+    await expect(client.deleteFlow("agentmesh.ci", "nightly")).rejects.toMatchObject({ status: 404 });
+  });
+});
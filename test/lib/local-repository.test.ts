@@ -0,0 +1,80 @@
+import * as fs from "fs";
+import * as os from "os";
+import * as path from "path";
+import { afterEach, describe, expect, it } from "vitest";
+import { analyzeLocalRepository } from "../../src/lib/local-repository";
+import { createGitRepo, git } from "../helpers/git-repo";
+
+let dirs: string[] = [];
+
+afterEach(() => {
+  dirs.forEach((dir) => fs.rmSync(dir, { recursive: true, force: true }));
+  dirs = [];
+  delete process.env.GIT_AUTHOR_DATE;
+  delete process.env.GIT_COMMITTER_DATE;
+});
+
+/** Makes the git commands that follow commit at a fixed time */
+function commitDate(date: string) {
+  process.env.GIT_AUTHOR_DATE = date;
+  process.env.GIT_COMMITTER_DATE = date;
+}
+
+describe("analyzeLocalRepository", () => {
+  it("reads activity, markers, tests and lockfile age from git alone", async () => {
+    commitDate("2025-01-01T00:00:00Z");
+    const repo = createGitRepo({
+      "src/a.ts": "// TODO: split this up\n// FIXME: crashes on empty input\nexport const a = 1;\n",
+      "src/b.ts": "export const b = 2;\n",
+      "src/c.py": "c = 3\n",
+      "test/a.test.ts": "it('works', () => {});\n",
+      "node_modules/dep/index.js": "// TODO: vendored code is ignored\n",
+      "package-lock.json": "{}\n",
+    });
+    dirs.push(repo);
+    git(repo, "tag", "v1.0.0");
+    git(repo, "branch", "old-experiment");
+    commitDate("2025-11-20T00:00:00Z");
+    fs.writeFileSync(path.join(repo, "src", "b.ts"), "export const b = 3;\n");
+    git(repo, "-c", "user.name=Bob", "commit", "-qam", "update b");
+
+    const data = await analyzeLocalRepository(path.join(repo, "src"), Date.parse("2025-12-01T00:00:00Z"));
+
+    expect(data).toMatchObject({
+      root: repo,
+      name: path.basename(repo),
+      branch: "main",
+      language: "TypeScript",
+      commits: { total: 2, recent: 1 },
+      contributors: [{ name: "Bob", contributions: 1 }, { name: "Test", contributions: 1 }],
+      activeContributors: 1,
+      branches: { total: 2, stale: ["old-experiment"] },
+      tags: { total: 1, latest: { name: "v1.0.0" } },
+      markers: { todo: 1, fixme: 1 },
+      tests: { sourceFiles: 3, testFiles: 1 },
+      lockfiles: [{ file: "package-lock.json", ageDays: 334 }],
+    });
+    expect(data.commits.lastCommitAt).toMatch(/^2025-11-20T/);
+  });
+
+  it("skips lockfiles that are tracked but not committed yet", async () => {
+    const repo = createGitRepo({ "package-lock.json": "{}\n" });
+    dirs.push(repo);
+    fs.writeFileSync(path.join(repo, "pnpm-lock.yaml"), "lockfileVersion: '9.0'\n");
+    git(repo, "add", "pnpm-lock.yaml");
+
+    const data = await analyzeLocalRepository(repo);
+
+    expect(data.lockfiles.map((l) => l.file)).toEqual(["package-lock.json"]);
+    expect(data.lockfiles[0].ageDays).not.toBeNaN();
+  });
+
+  it("refuses directories without git history", async () => {
+    const plain = fs.realpathSync(fs.mkdtempSync(path.join(os.tmpdir(), "agentmesh-plain-")));
+    dirs.push(plain);
+    await expect(analyzeLocalRepository(plain)).rejects.toThrow(`${plain} is not inside a git repository`);
+
+    git(plain, "init", "-q");
+    await expect(analyzeLocalRepository(plain)).rejects.toThrow(`${plain} has no commits yet`);
+  });
+});
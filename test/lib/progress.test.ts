@@ -0,0 +1,99 @@
+import { afterEach, describe, expect, it } from "vitest";
+import { z } from "zod";
+import * as agentWorkflow from "../../src/tools/agent-workflow";
+import * as codeTask from "../../src/tools/code-task";
+import type { AgentEvent } from "../../src/lib/agent";
+import { ProgressReporter, withProgress, type ToolExtra } from "../../src/lib/progress";
+import { emitAgentEvent, reportStep, withRunContext } from "../../src/lib/run-context";
+import { useFakeAgent, type FakeAgentHandle } from "../helpers/fake-agent";
+
+let fake: FakeAgentHandle;
+
+afterEach(() => fake?.restore());
+
+type Notification = Parameters<NonNullable<ToolExtra["sendNotification"]>>[0];
+
+function recordingExtra(progressToken?: string): { extra: ToolExtra; sent: Notification[] } {
+  const sent: Notification[] = [];
+  return {
+    sent,
+    extra: {
+      _meta: progressToken ? { progressToken } : undefined,
+      sendNotification: async (notification) => { sent.push(notification); },
+    },
+  };
+}
+
+describe("progress notifications", () => {
+  it("reports workflow steps and agent text with increasing progress", async () => {
+    fake = useFakeAgent({ fixture: "cline/completion.jsonl" });
+    const { extra, sent } = recordingExtra("tok-1");
+    const args = z.object(agentWorkflow.schema).parse({ workflow: "bug-fix", target: "crash" });
+
+    await agentWorkflow.default(args, extra);
+
+    const messages = sent.map((n) => n.params.message);
+    expect(sent.every((n) => n.method === "notifications/progress" && n.params.progressToken === "tok-1")).toBe(true);
+    expect(messages).toContain("Step 1/3: Analysis");
+    expect(messages).toContain("Step 3/3: Testing");
+    expect(messages).toContain("Step 2/3 (Fix): editedExistingFile src/index.ts");
+    expect(messages.some((m) => m?.startsWith("Step 1/3 (Analysis): I'll start by reading"))).toBe(true);
+
+    const progress = sent.map((n) => n.params.progress);
+    expect(progress).toEqual([...progress].sort((a, b) => a - b));
+    expect(new Set(progress).size).toBe(progress.length);
+    expect(sent.every((n) => n.params.total === 3)).toBe(true);
+  });
+
+  it("labels events of parallel steps with the step that produced them", async () => {
+    const { extra, sent } = recordingExtra("tok-3");
+    const lint = { index: 1, total: 2, name: "Lint" };
+    const types = { index: 2, total: 2, name: "Types" };
+    const command = (text: string) => ({ kind: "command", text, raw: {} } as AgentEvent);
+
+    await withProgress(extra, async () => {
+      reportStep(lint);
+      reportStep(types);
+      await Promise.all([
+        withRunContext({ step: lint }, async () => emitAgentEvent(command("pnpm lint"))),
+        withRunContext({ step: types }, async () => emitAgentEvent(command("pnpm tsc"))),
+      ]);
+    });
+
+    expect(sent.map((n) => n.params.message)).toEqual([
+      "Step 1/2: Lint",
+      "Step 2/2: Types",
+      "Step 1/2 (Lint): pnpm lint",
+      "Step 2/2 (Types): pnpm tsc",
+    ]);
+    const progress = sent.map((n) => n.params.progress);
+    expect(progress).toEqual([...progress].sort((a, b) => a - b));
+    expect(new Set(progress).size).toBe(progress.length);
+
+    // A step started after a later one does not move progress back
+    const reporter = new ProgressReporter(extra);
+    reporter.onStep(types);
+    reporter.onStep(lint);
+    expect(sent[sent.length - 1].params.progress).toBeGreaterThan(sent[sent.length - 2].params.progress);
+  });
+
+  it("stays silent without a progress token", async () => {
+    fake = useFakeAgent({ fixture: "cline/completion.jsonl" });
+    const { extra, sent } = recordingExtra();
+
+    await codeTask.default(z.object(codeTask.schema).parse({ prompt: "x" }), extra);
+
+    expect(sent).toEqual([]);
+  });
+
+  it("kills the agent when the client cancels the request", async () => {
+    fake = useFakeAgent({ fixture: "cline/completion.jsonl", sleepMs: 30000 });
+    const controller = new AbortController();
+    const { extra } = recordingExtra("tok-2");
+    setTimeout(() => controller.abort(), 500);
+
+    const text = await codeTask.default(z.object(codeTask.schema).parse({ prompt: "x" }), { ...extra, signal: controller.signal });
+
+    expect(text).toContain("Cline run was cancelled");
+  });
+});
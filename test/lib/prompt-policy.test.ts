@@ -0,0 +1,52 @@
+import { afterEach, describe, expect, it } from "vitest";
+import { applyPromptPolicy, PromptPolicyError } from "../../src/lib/prompt-policy";
+import { runAgentTask } from "../../src/lib/agent";
+import { useFakeAgent, type FakeAgentHandle } from "../helpers/fake-agent";
+
+let fake: FakeAgentHandle | undefined;
+
+afterEach(() => {
+  fake?.restore();
+  fake = undefined;
+});
+
+const policy = { maxLength: 50 };
+
+describe("applyPromptPolicy", () => {
+  it("preserves code, quotes, globs and punctuation", () => {
+    const prompt = 'Fix `parse("a:b")` in src/**/*.{ts,tsx} -- use $HOME & (a|b); {"k": [1]}';
+    expect(applyPromptPolicy(prompt, { maxLength: 1000 })).toBe(prompt);
+  });
+
+  it("keeps tabs and newlines and normalises CRLF", () => {
+    expect(applyPromptPolicy("a\r\n\tb\rc", policy)).toBe("a\n\tb\nc");
+  });
+
+  it("strips terminal escapes, NUL and bidi overrides", () => {
+    expect(applyPromptPolicy("red\u001b[31m\u0000 text‮evil⁦", policy)).toBe("red[31m textevil");
+  });
+
+  it("rejects empty and oversized prompts", () => {
+    expect(() => applyPromptPolicy(" \n\u0007", policy)).toThrow(PromptPolicyError);
+    expect(() => applyPromptPolicy("x".repeat(51), policy)).toThrow(/exceeds maximum length \(51 > 50/);
+  });
+});
+
+describe("prompt delivery", () => {
+  it("delivers the prompt to Cline on stdin, byte for byte", async () => {
+    fake = useFakeAgent({ fixture: "cline/completion.jsonl" });
+    const prompt = "Rename `foo()` to `bar()` in src/*.ts: keep \"quotes\", {braces} & 100% of behaviour.\nThanks!";
+
+    await runAgentTask(prompt);
+
+    expect(fake.lastCall().stdin).toBe(prompt);
+    expect(fake.lastCall().argv).not.toContain(prompt);
+  });
+
+  it("refuses prompts over the configured limit before spawning", async () => {
+    fake = useFakeAgent({ fixture: "cline/completion.jsonl" });
+
+    await expect(runAgentTask("x".repeat(100001))).rejects.toThrow(PromptPolicyError);
+    expect(fake.calls()).toHaveLength(0);
+  });
+});
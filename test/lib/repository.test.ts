@@ -0,0 +1,93 @@
+import { afterEach, describe, expect, it, vi } from "vitest";
+import { fetchRepository, parseRepositoryUrl, repositoryApiUrls } from "../../src/lib/repository";
+import { fixture, useFakeAgent, type FakeAgentHandle } from "../helpers/fake-agent";
+
+let fake: FakeAgentHandle | undefined;
+
+afterEach(() => {
+  fake?.restore();
+  fake = undefined;
+  vi.unstubAllGlobals();
+});
+
+describe("repository URLs", () => {
+  it("recognises GitHub, GitLab and Gitea web and clone URLs", () => {
+    fake = useFakeAgent({ config: { gitlab: { hosts: ["git.acme.dev"] }, gitea: { hosts: ["code.acme.dev:3000"] } } });
+
+    expect(parseRepositoryUrl("https://github.com/acme/widgets/pull/4")).toEqual({
+      forge: "github",
+      origin: "https://github.com",
+      owner: "acme",
+      name: "widgets",
+      path: "acme/widgets",
+      url: "https://github.com/acme/widgets",
+    });
+    expect(parseRepositoryUrl("git@gitlab.com:acme/platform/widgets.git")).toMatchObject({
+      forge: "gitlab", owner: "acme/platform", name: "widgets", path: "acme/platform/widgets",
+    });
+    expect(parseRepositoryUrl("https://git.acme.dev/acme/platform/widgets/-/merge_requests/8")).toMatchObject({
+      forge: "gitlab", origin: "https://git.acme.dev", path: "acme/platform/widgets",
+    });
+    expect(parseRepositoryUrl("http://code.acme.dev:3000/acme/widgets/issues/4")).toMatchObject({
+      forge: "gitea", origin: "http://code.acme.dev:3000", path: "acme/widgets",
+    });
+    expect(parseRepositoryUrl("https://codeberg.org/acme/widgets.git")?.forge).toBe("gitea");
+    expect(parseRepositoryUrl("https://forgejo.example.org/acme/widgets")?.forge).toBe("gitea");
+
+    expect(parseRepositoryUrl("https://example.com/acme/widgets")).toBeUndefined();
+    expect(parseRepositoryUrl("https://github.com/acme")).toBeUndefined();
+    expect(parseRepositoryUrl("not a url")).toBeUndefined();
+  });
+
+  it("points Kestra flows at each forge's API", () => {
+    const gitlab = parseRepositoryUrl("https://gitlab.com/acme/platform/widgets");
+    const gitea = parseRepositoryUrl("https://codeberg.org/acme/widgets");
+
+    expect(repositoryApiUrls(gitlab!).pullRequests).toBe("https://gitlab.com/api/v4/projects/acme%2Fplatform%2Fwidgets/merge_requests?state=opened");
+    expect(repositoryApiUrls(gitea!).issues).toBe("https://codeberg.org/api/v1/repos/acme/widgets/issues?state=open&type=issues");
+  });
+});
+
+describe("fetchRepository", () => {
+  it("reads the same data from GitLab and Gitea", async () => {
+    fake = useFakeAgent({ config: { gitlab: { fixtures: fixture("gitlab/recorded") }, gitea: { fixtures: fixture("gitea/recorded") } } });
+    vi.stubGlobal("fetch", vi.fn(async () => { throw new TypeError("fetch failed"); }));
+
+    const gitlab = await fetchRepository(parseRepositoryUrl("https://gitlab.com/acme/platform/widgets")!);
+    expect(gitlab.repo).toMatchObject({ owner: "acme/platform", fullName: "acme/platform/widgets", stars: 12, language: "Go" });
+    expect(gitlab.issues[1]).toEqual({
+      number: 2,
+      title: "Token leaked in logs",
+      state: "opened",
+      labels: ["security", "bug"],
+      author: "bob",
+      createdAt: "2026-09-02T00:00:00Z",
+      url: "https://gitlab.com/acme/platform/widgets/-/issues/2",
+    });
+    expect(gitlab.pullRequests.map((mr) => [mr.number, mr.draft])).toEqual([[7, true], [8, false]]);
+    expect(gitlab.pipelines?.map((p) => p.state)).toEqual(["failed", "failed", "success"]);
+    expect(gitlab.contributors).toEqual([{ name: "Alice", contributions: 80 }, { name: "Bob", contributions: 12 }]);
+
+    const gitea = await fetchRepository(parseRepositoryUrl("https://codeberg.org/acme/widgets")!);
+    expect(gitea.repo).toMatchObject({ fullName: "acme/widgets", description: undefined, language: "Rust" });
+    expect(gitea.issues[0].labels).toEqual(["Kind/Bug"]);
+    expect(gitea.pullRequests[0].draft).toBe(true);
+    expect(gitea.pipelines).toBeUndefined();
+    expect(gitea.contributors).toEqual([{ name: "alice", contributions: 2 }, { name: "Bob", contributions: 1 }]);
+  });
+
+  it("sends each forge's token", async () => {
+    fake = useFakeAgent({ config: { gitlab: { token: "glpat-1" }, gitea: { token: "gt-2" } } });
+    const fetchMock = vi.fn(async () => new Response(JSON.stringify({ message: "404 Project Not Found" }), { status: 404 }));
+    vi.stubGlobal("fetch", fetchMock);
+
+    await expect(fetchRepository(parseRepositoryUrl("https://gitlab.com/acme/gone")!))
+      .rejects.toThrow(/^GitLab GET \/projects\/acme%2Fgone.* failed \(404\): 404 Project Not Found$/);
+    await expect(fetchRepository(parseRepositoryUrl("https://codeberg.org/acme/gone")!)).rejects.toThrow("failed (404)");
+
+    const headers = (fetchMock.mock.calls as unknown as Array<[string, RequestInit]>)
+      .map(([url, init]) => [new URL(url).host, init.headers as Record<string, string>]);
+    expect(headers).toContainEqual(["gitlab.com", expect.objectContaining({ "PRIVATE-TOKEN": "glpat-1" })]);
+    expect(headers).toContainEqual(["codeberg.org", expect.objectContaining({ Authorization: "token gt-2" })]);
+  });
+});
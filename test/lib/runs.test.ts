@@ -0,0 +1,103 @@
+import * as fs from "fs";
+import * as os from "os";
+import * as path from "path";
+import { afterEach, beforeEach, describe, expect, it } from "vitest";
+import { createRun, finishRun, formatRunLine, getRunsDir, listRuns, loadRun, recordStep, runStatus } from "../../src/lib/runs";
+import type { StepReport } from "../../src/lib/workflow-runner";
+
+const saved = process.env.AGENTMESH_CONFIG;
+let dir: string;
+
+beforeEach(() => {
+  dir = fs.mkdtempSync(path.join(os.tmpdir(), "agentmesh-runs-"));
+  process.env.AGENTMESH_CONFIG = path.join(dir, "config.json");
+});
+
+afterEach(() => {
+  if (saved === undefined) delete process.env.AGENTMESH_CONFIG;
+  else process.env.AGENTMESH_CONFIG = saved;
+});
+
+function newRun(target = "login crash") {
+  return createRun({ workflow: "bug-fix", target, inputs: {}, options: {}, isolation: "none" });
+}
+
+function report(fields: Partial<StepReport> = {}): StepReport {
+  return {
+    step: { id: "fix", name: "Fix", prompt: "Fix {target}" },
+    index: 2,
+    status: "succeeded",
+    input: "Fix login crash",
+    output: "Fixed it",
+    changedFiles: ["src/login.ts"],
+    diff: "--- a/src/login.ts\n+++ b/src/login.ts",
+    attempts: 1,
+    startedAt: new Date("2026-01-01T10:00:00Z"),
+    finishedAt: new Date("2026-01-01T10:00:05Z"),
+    ...fields,
+  };
+}
+
+describe("run history", () => {
+  it("stores runs next to the config file and records each step", () => {
+    const run = newRun();
+    recordStep(run, report(), "abc123");
+
+    expect(getRunsDir()).toBe(path.join(dir, "runs"));
+    const stored = loadRun(run.id);
+    expect(stored).toMatchObject({ workflow: "bug-fix", status: "running" });
+    expect(stored?.steps).toEqual([{
+      id: "fix",
+      name: "Fix",
+      index: 2,
+      status: "succeeded",
+      input: "Fix login crash",
+      output: "Fixed it",
+      changedFiles: ["src/login.ts"],
+      diff: "--- a/src/login.ts\n+++ b/src/login.ts",
+      commit: "abc123",
+      attempts: 1,
+      startedAt: "2026-01-01T10:00:00.000Z",
+      finishedAt: "2026-01-01T10:00:05.000Z",
+      durationMs: 5000,
+    }]);
+  });
+
+  it("replaces the record of a step that runs again and keeps file order", () => {
+    const run = newRun();
+    recordStep(run, report({ status: "failed", error: "crashed" }));
+    recordStep(run, report({ step: { id: "plan", name: "Plan", prompt: "Plan" }, index: 1 }));
+    recordStep(run, report());
+
+    expect(loadRun(run.id)?.steps.map((s) => [s.id, s.status])).toEqual([["plan", "succeeded"], ["fix", "succeeded"]]);
+  });
+
+  it("marks runs finished, and unfinished runs of another process as interrupted", () => {
+    const run = newRun();
+    expect(runStatus(run)).toBe("running");
+    finishRun(run, "failed", "Fix failed");
+
+    expect(loadRun(run.id)).toMatchObject({ status: "failed", error: "Fix failed" });
+    expect(loadRun(run.id)?.finishedAt).toBeDefined();
+
+    const crashed = { ...run, id: "00000000-0000-4000-8000-000000000000", status: "running" as const };
+    expect(runStatus(crashed)).toBe("interrupted");
+    expect(formatRunLine(crashed)).toContain("⚠️ `00000000-0000-4000-8000-000000000000` bug-fix \"login crash\" (interrupted");
+  });
+
+  it("lists runs newest first", async () => {
+    const first = newRun("first");
+    await new Promise((resolve) => setTimeout(resolve, 5));
+    const second = newRun("second");
+
+    expect(listRuns().map((r) => r.id)).toEqual([second.id, first.id]);
+    expect(listRuns(1)).toHaveLength(1);
+  });
+
+  it("only loads ids in the format it creates", () => {
+    fs.writeFileSync(path.join(dir, "secret.json"), "{}");
+
+    expect(loadRun("../secret")).toBeUndefined();
+    expect(loadRun("12345678-1234-1234-1234-123456789abc")).toBeUndefined();
+  });
+});
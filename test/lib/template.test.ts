@@ -0,0 +1,59 @@
+import { afterEach, describe, expect, it } from "vitest";
+import { checkReference, renderTemplate, templateReferences, TemplateError, type TemplateContext } from "../../src/lib/template";
+
+const context = (overrides: Partial<TemplateContext> = {}): TemplateContext => ({
+  target: "src/app.ts",
+  inputs: { tone: "formal" },
+  steps: { plan: { status: "succeeded", output: "1. do {this}", changedFiles: ["a.ts", "b.ts"] } },
+  envAllowlist: ["AGENTMESH_TEST_TICKET"],
+  ...overrides,
+});
+
+afterEach(() => {
+  delete process.env.AGENTMESH_TEST_TICKET;
+});
+
+describe("renderTemplate", () => {
+  it("replaces every occurrence of each placeholder", () => {
+    expect(renderTemplate("{target} and {target} in a {inputs.tone} tone", context()))
+      .toBe("src/app.ts and src/app.ts in a formal tone");
+  });
+
+  it("inserts step outputs and changed files without re-rendering them", () => {
+    expect(renderTemplate("Plan: {steps.plan.output}\nFiles:\n{steps.plan.changedFiles}", context()))
+      .toBe("Plan: 1. do {this}\nFiles:\na.ts\nb.ts");
+  });
+
+  it("leaves other braces alone", () => {
+    const prompt = 'Keep {"k": 1}, {name} and function f() { return {target: 1}; }';
+    expect(renderTemplate(prompt, context())).toBe(prompt);
+  });
+
+  it("reads only allowlisted environment variables", () => {
+    process.env.AGENTMESH_TEST_TICKET = "OPS-7";
+
+    expect(renderTemplate("Ticket {env.AGENTMESH_TEST_TICKET}", context())).toBe("Ticket OPS-7");
+    expect(() => renderTemplate("{env.HOME}", context())).toThrow("{env.HOME}: HOME is not listed in templateEnv");
+  });
+
+  it("rejects steps that have not run and undeclared inputs", () => {
+    expect(() => renderTemplate("{steps.review.output}", context())).toThrow(TemplateError);
+    expect(() => renderTemplate("{inputs.missing}", context())).toThrow('input "missing" is not declared');
+  });
+});
+
+describe("checkReference", () => {
+  const check = (template: string) => checkReference(templateReferences(template)[0], ["plan"], ["tone"]);
+
+  it("accepts well-formed references", () => {
+    expect(check("{steps.plan.changedFiles}")).toBeUndefined();
+    expect(check("{inputs.tone}")).toBeUndefined();
+    expect(check("{env.ANYTHING}")).toBeUndefined();
+  });
+
+  it("explains malformed ones", () => {
+    expect(check("{steps.plan.result}")).toBe("{steps.plan.result}: use {steps.<id>.output|changedFiles|status}");
+    expect(check("{steps.later.output}")).toBe('{steps.later.output}: step "later" does not run before this one');
+    expect(check("{target.name}")).toBe("{target.name}: target has no fields");
+  });
+});
@@ -0,0 +1,80 @@
+import * as fs from "fs";
+import * as os from "os";
+import * as path from "path";
+import { afterEach, beforeEach, describe, expect, it } from "vitest";
+import { runCommand, runGate } from "../../src/lib/verify";
+import { useFakeAgent, type FakeAgentHandle } from "../helpers/fake-agent";
+
+// Passes once the agent has created fixed.txt
+const CHECK = `node -e "const ok = require('fs').existsSync('fixed.txt'); console.log(ok ? 'all good' : '1 test failed'); process.exit(ok ? 0 : 3)"`;
+
+let cwd: string;
+let fake: FakeAgentHandle | undefined;
+
+beforeEach(() => {
+  cwd = fs.realpathSync(fs.mkdtempSync(path.join(os.tmpdir(), "agentmesh-verify-")));
+});
+
+afterEach(() => {
+  fake?.restore();
+  fake = undefined;
+  fs.rmSync(cwd, { recursive: true, force: true });
+});
+
+describe("runCommand", () => {
+  it("captures exit code and combined output without throwing", async () => {
+    const result = await runCommand(`node -e "console.log('out'); console.error('err'); process.exit(2)"`, cwd);
+
+    expect(result).toMatchObject({ success: false, exitCode: 2, timedOut: false });
+    expect(result.output).toBe("out\nerr");
+  });
+
+  it("reports timeouts", async () => {
+    const result = await runCommand(`node -e "setTimeout(() => {}, 5000)"`, cwd, 200);
+
+    expect(result).toMatchObject({ success: false, timedOut: true });
+  });
+});
+
+describe("runGate", () => {
+  it("passes without calling the agent when the command succeeds", async () => {
+    fake = useFakeAgent({ workspaceRoots: [cwd] });
+    fs.writeFileSync(path.join(cwd, "fixed.txt"), "");
+
+    const gate = await runGate(CHECK, { cwd, fixAttempts: 2 });
+
+    expect(gate).toMatchObject({ success: true, fixes: 0, changedFiles: [] });
+    expect(gate.output).toMatch(/^`node .*` passed\n\nall good$/);
+    expect(fake.calls()).toHaveLength(0);
+  });
+
+  it("hands the failing output to the agent and runs the command again", async () => {
+    fake = useFakeAgent({ fixture: "cline/completion.jsonl", writes: { "fixed.txt": "" }, workspaceRoots: [cwd] });
+
+    const gate = await runGate(CHECK, { cwd, fixAttempts: 2, fixPrompt: "Make it green", trackChanges: true });
+
+    expect(gate).toMatchObject({ success: true, fixes: 1, changedFiles: ["fixed.txt"] });
+    expect(gate.output).toMatch(/passed after 1 fix attempt\n/);
+    const prompt = fake.lastPrompt();
+    expect(prompt).toMatch(/^Make it green\n\nCommand: node -e .*\nResult: `node -e .*` exited with code 3\n\nOutput:\n1 test failed$/);
+  });
+
+  it("fails with the last output once the fix attempts are used up", async () => {
+    fake = useFakeAgent({ fixture: "cline/completion.jsonl", workspaceRoots: [cwd] });
+
+    const gate = await runGate(CHECK, { cwd, fixAttempts: 2 });
+
+    expect(fake.calls()).toHaveLength(2);
+    expect(gate.success).toBe(false);
+    expect(gate.error).toMatch(/exited with code 3 after 2 fix attempts\n\n```\n1 test failed\n```$/);
+  });
+
+  it("stops when a fix run fails", async () => {
+    fake = useFakeAgent({ exitCode: 1, stderr: "agent crashed", workspaceRoots: [cwd] });
+
+    const gate = await runGate(CHECK, { cwd, fixAttempts: 3 });
+
+    expect(fake.calls()).toHaveLength(1);
+    expect(gate.error).toContain("after 1 fix attempt (fix attempt 1 failed: agent crashed)");
+  });
+});
@@ -0,0 +1,203 @@
+import { describe, expect, it } from "vitest";
+import { runWorkflow, type StepAttempt, type WorkflowRunOptions } from "../../src/lib/workflow-runner";
+import type { TemplateContext } from "../../src/lib/template";
+import type { WorkflowDefinition, WorkflowStep } from "../../src/lib/workflows";
+
+const sleep = (ms: number) => new Promise((resolve) => setTimeout(resolve, ms));
+
+function workflow(steps: Array<Partial<WorkflowStep> & { name: string }>, concurrency?: number): WorkflowDefinition {
+  return { name: "test", file: "test.yaml", concurrency, steps: steps.map((s) => ({ prompt: s.name, ...s })) };
+}
+
+function context(): TemplateContext {
+  return { target: "app", inputs: {}, steps: {}, envAllowlist: [] };
+}
+
+/** Runs steps with scripted attempts and records the order of events */
+function recorder(script: Record<string, Array<Partial<StepAttempt>>> = {}) {
+  const events: string[] = [];
+  const prompts: Record<string, string> = {};
+  const options: Omit<WorkflowRunOptions, "context"> = {
+    runStep: async (step, prompt, attempt) => {
+      events.push(`start ${step.name}`);
+      prompts[step.name] = prompt;
+      await sleep(10);
+      events.push(`end ${step.name}`);
+      return { success: true, output: `${step.name} done`, changedFiles: [], ...(script[step.name]?.[attempt - 1] ?? {}) };
+    },
+  };
+  return { events, prompts, options };
+}
+
+describe("runWorkflow", () => {
+  it("runs steps without dependsOn one after another", async () => {
+    const { events, options } = recorder();
+
+    const run = await runWorkflow(workflow([{ name: "A" }, { name: "B" }, { name: "C" }], 3), { ...options, context: context() });
+
+    expect(events).toEqual(["start A", "end A", "start B", "end B", "start C", "end C"]);
+    expect(run.completed).toBe(true);
+    expect(run.reports.map((r) => r.status)).toEqual(["succeeded", "succeeded", "succeeded"]);
+  });
+
+  it("runs independent branches in parallel up to the concurrency limit", async () => {
+    const steps = [
+      { name: "Impl" },
+      { name: "Docs", dependsOn: ["Impl"] },
+      { name: "Tests", dependsOn: ["Impl"] },
+      { name: "Lint", dependsOn: ["Impl"] },
+      { name: "Review", dependsOn: ["Docs", "Tests", "Lint"] },
+    ];
+    const { events, options } = recorder();
+
+    await runWorkflow(workflow(steps, 2), { ...options, context: context() });
+
+    expect(events.slice(0, 4)).toEqual(["start Impl", "end Impl", "start Docs", "start Tests"]);
+    expect(events.indexOf("start Lint")).toBeGreaterThan(events.indexOf("end Docs"));
+    expect(events[events.length - 2]).toBe("start Review");
+  });
+
+  it("skips steps whose condition is false, and their dependents", async () => {
+    const steps = [
+      { name: "Scan" },
+      { name: "Fix", when: 'steps.Scan.output contains "CRITICAL"' },
+      { name: "Verify" },
+      { name: "Report", dependsOn: ["Scan"], prompt: "Fix was {steps.Fix.status}", when: "!steps.Fix.changedFiles" },
+    ];
+    const { events, prompts, options } = recorder();
+
+    const run = await runWorkflow(workflow(steps), { ...options, context: context() });
+
+    expect(run.completed).toBe(true);
+    expect(run.reports.map((r) => [r.step.name, r.status, r.reason])).toEqual([
+      ["Scan", "succeeded", undefined],
+      ["Fix", "skipped", '`steps.Scan.output contains "CRITICAL"` is false'],
+      ["Verify", "skipped", "Fix was skipped"],
+      ["Report", "succeeded", undefined],
+    ]);
+    expect(events).not.toContain("start Fix");
+    expect(prompts.Report).toBe("Fix was skipped");
+  });
+
+  it("retries failed attempts", async () => {
+    const { events, options } = recorder({ Flaky: [{ success: false, error: "boom" }, { success: false }, {}] });
+
+    const run = await runWorkflow(workflow([{ name: "Flaky", retries: 2 }]), { ...options, context: context() });
+
+    expect(events.filter((e) => e === "start Flaky")).toHaveLength(3);
+    expect(run.reports[0]).toMatchObject({ status: "succeeded", attempts: 3 });
+  });
+
+  it("stops on failure unless continueOnError is set", async () => {
+    const script = { Lint: [{ success: false, error: "lint" }], Build: [{ success: false, error: "build" }] };
+    const steps = [
+      { name: "Lint", continueOnError: true },
+      { name: "Build", when: 'steps.Lint.status == "failed"' },
+      { name: "Deploy" },
+    ];
+    const { events, options } = recorder(script);
+
+    const run = await runWorkflow(workflow(steps), { ...options, context: context() });
+
+    expect(run.completed).toBe(false);
+    expect(run.stoppedAt).toMatchObject({ index: 2, error: "build" });
+    expect(run.reports.map((r) => r.status)).toEqual(["failed", "failed"]);
+    expect(events).not.toContain("start Deploy");
+  });
+
+  it("lets running branches finish after a failure and calls onStepEnd one at a time", async () => {
+    const ended: string[] = [];
+    let inside = 0;
+    const { options } = recorder({ A: [{ success: false }] });
+    const steps = [{ name: "A", dependsOn: [] }, { name: "B", dependsOn: [] }, { name: "C", dependsOn: ["B"] }];
+
+    const run = await runWorkflow(workflow(steps, 2), {
+      ...options,
+      context: context(),
+      onStepEnd: async (report) => {
+        expect(++inside).toBe(1);
+        await sleep(5);
+        ended.push(report.step.name);
+        inside--;
+      },
+    });
+
+    expect(ended.sort()).toEqual(["A", "B"]);
+    expect(run.completed).toBe(false);
+  });
+
+  describe("approval", () => {
+    it("waits for approval before dependents start", async () => {
+      const { events, options } = recorder();
+      const asked: string[] = [];
+
+      const run = await runWorkflow(workflow([{ name: "Plan", approval: true }, { name: "Build" }]), {
+        ...options,
+        context: context(),
+        approve: async (step, output) => {
+          asked.push(`${step.name}: ${output}`);
+          events.push("approved");
+          return { approved: true };
+        },
+      });
+
+      expect(asked).toEqual(["Plan: Plan done"]);
+      expect(events).toEqual(["start Plan", "end Plan", "approved", "start Build", "end Build"]);
+      expect(run.reports[0]).toMatchObject({ status: "succeeded", review: { approved: true, revisions: 0 } });
+    });
+
+    it("re-runs the step with the reviewer's feedback", async () => {
+      const { prompts, options } = recorder();
+      const decisions = [{ approved: false, feedback: "Split it into two PRs" }, { approved: true }];
+
+      const run = await runWorkflow(workflow([{ name: "Plan", prompt: "Plan {target}", approval: true }]), {
+        ...options,
+        context: context(),
+        approve: async () => decisions.shift()!,
+      });
+
+      expect(prompts.Plan).toBe(
+        "Plan app\n\nA reviewer rejected your previous answer:\n\nPlan done\n\n" +
+        "Reviewer feedback:\nSplit it into two PRs\n\nProduce a new answer that addresses the feedback."
+      );
+      expect(run.reports[0]).toMatchObject({ status: "succeeded", attempts: 2, review: { approved: true, revisions: 1 } });
+    });
+
+    it("stops on a rejection without feedback, even with continueOnError", async () => {
+      const { events, options } = recorder();
+      const steps = [{ name: "Plan", approval: true, continueOnError: true }, { name: "Build" }];
+
+      const run = await runWorkflow(workflow(steps), { ...options, context: context(), approve: async () => ({ approved: false }) });
+
+      expect(run.completed).toBe(false);
+      expect(run.stoppedAt).toMatchObject({ status: "failed", error: "Not approved (rejected by the reviewer)" });
+      expect(events).not.toContain("start Build");
+    });
+
+    it("does not approve when nobody can be asked", async () => {
+      const { options } = recorder();
+
+      const run = await runWorkflow(workflow([{ name: "Plan", approval: true }]), { ...options, context: context() });
+
+      expect(run.stoppedAt?.error).toBe("Not approved (no reviewer is available)");
+    });
+  });
+
+  it("reuses the outcome of steps that already succeeded", async () => {
+    const { events, prompts, options } = recorder();
+    const steps = [{ name: "Plan" }, { name: "Build", prompt: "Build {steps.Plan.output} ({steps.Plan.changedFiles})" }];
+
+    const run = await runWorkflow(workflow(steps), {
+      ...options,
+      context: context(),
+      reuse: { Plan: { status: "succeeded", output: "the plan", changedFiles: ["plan.md"] } },
+    });
+
+    expect(events).toEqual(["start Build", "end Build"]);
+    expect(prompts.Build).toBe("Build the plan (plan.md)");
+    expect(run.reports[0]).toMatchObject({ status: "succeeded", reused: true, attempts: 0, changedFiles: ["plan.md"] });
+    expect(run.reports[1].input).toBe("Build the plan (plan.md)");
+    expect(run.reports[1].reused).toBeUndefined();
+    expect(run.reports[1].startedAt).toBeInstanceOf(Date);
+  });
+});
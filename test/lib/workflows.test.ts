@@ -0,0 +1,77 @@
+import * as fs from "fs";
+import * as path from "path";
+import { afterEach, beforeEach, describe, expect, it } from "vitest";
+import { getUserWorkflowDir, getWorkflow, loadWorkflows, stepId } from "../../src/lib/workflows";
+import { useFakeAgent, type FakeAgentHandle } from "../helpers/fake-agent";
+
+let fake: FakeAgentHandle;
+
+beforeEach(() => {
+  fake = useFakeAgent();
+});
+
+afterEach(() => fake.restore());
+
+function writeWorkflow(file: string, yaml: string) {
+  fs.mkdirSync(getUserWorkflowDir(), { recursive: true });
+  fs.writeFileSync(path.join(getUserWorkflowDir(), file), yaml);
+}
+
+describe("loadWorkflows", () => {
+  it("ships the built-in workflows as files", () => {
+    const workflows = loadWorkflows();
+
+    expect(Object.keys(workflows).sort()).toEqual(["bug-fix", "full-feature", "refactor-deploy", "security-audit"]);
+    expect(workflows["bug-fix"].steps.map((s) => s.name)).toEqual(["Analysis", "Fix", "Testing"]);
+    expect(workflows["bug-fix"].steps[0]).toMatchObject({ mode: "plan", prompt: "Analyze and identify the root cause of: {target}" });
+  });
+
+  it("adds user workflows next to the config and lets them override built-ins", () => {
+    expect(getUserWorkflowDir()).toBe(path.join(fake.dir, "workflows"));
+    writeWorkflow("release.yml", "steps:\n  - name: Changelog\n    prompt: Write the changelog\n    timeout: 90\n");
+    writeWorkflow("bug-fix.yaml", "description: Just fix it\nsteps:\n  - name: Fix\n    prompt: 'Fix {target}'\n");
+
+    const workflows = loadWorkflows();
+
+    expect(workflows.release).toMatchObject({ name: "release", steps: [{ name: "Changelog", timeout: 90 }] });
+    expect(workflows["bug-fix"]).toMatchObject({ description: "Just fix it", steps: [{ name: "Fix" }] });
+  });
+
+  it("chains the built-in steps through earlier outputs", () => {
+    const [analysis, fix, testing] = loadWorkflows()["bug-fix"].steps;
+
+    expect(fix.prompt).toContain("{steps.Analysis.output}");
+    expect(testing.prompt).toContain("{steps.Fix.changedFiles}");
+    expect(stepId(analysis)).toBe("Analysis");
+  });
+
+  it.each([
+    ["unknown keys", "steps:\n  - name: A\n    prompt: B\n    shell: rm -rf /\n", /steps\.0: Unrecognized key/],
+    ["an empty step list", "steps: []\n", /steps: Array must contain at least 1/],
+    ["a bad mode", "steps:\n  - name: A\n    prompt: B\n    mode: yolo\n", /steps\.0\.mode/],
+    ["broken YAML", "steps: [\n", /Invalid workflow .*broken\.yaml/],
+    ["a reference to a later step", "steps:\n  - name: A\n    prompt: '{steps.B.output}'\n  - name: B\n    prompt: C\n", /steps\.0\.prompt: .*step "B" does not run before this one/],
+    ["a reference to a parallel step", "steps:\n  - name: A\n    prompt: B\n  - name: C\n    prompt: D\n    dependsOn: []\n  - name: E\n    prompt: '{steps.A.output}'\n    dependsOn: [C]\n", /steps\.2\.prompt: .*step "A" does not run before/],
+    ["a dependency on a later step", "steps:\n  - name: A\n    prompt: B\n    dependsOn: [C]\n  - name: C\n    prompt: D\n", /steps\.0\.dependsOn: "C" is not an earlier step/],
+    ["a step with both prompt and run", "steps:\n  - name: A\n    prompt: B\n    run: pnpm test\n", /steps\.0\.prompt: set either "prompt" or "run"/],
+    ["fix on a prompt step", "steps:\n  - name: A\n    prompt: B\n    fix: { attempts: 2 }\n", /steps\.0\.fix: fix only applies to run steps/],
+    ["a malformed condition", "steps:\n  - name: A\n    prompt: B\n    when: 'inputs.x =='\n", /steps\.0\.when: Unexpected end/],
+    ["an undeclared input", "steps:\n  - name: A\n    prompt: 'Fix {inputs.area}'\n", /input "area" is not declared/],
+    ["duplicate step ids", "steps:\n  - name: A\n    prompt: B\n  - id: A\n    name: C\n    prompt: D\n", /steps\.1\.id: Duplicate step id "A"/],
+  ])("rejects %s with the file name", (_, yaml, message) => {
+    writeWorkflow("broken.yaml", yaml);
+
+    expect(() => getWorkflow("broken")).toThrow(message);
+    expect(() => getWorkflow("broken")).toThrow(path.join(getUserWorkflowDir(), "broken.yaml"));
+  });
+
+  it("skips a broken file without affecting the other workflows", () => {
+    writeWorkflow("broken.yaml", "steps: [\n");
+    writeWorkflow("release.yaml", "steps:\n  - name: Changelog\n    prompt: Write the changelog\n");
+
+    expect(Object.keys(loadWorkflows())).toEqual(expect.arrayContaining(["bug-fix", "release"]));
+    expect(loadWorkflows().broken).toBeUndefined();
+    expect(getWorkflow("bug-fix")?.steps).toHaveLength(3);
+    expect(getWorkflow("release")?.steps).toHaveLength(1);
+  });
+});
@@ -0,0 +1,73 @@
+import * as fs from "fs";
+import * as os from "os";
+import * as path from "path";
+import { afterEach, beforeEach, describe, expect, it } from "vitest";
+import { resetConfig } from "../../src/lib/config";
+import { checkWorkspaceTarget, getWorkspaceRoots, resolveWorkspacePath, WorkspaceError } from "../../src/lib/workspace";
+import { useFakeAgent, type FakeAgentHandle } from "../helpers/fake-agent";
+
+let fake: FakeAgentHandle;
+let root: string;
+let outside: string;
+
+beforeEach(() => {
+  root = fs.realpathSync(fs.mkdtempSync(path.join(os.tmpdir(), "agentmesh-root-")));
+  outside = fs.realpathSync(fs.mkdtempSync(path.join(os.tmpdir(), "agentmesh-outside-")));
+  fs.mkdirSync(path.join(root, "src"));
+  fs.writeFileSync(path.join(root, "src", "index.ts"), "");
+  fake = useFakeAgent({ workspaceRoots: [root] });
+});
+
+afterEach(() => {
+  fake.restore();
+  fs.rmSync(root, { recursive: true, force: true });
+  fs.rmSync(outside, { recursive: true, force: true });
+});
+
+describe("resolveWorkspacePath", () => {
+  it("accepts the root, existing and not-yet-existing paths inside it", () => {
+    expect(resolveWorkspacePath(root)).toBe(root);
+    expect(resolveWorkspacePath("src/index.ts", root)).toBe(path.join(root, "src", "index.ts"));
+    expect(resolveWorkspacePath("new/dir/file.ts", root)).toBe(path.join(root, "new", "dir", "file.ts"));
+  });
+
+  it("rejects traversal and sibling directories sharing a prefix", () => {
+    expect(() => resolveWorkspacePath("../", root)).toThrow(WorkspaceError);
+    expect(() => resolveWorkspacePath(`${root}-evil`)).toThrow(WorkspaceError);
+  });
+
+  it("resolves symlinks before checking", () => {
+    fs.symlinkSync(outside, path.join(root, "escape"));
+
+    expect(() => resolveWorkspacePath("escape", root)).toThrow(/outside the allowed workspace roots/);
+    expect(() => resolveWorkspacePath("escape/missing.txt", root)).toThrow(WorkspaceError);
+  });
+
+  it("reads extra roots from AGENTMESH_WORKSPACE_ROOTS", () => {
+    fake.restore();
+    process.env.AGENTMESH_WORKSPACE_ROOTS = [root, outside].join(path.delimiter);
+    resetConfig();
+    try {
+      expect(getWorkspaceRoots()).toEqual([root, outside]);
+      expect(resolveWorkspacePath(outside)).toBe(outside);
+    } finally {
+      delete process.env.AGENTMESH_WORKSPACE_ROOTS;
+      fake = useFakeAgent({ workspaceRoots: [root] });
+    }
+  });
+});
+
+describe("checkWorkspaceTarget", () => {
+  it("lets free text, git refs and snippets through", () => {
+    expect(() => checkWorkspaceTarget("the login crash", root)).not.toThrow();
+    expect(() => checkWorkspaceTarget("HEAD~3..HEAD", root)).not.toThrow();
+    expect(() => checkWorkspaceTarget("function f() {\n  return 1;\n}", root)).not.toThrow();
+  });
+
+  it("checks paths and rejects ones outside the roots", () => {
+    expect(() => checkWorkspaceTarget("src/index.ts", root)).not.toThrow();
+    expect(() => checkWorkspaceTarget(outside, root)).toThrow(WorkspaceError);
+    expect(() => checkWorkspaceTarget("../../etc", root)).toThrow(WorkspaceError);
+    expect(() => checkWorkspaceTarget("src", outside)).toThrow(WorkspaceError);
+  });
+});
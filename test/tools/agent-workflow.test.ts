@@ -0,0 +1,433 @@
+import * as fs from "fs";
+import * as os from "os";
+import * as path from "path";
+import { afterEach, describe, expect, it, vi } from "vitest";
+import * as agentWorkflow from "../../src/tools/agent-workflow";
+import { LocalForge } from "../../src/lib/forge";
+import { callTool, useFakeAgent, type FakeAgentHandle } from "../helpers/fake-agent";
+import { createGitRepo, git } from "../helpers/git-repo";
+
+let fake: FakeAgentHandle;
+
+afterEach(() => fake?.restore());
+
+describe("agent_workflow", () => {
+  it("runs every step in order with its mode", async () => {
+    fake = useFakeAgent({ fixture: "cline/completion.jsonl" });
+
+    const text = await callTool(agentWorkflow, { workflow: "bug-fix", target: "login crash" });
+
+    const calls = fake.calls();
+    expect(calls).toHaveLength(3);
+    expect(calls[0].argv).toEqual(expect.arrayContaining(["-m", "plan"]));
+    expect(calls[0].stdin).toBe("Analyze and identify the root cause of: login crash");
+    expect(text).toContain("📋 Steps: Analysis → Fix → Testing");
+    expect(text).toContain("### Step 3/3: Testing");
+    expect(text).toContain('🎉 Workflow "bug-fix" completed!');
+  });
+
+  it("stops at the first failing step", async () => {
+    fake = useFakeAgent({ exitCode: 1, stderr: "crashed" });
+
+    const text = await callTool(agentWorkflow, { workflow: "security-audit", target: "src" });
+
+    expect(fake.calls()).toHaveLength(1);
+    expect(text).toContain("❌ Scanning failed: crashed");
+    expect(text).toContain("⚠️ Workflow stopped at step 1.");
+  });
+});
+
+describe("agent_workflow user-defined workflows", () => {
+  it("offers and runs workflows from .agentmesh/workflows with step timeouts", async () => {
+    fake = useFakeAgent({ fixture: "cline/completion.jsonl" });
+    fs.mkdirSync(path.join(fake.dir, "workflows"));
+    fs.writeFileSync(path.join(fake.dir, "workflows", "release.yaml"), [
+      "description: Changelog → Tag",
+      "steps:",
+      "  - name: Changelog",
+      "    prompt: 'Write the changelog for {target}'",
+      "    mode: plan",
+      "  - name: Tag",
+      "    prompt: Tag the release",
+      "    timeout: 0.3",
+    ].join("\n"));
+    // The description is built when the module loads
+    vi.resetModules();
+    const tool = await import("../../src/tools/agent-workflow");
+
+    expect(tool.metadata.description).toContain("- **release**: Changelog → Tag");
+    fake.set({ fixture: "cline/completion.jsonl", sleepMs: 2000 });
+    const text = await callTool(tool, { workflow: "release", target: "v2.0" });
+
+    expect(fake.calls()[0].stdin).toBe("Write the changelog for v2.0");
+    expect(text).toContain("✅ Changelog completed");
+    expect(text).toMatch(/❌ Tag failed: .*timed out/i);
+  });
+
+  it("reports a broken workflow file only when that workflow is called", async () => {
+    fake = useFakeAgent({ fixture: "cline/completion.jsonl" });
+    fs.mkdirSync(path.join(fake.dir, "workflows"));
+    const file = path.join(fake.dir, "workflows", "broken.yaml");
+    fs.writeFileSync(file, "steps: [unclosed");
+    vi.resetModules();
+    const tool = await import("../../src/tools/agent-workflow");
+
+    const text = await callTool(tool, { workflow: "broken", target: "src" });
+
+    expect(text).toMatch(/^❌ Could not load workflow "broken"\n\nError: /);
+    expect(text).toContain(file);
+    expect(fake.calls()).toHaveLength(0);
+    expect(tool.schema.workflow.options[0].options).not.toContain("broken");
+    expect(await callTool(tool, { workflow: "security-audit", target: "src" })).toContain('🎉 Workflow "security-audit" completed!');
+  });
+
+  it("lists the workflows found at startup and accepts ones added later by name", async () => {
+    fake = useFakeAgent({ fixture: "cline/completion.jsonl" });
+
+    expect(agentWorkflow.schema.workflow.options[0].options).toEqual(
+      expect.arrayContaining(["bug-fix", "full-feature", "refactor-deploy", "security-audit"])
+    );
+    expect(await callTool(agentWorkflow, { workflow: "nope", target: "src" })).toBe("❌ Unknown workflow: nope");
+    expect(agentWorkflow.schema.workflow.safeParse("../nope").success).toBe(false);
+  });
+});
+
+describe("agent_workflow templates", () => {
+  it("passes step outputs, changed files and inputs to later steps", async () => {
+    const cwd = fs.realpathSync(fs.mkdtempSync(path.join(os.tmpdir(), "agentmesh-workflow-")));
+    fake = useFakeAgent({ fixture: "cline/completion.jsonl", writes: { "notes.md": "draft\n" }, workspaceRoots: [cwd] });
+    fs.mkdirSync(path.join(fake.dir, "workflows"));
+    fs.writeFileSync(path.join(fake.dir, "workflows", "notes.yaml"), [
+      "inputs:",
+      "  tone: { default: formal }",
+      "  audience: {}",
+      "steps:",
+      "  - id: draft",
+      "    name: Draft",
+      "    prompt: 'Draft notes on {target} for {inputs.audience}'",
+      "  - name: Polish",
+      "    prompt: 'Polish {steps.draft.changedFiles} in a {inputs.tone} tone. Draft said: {steps.draft.output}'",
+    ].join("\n"));
+    vi.resetModules();
+    const tool = await import("../../src/tools/agent-workflow");
+
+    try {
+      const text = await callTool(tool, { workflow: "notes", target: "v2", workingDirectory: cwd, inputs: { audience: "users" } });
+
+      const [draft, polish] = fake.calls();
+      expect(draft.stdin).toBe("Draft notes on v2 for users");
+      expect(polish.stdin).toBe(
+        'Polish notes.md in a formal tone. Draft said: Fixed `parse()` so that {braces} and "quotes" survive.\nAll tests pass.'
+      );
+      expect(text).toContain('🎉 Workflow "notes" completed!');
+
+      expect(await callTool(tool, { workflow: "notes", target: "v2", workingDirectory: cwd }))
+        .toBe('❌ Missing input(s) for "notes": audience');
+      expect(await callTool(tool, { workflow: "notes", target: "v2", workingDirectory: cwd, inputs: { audience: "x", mood: "y" } }))
+        .toBe('❌ Unknown input(s) for "notes": mood');
+    } finally {
+      fs.rmSync(cwd, { recursive: true, force: true });
+    }
+  });
+});
+
+describe("agent_workflow step graph", () => {
+  it("does not track changed files of steps that ran in parallel", async () => {
+    const cwd = fs.realpathSync(fs.mkdtempSync(path.join(os.tmpdir(), "agentmesh-workflow-")));
+    fake = useFakeAgent({ fixture: "cline/completion.jsonl", sleepMs: 300, writes: { "notes.md": "draft\n" }, workspaceRoots: [cwd] });
+    fs.mkdirSync(path.join(fake.dir, "workflows"));
+    fs.writeFileSync(path.join(fake.dir, "workflows", "pair.yaml"), [
+      "concurrency: 2",
+      "steps:",
+      "  - name: Docs",
+      "    prompt: Write docs",
+      "  - name: Tests",
+      "    prompt: Write tests",
+      "    dependsOn: []",
+      "  - name: Review",
+      "    prompt: 'Review [{steps.Docs.changedFiles}] [{steps.Tests.changedFiles}]'",
+      "    dependsOn: [Docs, Tests]",
+    ].join("\n"));
+    vi.resetModules();
+    const tool = await import("../../src/tools/agent-workflow");
+
+    try {
+      const text = await callTool(tool, { workflow: "pair", target: "app", workingDirectory: cwd });
+
+      expect(fake.lastPrompt()).toBe("Review [] []");
+      expect(text).toMatch(/ℹ️ Changed files of (Docs, Tests|Tests, Docs) are not tracked because they ran next to other steps/);
+      expect(text).not.toMatch(/Changed files of .*Review/);
+    } finally {
+      fs.rmSync(cwd, { recursive: true, force: true });
+    }
+  });
+
+  it("reports skipped and tolerated steps and keeps going", async () => {
+    fake = useFakeAgent({ exitCode: 1, stderr: "lint errors" });
+    fs.mkdirSync(path.join(fake.dir, "workflows"));
+    fs.writeFileSync(path.join(fake.dir, "workflows", "checks.yaml"), [
+      "steps:",
+      "  - name: Lint",
+      "    prompt: 'Lint {target}'",
+      "    continueOnError: true",
+      "    retries: 1",
+      "  - name: Autofix",
+      "    prompt: Fix the lint errors",
+      "    dependsOn: [Lint]",
+      "    when: steps.Lint.status == 'succeeded'",
+    ].join("\n"));
+    vi.resetModules();
+    const tool = await import("../../src/tools/agent-workflow");
+
+    const text = await callTool(tool, { workflow: "checks", target: "src" });
+
+    expect(fake.calls()).toHaveLength(2);
+    expect(text).toContain("❌ Lint failed: lint errors\n↪️ Continuing (continueOnError)");
+    expect(text).toContain("### Step 2/2: Autofix\n⏭️ Autofix skipped: `steps.Lint.status == 'succeeded'` is false");
+    expect(text).not.toContain("⚠️ Workflow stopped");
+  });
+});
+
+describe("agent_workflow verification gates", () => {
+  it("gates on a command and loops failures back to the agent", async () => {
+    const cwd = fs.realpathSync(fs.mkdtempSync(path.join(os.tmpdir(), "agentmesh-gate-")));
+    fake = useFakeAgent({ fixture: "cline/completion.jsonl", writes: { "fixed.txt": "" }, workspaceRoots: [cwd] });
+    fs.mkdirSync(path.join(fake.dir, "workflows"));
+    fs.writeFileSync(path.join(fake.dir, "workflows", "ship.yaml"), [
+      "steps:",
+      "  - name: Tests",
+      "    run: test -f fixed.txt || (echo 'missing fixed.txt' && exit 1)",
+      "    fix: { prompt: 'Fix {target} so the tests pass', attempts: 2 }",
+      "  - name: Typecheck",
+      "    run: exit 4",
+    ].join("\n"));
+    vi.resetModules();
+    const tool = await import("../../src/tools/agent-workflow");
+
+    try {
+      const text = await callTool(tool, { workflow: "ship", target: "the parser", workingDirectory: cwd });
+
+      expect(fake.calls()).toHaveLength(1);
+      expect(fake.lastPrompt()).toContain("Fix the parser so the tests pass\n\nCommand: test -f fixed.txt");
+      expect(fake.lastPrompt()).toContain("Output:\nmissing fixed.txt");
+      expect(text).toContain("✅ Tests completed\n`test -f fixed.txt || (echo 'missing fixed.txt' && exit 1)` passed after 1 fix attempt");
+      expect(text).toContain("❌ Typecheck failed: `exit 4` exited with code 4");
+      expect(text).toContain("⚠️ Workflow stopped at step 2.");
+    } finally {
+      fs.rmSync(cwd, { recursive: true, force: true });
+    }
+  });
+});
+
+describe("resume_workflow", () => {
+  it("records the run and resumes it from the failed step", async () => {
+    const cwd = fs.realpathSync(fs.mkdtempSync(path.join(os.tmpdir(), "agentmesh-resume-")));
+    fake = useFakeAgent({ fixture: "cline/completion.jsonl", workspaceRoots: [cwd] });
+    fs.mkdirSync(path.join(fake.dir, "workflows"));
+    fs.writeFileSync(path.join(fake.dir, "workflows", "ship.yaml"), [
+      "steps:",
+      "  - id: plan",
+      "    name: Plan",
+      "    prompt: 'Plan {target}'",
+      "  - name: Check",
+      "    run: test -f ready.txt",
+      "  - name: Release",
+      "    prompt: 'Release after: {steps.plan.output}'",
+    ].join("\n"));
+    vi.resetModules();
+    const tool = await import("../../src/tools/agent-workflow");
+    const resume = await import("../../src/tools/resume-workflow");
+    const history = await import("../../src/resources/(runs)/history");
+    const { loadRun } = await import("../../src/lib/runs");
+
+    try {
+      const failed = await callTool(tool, { workflow: "ship", target: "v2", workingDirectory: cwd });
+      const runId = /🗂️ Run: `([^`]+)`/.exec(failed)![1];
+      expect(failed).toContain(`⚠️ Workflow stopped at step 2. Fix the issue and resume with \`resume_workflow\` (runId: "${runId}").`);
+      expect(loadRun(runId)?.status).toBe("failed");
+      expect(loadRun(runId)?.error).toContain("`test -f ready.txt` exited with code 1");
+      expect(loadRun(runId)?.steps.map((s) => `${s.id} ${s.status}`)).toEqual(["plan succeeded", "Check failed"]);
+
+      fs.writeFileSync(path.join(cwd, "ready.txt"), "");
+      const resumed = await callTool(resume, { runId });
+
+      expect(fake.calls()).toHaveLength(2);
+      expect(fake.lastPrompt()).toMatch(/^Release after: Fixed `parse\(\)`/);
+      expect(resumed).toContain(`(resuming \`${runId}\`)`);
+      expect(resumed).toContain("♻️ Plan reused from the previous run");
+      expect(resumed).toContain("✅ Check completed");
+      expect(resumed).toContain('🎉 Workflow "ship" completed!');
+
+      const runs = JSON.parse(history.default());
+      expect(runs).toHaveLength(2);
+      expect(runs[0]).toMatchObject({ resumedFrom: runId, status: "completed" });
+      expect(await callTool(resume, { runId: runs[0].id })).toContain("already completed; nothing to resume");
+      expect(await callTool(resume, { runId: "nope" })).toBe("❌ Unknown workflow run: nope");
+    } finally {
+      fs.rmSync(cwd, { recursive: true, force: true });
+    }
+  });
+});
+
+describe("agent_workflow git operations", () => {
+  let repo: string;
+
+  afterEach(() => fs.rmSync(repo, { recursive: true, force: true }));
+
+  function useRepoAgent(options: Parameters<typeof useFakeAgent>[0] = {}) {
+    repo = createGitRepo({ "app.ts": "export const ok = false;\n" });
+    fake = useFakeAgent({
+      fixture: "cline/completion.jsonl",
+      writes: { "app.ts": "export const ok = true;\n" },
+      workspaceRoots: [repo],
+      config: { forge: { type: "local", path: path.join(repo, ".git", "prs.json") } },
+      ...options,
+    });
+  }
+
+  it("commits step changes on a dedicated branch", async () => {
+    useRepoAgent();
+
+    const text = await callTool(agentWorkflow, {
+      workflow: "bug-fix", target: "login crash", workingDirectory: repo, options: { autoCommit: true, createPR: false },
+    });
+
+    const branch = git(repo, "rev-parse", "--abbrev-ref", "HEAD");
+    expect(branch).toMatch(/^agentmesh\/workflow-bug-fix-/);
+    expect(text).toContain(`🌿 Branch: \`${branch}\``);
+    expect(text).toContain(`↪️ Your checkout was switched to \`${branch}\`; run \`git checkout main\` to return to \`main\`.`);
+    const sha = git(repo, "rev-parse", "HEAD");
+    expect(text).toContain(`- ${sha.substring(0, 12)} Analysis`);
+    expect(git(repo, "log", "-1", "--format=%s")).toBe("bug-fix: Analysis - login crash");
+    expect(git(repo, "status", "--porcelain")).toBe("");
+  });
+
+  it("opens a pull request through the configured forge", async () => {
+    useRepoAgent();
+
+    const text = await callTool(agentWorkflow, {
+      workflow: "bug-fix", target: "login crash", workingDirectory: repo, options: { createPR: true },
+    });
+
+    const [pr] = new LocalForge({ type: "local", path: path.join(repo, ".git", "prs.json") }).list();
+    expect(pr).toMatchObject({ number: 1, base: "main", title: "bug-fix: login crash" });
+    expect(pr.head).toMatch(/^agentmesh\/workflow-bug-fix-/);
+    expect(pr.body).toContain("| Analysis |");
+    expect(text).toContain(`🔀 Pull request #1 opened on local: ${pr.url}`);
+  });
+
+  it("commits and opens a pull request by default once options are given", async () => {
+    useRepoAgent();
+
+    const text = await callTool(agentWorkflow, { workflow: "bug-fix", target: "login crash", workingDirectory: repo, options: {} });
+
+    expect(git(repo, "rev-parse", "--abbrev-ref", "HEAD")).toMatch(/^agentmesh\/workflow-bug-fix-/);
+    expect(text).toContain("🔀 Pull request #1 opened on local");
+  });
+
+  it("commits and opens the pull request from an isolated worktree", async () => {
+    useRepoAgent();
+
+    const text = await callTool(agentWorkflow, {
+      workflow: "bug-fix", target: "login crash", workingDirectory: repo, options: { createPR: true }, isolation: "worktree",
+    });
+
+    const [pr] = new LocalForge({ type: "local", path: path.join(repo, ".git", "prs.json") }).list();
+    expect(pr.head).toMatch(/^agentmesh\/workflow-bug-fix-[0-9a-f]+$/);
+    expect(git(repo, "rev-parse", "--abbrev-ref", "HEAD")).toBe("main");
+    expect(fs.readFileSync(path.join(repo, "app.ts"), "utf8")).toBe("export const ok = false;\n");
+    expect(text).toContain("🔀 Pull request #1 opened on local");
+    expect(text).not.toContain("Your checkout was switched");
+  });
+
+  it("skips the pull request when a step fails", async () => {
+    useRepoAgent({ exitCode: 1, stderr: "crashed" });
+
+    const text = await callTool(agentWorkflow, {
+      workflow: "bug-fix", target: "login crash", workingDirectory: repo, options: { createPR: true },
+    });
+
+    expect(git(repo, "log", "-1", "--format=%s")).toBe("bug-fix: Analysis (failed) - login crash");
+    expect(text).toContain("⚠️ Pull request skipped: the workflow did not complete.");
+    expect(text).toMatch(/❌ Workflow "bug-fix" stopped at step 1\.$/);
+    expect(text).not.toContain("🎉");
+  });
+
+  async function shipWorkflow() {
+    fs.mkdirSync(path.join(fake.dir, "workflows"));
+    fs.writeFileSync(path.join(fake.dir, "workflows", "ship.yaml"), [
+      "steps:",
+      "  - name: Edit",
+      "    prompt: 'Edit {target}'",
+      "  - name: Check",
+      "    run: echo partial > junk.txt && exit 1",
+    ].join("\n"));
+    vi.resetModules();
+    return import("../../src/tools/agent-workflow");
+  }
+
+  it("rolls back to the checkpoint before the failed step", async () => {
+    useRepoAgent();
+    const tool = await shipWorkflow();
+
+    const text = await callTool(tool, {
+      workflow: "ship", target: "app", workingDirectory: repo, options: { autoCommit: false, createPR: false, rollbackOnFailure: "lastGood" },
+    });
+
+    expect(text).toContain("↩️ Rolled back to the checkpoint before step 2 (Check)\n\n- 🗑️ junk.txt (added, removed)\n");
+    expect(fs.existsSync(path.join(repo, "junk.txt"))).toBe(false);
+    expect(text).toMatch(/↩️ Workflow "ship" stopped at step 2 and was rolled back\.$/);
+    expect(fs.readFileSync(path.join(repo, "app.ts"), "utf8")).toBe("export const ok = true;\n");
+    expect(git(repo, "for-each-ref", "--format=%(refname)", "refs/agentmesh/")).toMatch(/^refs\/agentmesh\/checkpoints\/[0-9a-f-]+\/discarded$/);
+  });
+
+  it("rolls back commits and files to the state before the workflow", async () => {
+    useRepoAgent();
+    const tool = await shipWorkflow();
+    const initial = git(repo, "rev-parse", "HEAD");
+
+    const text = await callTool(tool, {
+      workflow: "ship", target: "app", workingDirectory: repo, options: { createPR: false, rollbackOnFailure: "original" },
+    });
+
+    expect(text).toContain("↩️ Rolled back to the checkpoint before the workflow");
+    expect(text).toContain("- ↩️ app.ts (modified, reverted)");
+    expect(text).toMatch(/Commits undone:\n- [0-9a-f]+ ship: Check \(failed\) - app\n- [0-9a-f]+ ship: Edit - app\n/);
+    expect(text).toContain("No changes to commit.");
+    expect(git(repo, "rev-parse", "HEAD")).toBe(initial);
+    expect(git(repo, "rev-parse", "--abbrev-ref", "HEAD")).toMatch(/^agentmesh\/workflow-ship-/);
+    expect(git(repo, "status", "--porcelain")).toBe("");
+  });
+
+  it("runs parallel steps one at a time so each commit holds only its own files", async () => {
+    useRepoAgent();
+    fs.mkdirSync(path.join(fake.dir, "workflows"));
+    fs.writeFileSync(path.join(fake.dir, "workflows", "pair.yaml"), [
+      "concurrency: 2",
+      "steps:",
+      "  - name: Slow",
+      "    run: sleep 0.3 && echo slow > slow.txt",
+      "  - name: Fast",
+      "    run: echo fast > fast.txt",
+      "    dependsOn: []",
+    ].join("\n"));
+    vi.resetModules();
+    const tool = await import("../../src/tools/agent-workflow");
+
+    const text = await callTool(tool, { workflow: "pair", target: "app", workingDirectory: repo, options: { createPR: false } });
+
+    expect(text).toContain("ℹ️ Steps run one at a time because autoCommit is set");
+    expect(git(repo, "show", "--name-only", "--format=%s", "HEAD~1")).toBe("pair: Slow - app\n\nslow.txt");
+    expect(git(repo, "show", "--name-only", "--format=%s", "HEAD")).toBe("pair: Fast - app\n\nfast.txt");
+  });
+
+  it("refuses to commit over uncommitted work", async () => {
+    useRepoAgent();
+    fs.writeFileSync(path.join(repo, "app.ts"), "// mine\n");
+
+    await expect(callTool(agentWorkflow, {
+      workflow: "bug-fix", target: "login crash", workingDirectory: repo, options: { autoCommit: true },
+    })).rejects.toThrow(/clean working tree/);
+    expect(fake.calls()).toHaveLength(0);
+  });
+});
@@ -0,0 +1,26 @@
+import { afterEach, describe, expect, it } from "vitest";
+import * as clineStatus from "../../src/tools/cline-status";
+import { callTool, useFakeAgent, type FakeAgentHandle } from "../helpers/fake-agent";
+
+let fake: FakeAgentHandle;
+
+afterEach(() => fake?.restore());
+
+describe("cline_status", () => {
+  it("reports each configured backend", async () => {
+    fake = useFakeAgent({ extraBackends: { custom: { type: "command" } } });
+
+    const text = await callTool(clineStatus, {});
+
+    expect(text).toContain("✅ **cline** (cline, default) v0.0.0-fake is installed and ready!");
+    expect(text).toContain("✅ **custom** (command) v0.0.0-fake is installed and ready!");
+    expect(text).toContain("Default backend: cline");
+  });
+
+  it("filters to one backend", async () => {
+    fake = useFakeAgent();
+
+    expect(await callTool(clineStatus, { backend: "cline" })).not.toContain("aider");
+    expect(await callTool(clineStatus, { backend: "missing" })).toBe("❌ Unknown backend: missing");
+  });
+});
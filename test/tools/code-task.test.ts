@@ -0,0 +1,105 @@
+import * as fs from "fs";
+import * as os from "os";
+import * as path from "path";
+import { afterEach, describe, expect, it } from "vitest";
+import { PromptPolicyError } from "../../src/lib/prompt-policy";
+import * as codeTask from "../../src/tools/code-task";
+import { callTool, useFakeAgent, type FakeAgentHandle } from "../helpers/fake-agent";
+import { createGitRepo, git } from "../helpers/git-repo";
+
+let fake: FakeAgentHandle;
+
+afterEach(() => fake?.restore());
+
+describe("code_task", () => {
+  it("passes the prompt, mode and working directory through", async () => {
+    const cwd = fs.realpathSync(fs.mkdtempSync(path.join(os.tmpdir(), "agentmesh-task-")));
+    fake = useFakeAgent({ fixture: "cline/completion.jsonl", workspaceRoots: [cwd] });
+
+    const text = await callTool(codeTask, { prompt: "Add a CLI flag", mode: "plan", workingDirectory: cwd });
+
+    expect(text).toMatch(/^✅ Task completed successfully!/);
+    expect(text).toContain("All tests pass.");
+    expect(fake.lastPrompt()).toBe("Add a CLI flag");
+    expect(fake.lastCall().argv).toEqual(expect.arrayContaining(["-m", "plan"]));
+    expect(fake.lastCall().cwd).toBe(cwd);
+    fs.rmSync(cwd, { recursive: true, force: true });
+  });
+
+  it("lists the files the agent changed", async () => {
+    const repo = createGitRepo({ "index.ts": "export const v = 1;\n" });
+    fake = useFakeAgent({
+      fixture: "cline/completion.jsonl",
+      workspaceRoots: [repo],
+      writes: { "index.ts": "export const v = 2;\n", "cli.ts": "// new\n" },
+    });
+
+    const text = await callTool(codeTask, { prompt: "Add a CLI", workingDirectory: repo });
+
+    expect(text).toContain("### Changed Files (2)\n\n- ➕ cli.ts\n- ✏️ index.ts");
+    expect(text).toContain("-export const v = 1;\n+export const v = 2;");
+    fs.rmSync(repo, { recursive: true, force: true });
+  });
+
+  it("refuses working directories outside the workspace roots", async () => {
+    fake = useFakeAgent({ fixture: "cline/completion.jsonl" });
+
+    await expect(callTool(codeTask, { prompt: "Delete everything", workingDirectory: "/" }))
+      .rejects.toThrow(/outside the allowed workspace roots/);
+    expect(fake.calls()).toHaveLength(0);
+  });
+
+  it("reports failures with the error and partial output", async () => {
+    fake = useFakeAgent({ fixture: "cline/plan.jsonl", exitCode: 1, stderr: "boom" });
+
+    const text = await callTool(codeTask, { prompt: "Break things" });
+
+    expect(text).toMatch(/^❌ Task failed/);
+    expect(text).toContain("Error: boom");
+    expect(text).toContain("## Plan");
+  });
+
+  it("runs in an isolated worktree and leaves the checkout untouched", async () => {
+    const repo = createGitRepo({ "src/app.ts": "export const a = 1;\n" });
+    fs.writeFileSync(path.join(repo, "src/app.ts"), "// uncommitted work\n");
+    fake = useFakeAgent({
+      fixture: "cline/completion.jsonl",
+      workspaceRoots: [repo],
+      writes: { "app.ts": "export const a = 2;\n" },
+    });
+
+    const text = await callTool(codeTask, { prompt: "Bump a", workingDirectory: path.join(repo, "src"), isolation: "worktree" });
+
+    const branch = text.match(/Branch: `(agentmesh\/code-task-[0-9a-f]+)`/)?.[1];
+    expect(branch).toBeDefined();
+    expect(fake.lastCall().cwd).toMatch(/\.git\/agentmesh\/worktrees\/code-task-[0-9a-f]+\/src$/);
+    expect(text).toContain("+export const a = 2;");
+    expect(fs.readFileSync(path.join(repo, "src/app.ts"), "utf8")).toBe("// uncommitted work\n");
+    expect(git(repo, "show", `${branch}:src/app.ts`)).toBe("export const a = 2;");
+    fs.rmSync(repo, { recursive: true, force: true });
+  });
+
+  it("removes the worktree when the agent changes nothing", async () => {
+    const repo = createGitRepo();
+    fake = useFakeAgent({ fixture: "cline/completion.jsonl", workspaceRoots: [repo] });
+
+    const text = await callTool(codeTask, { prompt: "Look around", workingDirectory: repo, isolation: "worktree" });
+
+    expect(text).toContain("No files changed; the worktree was removed.");
+    expect(git(repo, "branch", "--list", "agentmesh/*")).toBe("");
+    fs.rmSync(repo, { recursive: true, force: true });
+  });
+
+  it("removes the worktree when the isolated run throws", async () => {
+    const repo = createGitRepo();
+    fake = useFakeAgent({ fixture: "cline/completion.jsonl", workspaceRoots: [repo] });
+
+    await expect(callTool(codeTask, { prompt: "   ", workingDirectory: repo, isolation: "worktree" }))
+      .rejects.toThrow(PromptPolicyError);
+
+    expect(fake.calls()).toHaveLength(0);
+    expect(git(repo, "branch", "--list", "agentmesh/*")).toBe("");
+    expect(git(repo, "worktree", "list").split("\n")).toHaveLength(1);
+    fs.rmSync(repo, { recursive: true, force: true });
+  });
+});
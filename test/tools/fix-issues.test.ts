@@ -0,0 +1,71 @@
+import * as fs from "fs";
+import * as os from "os";
+import * as path from "path";
+import { afterEach, describe, expect, it } from "vitest";
+import { WorkspaceError } from "../../src/lib/workspace";
+import * as fixIssues from "../../src/tools/fix-issues";
+import * as refactor from "../../src/tools/refactor";
+import { callTool, useFakeAgent, type FakeAgentHandle } from "../helpers/fake-agent";
+import { createGitRepo, git } from "../helpers/git-repo";
+
+let fake: FakeAgentHandle;
+
+afterEach(() => fake?.restore());
+
+describe("fix_issues", () => {
+  it("disables yolo mode for dry runs", async () => {
+    fake = useFakeAgent({ fixture: "cline/completion.jsonl" });
+
+    const text = await callTool(fixIssues, { dryRun: true, issueTypes: ["security"] });
+
+    expect(text).toMatch(/^🔧 Analysis Complete/);
+    expect(fake.lastCall().argv).not.toContain("-y");
+    expect(fake.lastPrompt()).toContain("DO NOT make changes.");
+    expect(fake.lastPrompt()).toContain("- security vulnerabilities");
+  });
+
+  it("fixes lint and type issues by default", async () => {
+    fake = useFakeAgent({ fixture: "cline/completion.jsonl" });
+
+    const text = await callTool(fixIssues, {});
+
+    expect(text).toMatch(/^🔧 Fix Complete/);
+    expect(fake.lastCall().argv).toContain("-y");
+    expect(fake.lastPrompt()).toContain("- linting issues (ESLint, Prettier, etc.)\n- TypeScript type errors");
+  });
+});
+
+describe("refactor", () => {
+  it("lists goals in the prompt and the response", async () => {
+    fake = useFakeAgent({ fixture: "cline/completion.jsonl" });
+
+    const text = await callTool(refactor, { target: "src/tools", goals: ["modularity"] });
+
+    expect(text).toMatch(/^♻️ Refactoring Complete\n\nGoals: modularity/);
+    expect(fake.lastPrompt()).toContain("- Improve modularity");
+  });
+
+  it("points absolute targets at the worktree copy in isolated runs", async () => {
+    const repo = createGitRepo({ "src/app.ts": "export const a = 1;\n" });
+    fake = useFakeAgent({ fixture: "cline/completion.jsonl", workspaceRoots: [repo] });
+
+    await callTool(refactor, { target: path.join(repo, "src/app.ts"), workingDirectory: repo, isolation: "worktree" });
+
+    expect(fake.lastPrompt()).toMatch(/^Refactor the code in .*\.git\/agentmesh\/worktrees\/refactor-[0-9a-f]+\/src\/app\.ts\./);
+    fs.rmSync(repo, { recursive: true, force: true });
+  });
+
+  it("refuses absolute targets outside the repository of an isolated run", async () => {
+    const repo = createGitRepo();
+    const other = fs.realpathSync(fs.mkdtempSync(path.join(os.tmpdir(), "agentmesh-other-")));
+    fake = useFakeAgent({ fixture: "cline/completion.jsonl", workspaceRoots: [repo, other] });
+
+    await expect(callTool(refactor, { target: other, workingDirectory: repo, isolation: "worktree" }))
+      .rejects.toThrow(WorkspaceError);
+
+    expect(fake.calls()).toHaveLength(0);
+    expect(git(repo, "branch", "--list", "agentmesh/*")).toBe("");
+    fs.rmSync(repo, { recursive: true, force: true });
+    fs.rmSync(other, { recursive: true, force: true });
+  });
+});
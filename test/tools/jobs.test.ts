@@ -0,0 +1,139 @@
+import * as fs from "fs";
+import * as path from "path";
+import { afterEach, describe, expect, it } from "vitest";
+import * as approveJob from "../../src/tools/approve-job";
+import * as cancelJob from "../../src/tools/cancel-job";
+import * as jobStatus from "../../src/tools/job-status";
+import * as listJobs from "../../src/tools/list-jobs";
+import * as startJob from "../../src/tools/start-job";
+import { getJob, latestJobText, waitForJob } from "../../src/lib/jobs";
+import { callTool, useFakeAgent, type FakeAgentHandle } from "../helpers/fake-agent";
+
+let fake: FakeAgentHandle;
+
+afterEach(() => fake?.restore());
+
+const sleep = (ms: number) => new Promise((resolve) => setTimeout(resolve, ms));
+
+async function start(tool: string, args: Record<string, unknown>): Promise<string> {
+  const text = await callTool(startJob, { tool, arguments: args });
+  const id = text.match(/Job started: `([^`]+)`/)?.[1];
+  if (!id) throw new Error(`no job id in: ${text}`);
+  return id;
+}
+
+async function until(check: () => boolean, timeoutMs = 5000): Promise<void> {
+  const deadline = Date.now() + timeoutMs;
+  while (!check()) {
+    if (Date.now() > deadline) throw new Error("condition not met in time");
+    await sleep(25);
+  }
+}
+
+describe("background jobs", () => {
+  it("runs a tool in the background and reports its final output", async () => {
+    fake = useFakeAgent({ fixture: "cline/completion.jsonl" });
+
+    const id = await start("code_task", { prompt: "Add logging" });
+    const text = await callTool(jobStatus, { jobId: id, waitMs: 10000 });
+
+    expect(text).toContain(`\`${id}\` code_task (completed`);
+    expect(text).toContain("## Output\n✅ Task completed successfully!");
+    expect(text).toContain("- tool_use src/index.ts:");
+    expect(await callTool(listJobs, { state: "completed" })).toContain(id);
+  });
+
+  it("exposes partial output while running", async () => {
+    fake = useFakeAgent({ fixture: "cline/completion.jsonl", lineDelayMs: 300 });
+
+    const id = await start("code_task", { prompt: "Slowly" });
+    await until(() => getJob(id)!.events.some((e) => e.kind === "text"));
+
+    expect(getJob(id)!.state).toBe("running");
+    expect(latestJobText(getJob(id)!)).toContain('"main" entry point');
+    expect(await callTool(jobStatus, { jobId: id })).toContain("## Latest Output");
+
+    await waitForJob(id, 10000);
+  });
+
+  it("cancels the agent process", async () => {
+    fake = useFakeAgent({ fixture: "cline/plan.jsonl", sleepMs: 30000 });
+
+    const id = await start("agent_workflow", { workflow: "bug-fix", target: "crash" });
+    await until(() => fake.calls().length === 1);
+
+    expect(await callTool(cancelJob, { jobId: id })).toMatch(/^🛑 Job cancelled/);
+    const job = await waitForJob(id, 5000);
+
+    expect(job!.state).toBe("cancelled");
+    expect(job!.finishedAt).toBeDefined();
+    expect(job!.output).toContain("run was cancelled");
+    expect(fake.calls()).toHaveLength(1);
+  });
+
+  it("rejects invalid arguments before starting", async () => {
+    const text = await callTool(startJob, { tool: "code_task", arguments: {} });
+
+    expect(text).toMatch(/^❌ Could not start job/);
+    expect(text).toContain("Invalid arguments for code_task");
+  });
+
+  it("reports unknown jobs", async () => {
+    expect(await callTool(jobStatus, { jobId: "nope" })).toBe("❌ Unknown job: nope");
+    expect(await callTool(cancelJob, { jobId: "nope" })).toBe("❌ Unknown job: nope");
+  });
+
+  it("parks workflows at approval gates until approve_job answers", async () => {
+    fake = useFakeAgent({ fixture: "cline/completion.jsonl" });
+    fs.mkdirSync(path.join(fake.dir, "workflows"));
+    fs.writeFileSync(path.join(fake.dir, "workflows", "bug-fix.yaml"), [
+      "steps:",
+      "  - name: Analysis",
+      "    prompt: 'Analyze {target}'",
+      "    approval: true",
+      "  - name: Fix",
+      "    prompt: 'Fix {target}'",
+    ].join("\n"));
+
+    const id = await start("agent_workflow", { workflow: "bug-fix", target: "crash" });
+    await until(() => getJob(id)!.state === "awaiting_approval");
+
+    const status = await callTool(jobStatus, { jobId: id });
+    expect(status).toContain("⏸️");
+    expect(status).toContain('## Awaiting Approval\nWorkflow "bug-fix" finished step "Analysis"');
+    expect(status).toContain("All tests pass.");
+    expect(await callTool(listJobs, { state: "awaiting_approval" })).toContain(id);
+
+    expect(await callTool(approveJob, { jobId: id, decision: "reject", feedback: "Check the null case" }))
+      .toMatch(/^↩️ Rejected "Analysis"; it will run again/);
+    await until(() => fake.calls().length === 2 && getJob(id)!.state === "awaiting_approval");
+    expect(fake.lastPrompt()).toContain("Reviewer feedback:\nCheck the null case");
+
+    expect(await callTool(approveJob, { jobId: id, decision: "approve" })).toMatch(/^✅ Approved "Analysis"/);
+    const job = await waitForJob(id, 10000);
+
+    expect(job!.state).toBe("completed");
+    expect(fake.calls()).toHaveLength(3);
+    expect(job!.output).toContain("👍 Approved after 1 revision(s)");
+    expect(await callTool(approveJob, { jobId: id, decision: "approve" })).toMatch(/^ℹ️ Job is not awaiting approval/);
+  });
+
+  it("stops the workflow when a direct call cannot ask for approval", async () => {
+    fake = useFakeAgent({ fixture: "cline/completion.jsonl" });
+    fs.mkdirSync(path.join(fake.dir, "workflows"));
+    fs.writeFileSync(path.join(fake.dir, "workflows", "bug-fix.yaml"), [
+      "steps:",
+      "  - name: Analysis",
+      "    prompt: 'Analyze {target}'",
+      "    approval: true",
+      "  - name: Fix",
+      "    prompt: 'Fix {target}'",
+    ].join("\n"));
+    const agentWorkflow = await import("../../src/tools/agent-workflow");
+
+    const text = await callTool(agentWorkflow, { workflow: "bug-fix", target: "crash" });
+
+    expect(fake.calls()).toHaveLength(1);
+    expect(text).toContain("🛑 Analysis stopped: Not approved (this client cannot be asked for approval");
+  });
+});
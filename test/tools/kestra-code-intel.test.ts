@@ -0,0 +1,360 @@
+import * as fs from "fs";
+import * as os from "os";
+import * as path from "path";
+import { afterEach, describe, expect, it, vi } from "vitest";
+import { z } from "zod";
+import { codeIntelResultSchema } from "../../src/lib/code-intel-result";
+import * as kestraCallback from "../../src/tools/kestra-callback";
+import * as kestraCodeIntel from "../../src/tools/kestra-code-intel";
+import { callTool, fixture, useFakeAgent, type FakeAgentHandle } from "../helpers/fake-agent";
+import { createGitRepo } from "../helpers/git-repo";
+import { startKestraServer, type KestraServer } from "../helpers/kestra-server";
+
+let fake: FakeAgentHandle | undefined;
+let kestra: KestraServer | undefined;
+const realFetch = globalThis.fetch;
+
+afterEach(async () => {
+  fake?.restore();
+  fake = undefined;
+  await kestra?.close();
+  kestra = undefined;
+  vi.unstubAllGlobals();
+});
+
+/** Serves GitHub from fixtures; other requests go to the mock Kestra server or fail */
+function stubGitHub() {
+  const json = (name: string) => new Response(fs.readFileSync(fixture(`github/${name}.json`), "utf8"));
+  vi.stubGlobal("fetch", vi.fn(async (url: string, init?: RequestInit) => {
+    if (kestra && url.startsWith(kestra.url)) return realFetch(url, init);
+    if (url.includes("/issues")) return json("issues");
+    if (url.includes("/pulls")) return json("pulls");
+    if (url.includes("/contributors")) return new Response("[]");
+    if (url.includes("/actions/runs")) return new Response(JSON.stringify({ total_count: 0, workflow_runs: [] }));
+    if (url.startsWith("https://api.github.com/repos/")) return json("repo");
+    throw new TypeError("fetch failed");
+  }));
+}
+
+type IntelArgs = z.input<z.ZodObject<typeof kestraCodeIntel.schema>>;
+
+/** Runs kestra_code_intel and checks its structured content, as a client receives it, against the output schema */
+async function runIntel(args: IntelArgs) {
+  const result = await callTool(kestraCodeIntel, args);
+  codeIntelResultSchema.parse(JSON.parse(JSON.stringify(result.structuredContent)));
+  return result;
+}
+
+/** The markdown view of a kestra_code_intel result */
+async function intel(args: IntelArgs): Promise<string> {
+  return (await runIntel(args)).content[0].text;
+}
+
+describe("kestra_code_intel", () => {
+  it("analyze-repo summarises GitHub data when Kestra is offline", async () => {
+    stubGitHub();
+
+    const text = await intel({ action: "analyze-repo", repoUrl: "https://github.com/acme/widgets" });
+
+    expect(text).toContain("⚠️ **Kestra Server**: Not running at http://localhost:8080");
+    expect(text).toContain("Repository: widgets");
+    expect(text).toContain("Overall Health: CRITICAL");
+    expect(text).toContain("🚨 **CRITICAL**: 1 security issue(s) → Action: `security_audit`");
+    expect(text).toContain("⚠️ **HIGH**: 1 bug(s) reported → Action: `fix_issues`");
+  });
+
+  it("analyze-repo reads GitHub from recorded fixtures", async () => {
+    fake = useFakeAgent();
+    process.env.AGENTMESH_GITHUB_FIXTURES = fixture("github/recorded");
+    vi.stubGlobal("fetch", vi.fn(async () => { throw new TypeError("fetch failed"); }));
+
+    try {
+      const text = await intel({ action: "analyze-repo", repoUrl: "https://github.com/acme/widgets.git" });
+
+      expect(text).toContain("Repository: widgets");
+      expect(text).toContain("Security Issues: 1\nBug Reports: 1\nPending PRs: 1\nFailed CI Runs: 1\nCI Runs: 2\nContributors: 3");
+    } finally {
+      delete process.env.AGENTMESH_GITHUB_FIXTURES;
+    }
+  });
+
+  it("analyze-repo reads self-hosted GitLab and Gitea repositories with the same priorities", async () => {
+    fake = useFakeAgent({
+      config: {
+        gitlab: { hosts: ["git.acme.dev"], fixtures: fixture("gitlab/recorded") },
+        gitea: { fixtures: fixture("gitea/recorded") },
+      },
+    });
+    vi.stubGlobal("fetch", vi.fn(async () => { throw new TypeError("fetch failed"); }));
+
+    const gitlab = await intel({ action: "analyze-repo", repoUrl: "https://gitlab.com/acme/platform/widgets/-/issues" });
+    expect(gitlab).toContain("## Repository Analysis (from GitLab API)");
+    expect(gitlab).toContain("Repository: widgets\nForge: GitLab\nLanguage: Go");
+    expect(gitlab).toContain("Pending PRs: 1\nFailed CI Runs: 2\nCI Runs: 3\nContributors: 2\nHealth Score: 20/100\nOverall Health: CRITICAL");
+    expect(gitlab).toContain("🚨 **CRITICAL**: 1 security issue(s) → Action: `security_audit`");
+    expect(gitlab).toContain("⚠️ **HIGH**: 2 bug(s) reported → Action: `fix_issues`");
+    expect(gitlab).toContain("⚠️ **HIGH**: 2 of the last 3 CI runs failed → Action: `fix_issues`");
+    expect(gitlab).toContain("- -40 **security-issues**: securityIssues = 1 > 0");
+
+    // Gitea hides CI when Actions are disabled; the analysis goes on without it
+    const gitea = await intel({ action: "analyze-repo", repoUrl: "git@codeberg.org:acme/widgets.git" });
+    expect(gitea).toContain("Forge: Gitea\nLanguage: Rust");
+    expect(gitea).toContain("Bug Reports: 1\nPending PRs: 0\nContributors: 2\nHealth Score: 80/100\nOverall Health: MODERATE");
+
+    expect(await intel({ action: "analyze-repo", repoUrl: "https://git.acme.dev/acme/gone" }))
+      .toMatch(/^❌ \*\*GitLab\*\*: No GitLab fixture for GET \/projects\/acme%2Fgone/);
+  });
+
+  it("analyze-repo explains GitHub errors instead of analysing them", async () => {
+    const reset = Math.floor(Date.now() / 1000) + 3600;
+    vi.stubGlobal("fetch", vi.fn(async () => new Response(JSON.stringify({ message: "API rate limit exceeded" }), {
+      status: 403,
+      headers: { "x-ratelimit-remaining": "0", "x-ratelimit-reset": String(reset) },
+    })));
+
+    expect(await intel({ action: "analyze-repo", repoUrl: "https://github.com/acme/widgets" }))
+      .toMatch(/^❌ \*\*GitHub\*\*: GitHub rate limit exceeded; it resets at /);
+    expect(await intel({ action: "analyze-repo", repoUrl: "https://example.com/acme/widgets" }))
+      .toBe("❌ https://example.com/acme/widgets is not a GitHub, GitLab or Gitea repository URL; add self-hosted instances to `gitlab.hosts` or `gitea.hosts` in the config");
+  });
+
+  it("analyze-repo waits for the Kestra execution and reports its outputs and logs", async () => {
+    fake = useFakeAgent();
+    kestra = await startKestraServer({
+      polls: 0,
+      outputs: { status: "✅ Analysis completed for acme/widgets" },
+      logs: [{ level: "INFO", taskId: "start_analysis", message: "Initiating multi-phase analysis..." }],
+    });
+    stubGitHub();
+
+    const text = await intel({ action: "analyze-repo", repoUrl: "https://github.com/acme/widgets", kestraUrl: kestra.url });
+
+    expect(text).toContain(`🚀 **Kestra Flow Triggered**: Execution ID \`exec1\``);
+    expect(text).toContain("✅ `exec1` (agentmesh.github_repo_analysis): **SUCCESS**");
+    expect(text).toContain('"status": "✅ Analysis completed for acme/widgets"');
+    expect(text).toContain("INFO  [start_analysis] Initiating multi-phase analysis...");
+    expect(text).toMatch(/🧾 Request ID: `[0-9a-f-]{36}`/);
+    expect(kestra.requests[1]).toMatchObject({ method: "POST", path: "/api/v1/executions/webhook/agentmesh/github_repo_analysis/agentmesh-github-analysis" });
+  });
+
+  it("returns every report as structured content matching the output schema", async () => {
+    fake = useFakeAgent();
+    kestra = await startKestraServer({ polls: 0, outputs: { status: "done" } });
+    stubGitHub();
+
+    const report = (await runIntel({ action: "analyze-repo", repoUrl: "https://github.com/acme/widgets", kestraUrl: kestra.url })).structuredContent;
+
+    expect(report).toMatchObject({
+      action: "analyze-repo",
+      ok: true,
+      repository: { source: "github", name: "widgets", language: "TypeScript", stars: 42 },
+      metrics: { openIssues: 5, securityIssues: 1, bugReports: 1 },
+      health: { level: "CRITICAL", source: "built-in" },
+      priorities: [
+        { level: "CRITICAL", item: "1 security issue(s)", action: "security_audit" },
+        { level: "HIGH", item: "1 bug(s) reported", action: "fix_issues" },
+      ],
+      kestra: { url: kestra.url, status: "online", executionId: "exec1", state: "SUCCESS", finished: true, outputs: { status: "done" } },
+    });
+    expect(report.kestra?.requestId).toMatch(/^[0-9a-f-]{36}$/);
+    expect(report.health?.rules.find((r) => r.id === "security-issues")).toMatchObject({ fired: true, contribution: 40 });
+
+    const decision = await runIntel({ action: "process-summary", summary: "Bug Reports: 2\nFailed CI Runs: 3\nCI Runs: 4" });
+    expect(decision.structuredContent).toMatchObject({
+      metrics: { bugReports: 2, failedCiRuns: 3, ciRuns: 4 },
+      health: { score: 60, level: "NEEDS_ATTENTION" },
+      actions: [{ rule: "bug-reports", severity: "HIGH", tool: "fix_issues", arguments: { target: "." } }],
+    });
+
+    const dryRun = await runIntel({ action: "execute-decision", summary: "Security Issues: 1", dryRun: true, workingDirectory: "." });
+    expect(dryRun.structuredContent.decision).toEqual({
+      rule: "security-issues",
+      severity: "CRITICAL",
+      description: "Open security issues",
+      tool: "security_audit",
+      arguments: { target: ".", workingDirectory: "." },
+      status: "dry-run",
+    });
+
+    expect((await runIntel({ action: "fetch-results", executionId: "exec9" })).structuredContent)
+      .toEqual({ action: "fetch-results", ok: false, error: "No Kestra results stored for execution exec9" });
+  });
+
+  it("stores results flows report through kestra_callback for fetch-results", async () => {
+    fake = useFakeAgent();
+    kestra = await startKestraServer({ polls: 1000 });
+    stubGitHub();
+    const repoUrl = "https://github.com/acme/widgets";
+
+    const report = await intel({ action: "analyze-repo", repoUrl, kestraUrl: kestra.url, waitTimeout: 0 });
+    const requestId = report.match(/Request ID: `([^`]+)`/)![1];
+    expect(report).toContain(`Use \`fetch-results\` with requestId \`${requestId}\``);
+    expect(await intel({ action: "fetch-results", requestId }))
+      .toContain("⏳ The flow has not reported any results yet.");
+
+    expect(await callTool(kestraCallback, { executionId: "exec1", taskId: "report_to_agentmesh", results: { owner: "acme", health: 72 } }))
+      .toBe(`✅ Stored results of Kestra execution \`exec1\` under request \`${requestId}\``);
+
+    for (const args of [{ requestId }, { executionId: "exec1" }, { repoUrl }]) {
+      const text = await intel({ action: "fetch-results", ...args });
+      expect(text).toContain(`Request \`${requestId}\` (analyze-repo ${repoUrl}`);
+      expect(text).toContain('"health": 72');
+    }
+    expect(await intel({ action: "fetch-results", executionId: "exec9" })).toBe("ℹ️ No Kestra results stored for execution exec9");
+    expect(await callTool(kestraCallback, { executionId: "exec1", results: {}, requestId: "nope" })).toBe("❌ Unknown Kestra request: nope");
+  });
+
+  it("analyze-repo reports when Kestra rejects the credentials", async () => {
+    kestra = await startKestraServer({ authorization: "Bearer right" });
+    stubGitHub();
+
+    const text = await intel({ action: "analyze-repo", repoUrl: "https://github.com/acme/widgets", kestraUrl: kestra.url });
+
+    expect(text).toContain("🔐 **Kestra**: Server running (auth required for API)");
+  });
+
+  it("analyze-local reports on a checkout without touching the network", async () => {
+    process.env.GIT_AUTHOR_DATE = process.env.GIT_COMMITTER_DATE = "2024-01-01T00:00:00Z";
+    const repo = createGitRepo({
+      "src/index.ts": "// FIXME: handle errors\nexport {};\n",
+      "src/util.ts": "export {};\n",
+      "yarn.lock": "# yarn lockfile v1\n",
+    });
+    delete process.env.GIT_AUTHOR_DATE;
+    delete process.env.GIT_COMMITTER_DATE;
+    const plain = fs.realpathSync(fs.mkdtempSync(path.join(os.tmpdir(), "agentmesh-plain-")));
+    fake = useFakeAgent({ workspaceRoots: [repo, plain] });
+    const fetchMock = vi.fn(async () => { throw new TypeError("fetch failed"); });
+    vi.stubGlobal("fetch", fetchMock);
+
+    try {
+      const text = await intel({ action: "analyze-local", workingDirectory: repo });
+
+      expect(text).toContain(`🔍 **Local Code Intelligence Report**: \`${repo}\``);
+      expect(text).toContain("## Repository Analysis (from local git history)");
+      expect(text).toContain("Last Commit: 2024-01-01");
+      expect(text).toContain("Commits: 1\nRecent Commits: 0");
+      expect(text).toContain("FIXME Markers: 1\nSource Files: 2\nTest Files: 0\nTest Ratio: 0");
+      expect(text).toContain("Health Score: 70/100\nOverall Health: NEEDS_ATTENTION");
+      expect(text).toContain("⚠️ **HIGH**: 1 FIXME marker(s) in the code → Action: `fix_issues`");
+      expect(text).toMatch(/📋 \*\*MEDIUM\*\*: Lockfile not updated in \d+ days → Action: `security_audit`/);
+      expect(text).toContain("📋 **MEDIUM**: 0 test file(s) for 2 source file(s) → Action: `generate_tests`");
+      expect(fetchMock).not.toHaveBeenCalled();
+
+      expect(await intel({ action: "analyze-local", workingDirectory: plain }))
+        .toBe(`❌ **Local analysis**: ${plain} is not inside a git repository`);
+    } finally {
+      fs.rmSync(repo, { recursive: true, force: true });
+      fs.rmSync(plain, { recursive: true, force: true });
+    }
+  });
+
+  it("requires repoUrl for repository actions", async () => {
+    expect(await intel({ action: "analyze-repo" })).toBe("❌ repoUrl is required for analyze-repo action");
+    expect(await intel({ action: "setup-workflow" })).toBe("❌ repoUrl is required for setup-workflow action");
+  });
+
+  it("setup-workflow renders a flow for the repository", async () => {
+    const text = await intel({ action: "setup-workflow", repoUrl: "https://github.com/acme/widgets" });
+
+    expect(text).toContain("uri: \"https://api.github.com/repos/acme/widgets/issues?state=open\"");
+    expect(text).toContain("defaults: \"http://localhost:3001/mcp\"");
+  });
+
+  it("setup-workflow reads issues and merge requests from GitLab", async () => {
+    const text = await intel({ action: "setup-workflow", repoUrl: "git@gitlab.com:acme/platform/widgets.git" });
+
+    expect(text).toContain("defaults: \"https://gitlab.com/acme/platform/widgets\"");
+    expect(text).toContain("uri: \"https://gitlab.com/api/v4/projects/acme%2Fplatform%2Fwidgets/merge_requests?state=opened\"");
+    expect(text).toContain("✅ Fetch GitLab issues and merge requests daily");
+  });
+
+  it("process-summary maps the summary to actions", async () => {
+    const text = await intel({ action: "process-summary", summary: "Security Issues: 1\nBug Reports: 1" });
+
+    expect(text).toContain("1. `security_audit`");
+    expect(text).toContain("2. `fix_issues`");
+    expect(text).toContain("## Rules (built-in)");
+  });
+
+  it("process-summary scores metric lines and does not match words inside other words", async () => {
+    const text = await intel({
+      action: "process-summary",
+      summary: "Repository: widgets\nPending PRs: 5\nOpen Issues: 2\nNeeds improvement in the parser",
+    });
+
+    expect(text).toContain("1. `review_code` — Pull requests awaiting review (MEDIUM)");
+    expect(text).toContain("- ✅ **review-backlog** (MEDIUM, -10): pendingPRs = 5 > 3 → `review_code`");
+    expect(text).toContain("- ▫️ **issue-backlog**: openIssues = 2 > 20");
+    expect(text).toContain("- ⏭️ **fixme-markers**: not evaluated, fixmeMarkers missing");
+    expect(text).toContain("Health Score: 90/100 (1 of 9 rules fired)");
+
+    expect(await intel({ action: "process-summary", summary: "Suggest an improvement to the docs" }))
+      .toMatch(/^✅ \*\*No immediate actions required\*\*/);
+  });
+
+  it("execute-decision runs the chosen action through the agent", async () => {
+    fake = useFakeAgent({ fixture: "cline/completion.jsonl" });
+
+    const text = await intel({ action: "execute-decision", summary: "Bug Reports: 3" });
+
+    expect(text).toContain("Action: Open bug reports (`fix_issues`)");
+    expect(text).toContain("All tests pass.");
+    expect(fake.lastPrompt()).toContain("Fix the following issues in .");
+  });
+
+  it("execute-decision explains the decision without running it on a dry run", async () => {
+    fake = useFakeAgent({ fixture: "cline/completion.jsonl" });
+
+    const text = await intel({ action: "execute-decision", summary: "Security Issues: 2\nBug Reports: 1", dryRun: true });
+
+    expect(text).toContain("🧪 **Dry Run**: execute-decision would run `security_audit` for rule `security-issues`");
+    expect(text).toContain("Reason: securityIssues = 2 > 0");
+    expect(text).toContain("- ✅ **bug-reports** (HIGH, -20)");
+    expect(fake.calls()).toHaveLength(0);
+
+    expect(await intel({ action: "execute-decision", summary: "All quiet" }))
+      .toMatch(/^ℹ️ \*\*No action to execute\*\*/);
+  });
+
+  it("deploy-workflow validates the flow, then creates or updates it", async () => {
+    kestra = await startKestraServer();
+    const args = { action: "deploy-workflow" as const, repoUrl: "https://github.com/acme/widgets", kestraUrl: kestra.url, namespace: "acme.ci" };
+
+    const created = await intel(args);
+    const updated = await intel(args);
+
+    expect(created).toContain("🚀 **Kestra Flow Created**: `acme.ci.agentmesh-code-intel` (revision 1)");
+    expect(updated).toContain("🚀 **Kestra Flow Updated**: `acme.ci.agentmesh-code-intel` (revision 2)");
+    expect(kestra.requests[0]).toMatchObject({ method: "POST", path: "/api/v1/flows/validate" });
+    expect(kestra.flows.get("acme.ci/agentmesh-code-intel")?.source).toContain("namespace: acme.ci");
+  });
+
+  it("list-flows and delete-flow manage the flows of a namespace", async () => {
+    kestra = await startKestraServer({
+      flows: [
+        { id: "github_repo_analysis", namespace: "agentmesh", description: "4-phase analysis\nof a repository", revision: 3 },
+        { id: "other", namespace: "elsewhere" },
+      ],
+    });
+    const kestraUrl = kestra.url;
+
+    expect(await intel({ action: "list-flows", kestraUrl }))
+      .toBe("📋 **Kestra Flows** in `agentmesh` (1)\n\n- `agentmesh.github_repo_analysis` (revision 3): 4-phase analysis");
+    expect(await intel({ action: "delete-flow", kestraUrl }))
+      .toBe("❌ flowId is required for delete-flow action");
+    expect(await intel({ action: "delete-flow", flowId: "github_repo_analysis", kestraUrl }))
+      .toBe("🗑️ **Kestra Flow Deleted**: `agentmesh.github_repo_analysis`");
+    expect(await intel({ action: "delete-flow", flowId: "github_repo_analysis", kestraUrl }))
+      .toBe("❌ Flow `agentmesh.github_repo_analysis` does not exist in Kestra");
+    expect(await intel({ action: "list-flows", kestraUrl })).toBe("ℹ️ No flows in namespace `agentmesh`");
+  });
+
+  it("flow actions report rejected credentials and unreachable servers", async () => {
+    kestra = await startKestraServer({ authorization: "Bearer right" });
+
+    expect(await intel({ action: "list-flows", kestraUrl: kestra.url })).toContain("🔐 **Kestra** rejected the request");
+    expect(await intel({ action: "list-flows", kestraUrl: "http://127.0.0.1:1" }))
+      .toBe("❌ Kestra is not reachable at http://127.0.0.1:1");
+  });
+});
@@ -0,0 +1,61 @@
+import * as fs from "fs";
+import * as path from "path";
+import { afterEach, beforeEach, describe, expect, it } from "vitest";
+import * as codeTask from "../../src/tools/code-task";
+import * as manageWorktree from "../../src/tools/manage-worktree";
+import { callTool, useFakeAgent, type FakeAgentHandle } from "../helpers/fake-agent";
+import { createGitRepo, git } from "../helpers/git-repo";
+
+let fake: FakeAgentHandle;
+let repo: string;
+
+beforeEach(() => {
+  repo = createGitRepo({ "notes.txt": "one\n" });
+  fake = useFakeAgent({ fixture: "cline/completion.jsonl", workspaceRoots: [repo], writes: { "notes.txt": "two\n" } });
+});
+
+afterEach(() => {
+  fake.restore();
+  fs.rmSync(repo, { recursive: true, force: true });
+});
+
+async function isolatedRun(): Promise<string> {
+  const text = await callTool(codeTask, { prompt: "Edit notes", workingDirectory: repo, isolation: "worktree" });
+  return text.match(/Branch: `([^`]+)`/)![1];
+}
+
+describe("manage_worktree", () => {
+  it("lists pending isolation branches", async () => {
+    const branch = await isolatedRun();
+
+    const text = await callTool(manageWorktree, { action: "list", workingDirectory: repo });
+
+    expect(text).toContain(`\`${branch}\``);
+  });
+
+  it("merges a branch into the checkout and cleans up", async () => {
+    const branch = await isolatedRun();
+
+    const text = await callTool(manageWorktree, { action: "merge", branch, workingDirectory: repo });
+
+    expect(text).toMatch(/^✅ Merged agentmesh\//);
+    expect(fs.readFileSync(path.join(repo, "notes.txt"), "utf8")).toBe("two\n");
+    expect(git(repo, "branch", "--list", branch)).toBe("");
+  });
+
+  it("discards a branch without touching the checkout", async () => {
+    const branch = await isolatedRun();
+
+    const text = await callTool(manageWorktree, { action: "discard", branch, workingDirectory: repo });
+
+    expect(text).toBe(`🗑️ Discarded ${branch}`);
+    expect(fs.readFileSync(path.join(repo, "notes.txt"), "utf8")).toBe("one\n");
+    expect(await callTool(manageWorktree, { action: "list", workingDirectory: repo })).toBe("📋 No isolation worktrees.");
+  });
+
+  it("refuses branches it did not create", async () => {
+    const text = await callTool(manageWorktree, { action: "discard", branch: "main", workingDirectory: repo });
+
+    expect(text).toContain('"main" is not an AgentMesh isolation branch');
+  });
+});
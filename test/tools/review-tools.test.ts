@@ -0,0 +1,81 @@
+import { afterEach, describe, expect, it } from "vitest";
+import * as explainCode from "../../src/tools/explain-code";
+import * as generateDocs from "../../src/tools/generate-docs";
+import * as generateTests from "../../src/tools/generate-tests";
+import * as gitAssist from "../../src/tools/git-assist";
+import * as reviewCode from "../../src/tools/review-code";
+import * as securityAudit from "../../src/tools/security-audit";
+import { callTool, useFakeAgent, type FakeAgentHandle } from "../helpers/fake-agent";
+
+let fake: FakeAgentHandle;
+
+afterEach(() => fake?.restore());
+
+describe("single-shot agent tools", () => {
+  it("review_code includes focus areas in the prompt", async () => {
+    fake = useFakeAgent({ fixture: "cline/completion.jsonl" });
+
+    const text = await callTool(reviewCode, { target: "src/lib", focusAreas: ["security", "performance"] });
+
+    expect(text).toMatch(/^📝 Code Review Complete/);
+    expect(fake.lastPrompt()).toContain("Review the code in src/lib.");
+    expect(fake.lastPrompt()).toContain("Focus especially on: security, performance");
+  });
+
+  it("refuses targets outside the workspace roots", async () => {
+    fake = useFakeAgent({ fixture: "cline/completion.jsonl" });
+
+    await expect(callTool(reviewCode, { target: "/etc/passwd" })).rejects.toThrow(/outside the allowed workspace roots/);
+    expect(fake.calls()).toHaveLength(0);
+  });
+
+  it("security_audit lists the requested scans", async () => {
+    fake = useFakeAgent({ fixture: "cline/completion.jsonl" });
+
+    const text = await callTool(securityAudit, { scanTypes: ["secrets", "licenses"] });
+
+    expect(text).toContain("Scanned: secrets, licenses");
+    expect(fake.lastPrompt()).toContain("- exposed secrets, API keys, passwords, tokens");
+    expect(fake.lastPrompt()).toContain("- dependency license compliance");
+  });
+
+  it("explain_code applies the default detail level", async () => {
+    fake = useFakeAgent({ fixture: "cline/plan.jsonl" });
+
+    const text = await callTool(explainCode, { target: "src/lib/cline.ts" });
+
+    expect(text).toMatch(/^📖 Code Explanation \(detailed\)/);
+    expect(fake.lastPrompt()).toContain("Provide a detailed explanation with key concepts.");
+  });
+
+  it("generate_docs and generate_tests describe the requested output", async () => {
+    fake = useFakeAgent({ fixture: "cline/completion.jsonl" });
+
+    expect(await callTool(generateDocs, { target: "src", docType: "api" })).toMatch(/^📚 Documentation Generated \(api\)/);
+    expect(fake.lastPrompt()).toContain("Generate API documentation");
+
+    expect(await callTool(generateTests, { target: "src", framework: "jest" })).toMatch(/^🧪 Tests Generated \(jest, unit\)/);
+    expect(fake.lastPrompt()).toContain("Use jest as the testing framework");
+    expect(fake.lastPrompt()).toContain("Target 80% code coverage");
+  });
+
+  it("git_assist picks the operation prompt", async () => {
+    fake = useFakeAgent({ fixture: "cline/completion.jsonl" });
+
+    const text = await callTool(gitAssist, { operation: "review-diff", target: "main..HEAD" });
+
+    expect(text).toMatch(/^🔀 Git review-diff Complete/);
+    expect(fake.lastPrompt()).toContain("Review the git diff for main..HEAD");
+  });
+
+  it("reports failures of every tool", async () => {
+    fake = useFakeAgent({ exitCode: 3, stderr: "no credentials" });
+
+    expect(await callTool(reviewCode, {})).toBe("❌ Review failed\n\nError: no credentials");
+    expect(await callTool(securityAudit, {})).toBe("❌ Security audit failed\n\nError: no credentials");
+    expect(await callTool(explainCode, { target: "x" })).toBe("❌ Explanation failed\n\nError: no credentials");
+    expect(await callTool(generateDocs, { target: "x" })).toBe("❌ Documentation generation failed\n\nError: no credentials");
+    expect(await callTool(generateTests, { target: "x" })).toBe("❌ Test generation failed\n\nError: no credentials");
+    expect(await callTool(gitAssist, { operation: "analyze-commits" })).toBe("❌ Git operation failed\n\nError: no credentials");
+  });
+});
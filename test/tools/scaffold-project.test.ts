@@ -0,0 +1,38 @@
+import { afterEach, describe, expect, it } from "vitest";
+import { WorkspaceError } from "../../src/lib/workspace";
+import * as scaffoldProject from "../../src/tools/scaffold-project";
+import { callTool, useFakeAgent, type FakeAgentHandle } from "../helpers/fake-agent";
+
+let fake: FakeAgentHandle;
+
+afterEach(() => fake?.restore());
+
+describe("scaffold_project", () => {
+  it("builds the prompt from the template and features", async () => {
+    fake = useFakeAgent({ fixture: "cline/completion.jsonl" });
+
+    const text = await callTool(scaffoldProject, { projectType: "express-api", name: "orders", features: ["auth", "docker"] });
+
+    expect(text).toMatch(/^🎉 Project "orders" scaffolded successfully!/);
+    expect(text).toContain("Features: auth, docker");
+    expect(text).toContain("1. cd orders");
+    expect(fake.lastPrompt()).toContain('Create a new project called "orders".');
+    expect(fake.lastPrompt()).toContain("Create a new Express.js API project");
+    expect(fake.lastPrompt()).toContain("- auth\n- docker");
+  });
+
+  it("reports failures", async () => {
+    fake = useFakeAgent({ exitCode: 1, stderr: "disk full" });
+
+    const text = await callTool(scaffoldProject, { projectType: "cli-tool", name: "tool" });
+
+    expect(text).toMatch(/^❌ Failed to scaffold project\n\nError: disk full/);
+  });
+
+  it.each(["../escape", "a/b", "a\\b", "..", "/tmp/x"])("rejects the project name %s", async (name) => {
+    fake = useFakeAgent({ fixture: "cline/completion.jsonl" });
+
+    await expect(callTool(scaffoldProject, { projectType: "cli-tool", name })).rejects.toThrow(WorkspaceError);
+    expect(fake.calls()).toHaveLength(0);
+  });
+});
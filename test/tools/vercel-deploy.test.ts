@@ -0,0 +1,69 @@
+import * as fs from "fs";
+import * as os from "os";
+import * as path from "path";
+import { afterEach, beforeEach, describe, expect, it } from "vitest";
+import * as vercelDeploy from "../../src/tools/vercel-deploy";
+import { resetConfig } from "../../src/lib/config";
+import { WorkspaceError } from "../../src/lib/workspace";
+import { callTool } from "../helpers/fake-agent";
+
+let binDir: string;
+let savedPath: string | undefined;
+let savedRoots: string | undefined;
+
+beforeEach(() => {
+  savedPath = process.env.PATH;
+  savedRoots = process.env.AGENTMESH_WORKSPACE_ROOTS;
+  binDir = fs.mkdtempSync(path.join(os.tmpdir(), "agentmesh-vercel-"));
+  process.env.AGENTMESH_WORKSPACE_ROOTS = binDir;
+  resetConfig();
+});
+
+afterEach(() => {
+  process.env.PATH = savedPath;
+  if (savedRoots === undefined) delete process.env.AGENTMESH_WORKSPACE_ROOTS;
+  else process.env.AGENTMESH_WORKSPACE_ROOTS = savedRoots;
+  resetConfig();
+  fs.rmSync(binDir, { recursive: true, force: true });
+});
+
+function fakeVercel(script: string) {
+  const bin = path.join(binDir, "vercel");
+  fs.writeFileSync(bin, `#!/bin/sh\n${script}\n`, { mode: 0o755 });
+  process.env.PATH = `${binDir}${path.delimiter}${savedPath}`;
+}
+
+describe("vercel_deploy", () => {
+  it("extracts the deployment URL", async () => {
+    fakeVercel('echo "Deploying $*"; echo "Preview: https://widgets-abc123.vercel.app"');
+
+    const text = await callTool(vercelDeploy, { action: "deploy", environment: "production", projectPath: binDir });
+
+    expect(text).toContain("URL: https://widgets-abc123.vercel.app");
+    expect(text).toContain("Deploying deploy --prod");
+  });
+
+  it("lists recent deployments", async () => {
+    fakeVercel(`echo '[{"url":"a.vercel.app","state":"READY","created":0}]'`);
+
+    const text = await callTool(vercelDeploy, { action: "status", projectPath: binDir });
+
+    expect(text).toContain("- a.vercel.app (READY)");
+  });
+
+  it("explains failures", async () => {
+    fakeVercel('echo "not logged in" >&2; exit 1');
+
+    const text = await callTool(vercelDeploy, { action: "promote", projectPath: binDir });
+
+    expect(text).toMatch(/^❌ Vercel action failed/);
+    expect(text).toContain("vercel login");
+  });
+
+  it("refuses project paths outside the workspace roots", async () => {
+    fakeVercel('echo "should not run"');
+
+    await expect(callTool(vercelDeploy, { action: "deploy", projectPath: path.join(binDir, "..") }))
+      .rejects.toThrow(WorkspaceError);
+  });
+});